@@ -0,0 +1,85 @@
+#ifndef RBO_H
+#define RBO_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The outcome of a call to the C interface
+ */
+typedef enum {
+  /**
+   * The result was written
+   */
+  RBO_STATUS_OK = 0,
+  /**
+   * The persistence is not 0 <= p < 1
+   */
+  RBO_STATUS_INVALID_PERSISTENCE = 1,
+  /**
+   * A list contains an item more than once
+   */
+  RBO_STATUS_DUPLICATES_IN_LIST = 2,
+  /**
+   * A list with items, one of its items or the result is a null pointer
+   */
+  RBO_STATUS_NULL_POINTER = 3,
+  /**
+   * Rounding errors made the result meaningless, e.g. for `p` close to 0
+   */
+  RBO_STATUS_NUMERICAL_INSTABILITY = 4,
+} RboStatus;
+
+/**
+ * The result of [`rbo`](crate::rbo) for C callers
+ */
+typedef struct {
+  /**
+   * the lower bound on RBO
+   */
+  double min;
+  /**
+   * the maximum amount RBO can grow beyond `min`
+   */
+  double residual;
+  /**
+   * the extrapolated RBO
+   */
+  double ext;
+} RboResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ *
+ * RBO between the `alen` strings of `a` and the `blen` strings of `b` with
+ * persistence `p`. Items are compared byte by byte, they need not be valid
+ * UTF-8. The result is written to `out` only if `RboStatus::Ok` is returned.
+ *
+ * # Safety
+ *
+ * - `a` and `b` must point to `alen` and `blen` pointers to nul-terminated
+ *   strings, or may be null for empty lists
+ * - `out` must be null or point to memory valid for writing an `RboResult`
+ * - none of them may be modified during the call
+ *
+ */
+RboStatus rbo_compute(const char *const *a,
+                      size_t alen,
+                      const char *const *b,
+                      size_t blen,
+                      double p,
+                      RboResult *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RBO_H */
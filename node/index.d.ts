@@ -0,0 +1,13 @@
+/** The result of RBO between two rankings */
+export interface RboResult {
+  /** the lower bound on RBO */
+  min: number
+  /** the maximum amount RBO can grow beyond `min` */
+  residual: number
+  /** the extrapolated RBO */
+  ext: number
+}
+/** RBO between the rankings `first` and `second` with persistence `p` */
+export function rbo(first: Array<string>, second: Array<string>, p: number): RboResult
+/** The extrapolated RBO between the rankings `first` and `second` with persistence `p` */
+export function rboExt(first: Array<string>, second: Array<string>, p: number): number
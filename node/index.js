@@ -0,0 +1,2 @@
+// the addon built by `npm run build`
+module.exports = require("./rbo.node");
@@ -0,0 +1,56 @@
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+//!
+//! Node.js bindings for the [`rbo`] crate, built with
+//! [napi-rs](https://napi.rs) into a native addon:
+//!
+//! ```js
+//! const { rbo, rboExt } = require("rbo-node");
+//!
+//! const result = rbo(["a", "b", "c"], ["b", "a", "d"], 0.9);
+//! console.log(result.min, result.residual, result.ext);
+//! ```
+//!
+//! Rankings are arrays of strings. Invalid persistence values and duplicate
+//! items throw an `Error`.
+
+use napi::{Error, Status};
+use napi_derive::napi;
+
+/// The result of [`rbo::rbo`] as a JavaScript object
+#[napi(object)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RboResult {
+    /// the lower bound on RBO
+    pub min: f64,
+    /// the maximum amount RBO can grow beyond `min`
+    pub residual: f64,
+    /// the extrapolated RBO
+    pub ext: f64,
+}
+
+impl From<rbo::Rbo> for RboResult {
+    fn from(rbo: rbo::Rbo) -> Self {
+        Self {
+            min: rbo.min,
+            residual: rbo.residual,
+            ext: rbo.extrapolated,
+        }
+    }
+}
+
+// RBO errors are invalid arguments on the JavaScript side
+fn compute(first: &[String], second: &[String], p: f64) -> napi::Result<rbo::Rbo> {
+    rbo::rbo(first, second, p).map_err(|err| Error::new(Status::InvalidArg, err.to_string()))
+}
+
+/// RBO between the rankings `first` and `second` with persistence `p`
+#[napi(js_name = "rbo")]
+pub fn js_rbo(first: Vec<String>, second: Vec<String>, p: f64) -> napi::Result<RboResult> {
+    Ok(compute(&first, &second, p)?.into())
+}
+
+/// The extrapolated RBO between the rankings `first` and `second` with persistence `p`
+#[napi(js_name = "rboExt")]
+pub fn js_rbo_ext(first: Vec<String>, second: Vec<String>, p: f64) -> napi::Result<f64> {
+    Ok(compute(&first, &second, p)?.extrapolated)
+}
@@ -0,0 +1,360 @@
+//! RBO over [Arrow](https://arrow.apache.org) columns of rankings.
+//!
+//! Polars, DataFusion and other Arrow based engines store a column of
+//! rankings as a `ListArray` (or `LargeListArray`) of strings or integers.
+//! The functions here compare two such columns row by row, reading the items
+//! straight from the Arrow buffers, and return the results as Arrow arrays.
+//!
+//! ```
+//! use arrow_array::builder::{ListBuilder, StringBuilder};
+//! use arrow_array::{Array, Float64Array};
+//!
+//! let column = |rows: &[&[&str]]| {
+//!     let mut builder = ListBuilder::new(StringBuilder::new());
+//!     for row in rows {
+//!         for item in *row {
+//!             builder.values().append_value(item);
+//!         }
+//!         builder.append(true);
+//!     }
+//!     builder.finish()
+//! };
+//! let first = column(&[&["a", "b", "c"], &["x", "y"]]);
+//! let second = column(&[&["a", "b", "c"], &["z"]]);
+//! let ext: Float64Array = rbo::arrow::rbo_ext_arrow(&first, &second, 0.9).expect("valid rbo");
+//! assert_eq!(ext.values().to_vec(), vec![1.0, 0.0]);
+//! ```
+
+use crate::{Duplicate, Rbo, RboEngine, RboError};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    ArrowPrimitiveType, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrayRef, Float64Array, GenericListArray, OffsetSizeTrait, StructArray};
+use arrow_schema::{ArrowError, DataType, Field, Fields};
+use std::hash::Hash;
+use std::ops::Range;
+use std::sync::Arc;
+
+// a column of rankings, with 32 or 64 bit offsets
+enum Rankings<'a> {
+    List(&'a GenericListArray<i32>),
+    LargeList(&'a GenericListArray<i64>),
+}
+
+impl<'a> Rankings<'a> {
+    fn new(column: &'a dyn Array) -> Result<Self, RboError> {
+        if let Some(list) = column.as_list_opt::<i32>() {
+            Ok(Self::List(list))
+        } else if let Some(list) = column.as_list_opt::<i64>() {
+            Ok(Self::LargeList(list))
+        } else {
+            Err(invalid(format!(
+                "expected a column of lists, got {}",
+                column.data_type()
+            )))
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::List(list) => list.len(),
+            Self::LargeList(list) => list.len(),
+        }
+    }
+
+    fn is_null(&self, row: usize) -> bool {
+        match self {
+            Self::List(list) => list.is_null(row),
+            Self::LargeList(list) => list.is_null(row),
+        }
+    }
+
+    // the items of all rankings
+    fn values(&self) -> &'a ArrayRef {
+        match self {
+            Self::List(list) => list.values(),
+            Self::LargeList(list) => list.values(),
+        }
+    }
+
+    // the positions of the items of `row` in `values`
+    fn range(&self, row: usize) -> Range<usize> {
+        fn range<O: OffsetSizeTrait>(list: &GenericListArray<O>, row: usize) -> Range<usize> {
+            let offsets = list.value_offsets();
+            offsets[row].as_usize()..offsets[row + 1].as_usize()
+        }
+        match self {
+            Self::List(list) => range(list, row),
+            Self::LargeList(list) => range(list, row),
+        }
+    }
+}
+
+fn invalid(message: String) -> RboError {
+    ArrowError::InvalidArgumentError(message).into()
+}
+
+///
+/// RBO between every row of the ranking columns `first` and `second`, which
+/// must be `List` or `LargeList` arrays of the same length, with items of the
+/// same string (`Utf8`, `LargeUtf8`) or integer type. Returns a struct array
+/// with the `Float64` fields `min`, `residual` and `extrapolated`, which is
+/// null where either ranking is null.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if the columns are not rankings of the same type and length
+/// - Will return `Err` if a ranking contains a null item
+/// - Will return `Err` if lists contain duplicate items. The reported list is
+///   `2 * i` for the first and `2 * i + 1` for the second ranking of row `i`.
+///
+pub fn rbo_arrow(first: &dyn Array, second: &dyn Array, p: f64) -> Result<StructArray, RboError> {
+    let results = rows(first, second, p)?;
+    let field = |extract: fn(&Rbo) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter(
+            results.iter().map(|rbo| rbo.as_ref().map(extract)),
+        ))
+    };
+    let fields: Fields = ["min", "residual", "extrapolated"]
+        .map(|name| Field::new(name, DataType::Float64, true))
+        .into_iter()
+        .collect();
+    let arrays = vec![
+        field(|rbo| rbo.min),
+        field(|rbo| rbo.residual),
+        field(|rbo| rbo.extrapolated),
+    ];
+    let nulls = results.iter().map(Option::is_some).collect();
+    Ok(StructArray::try_new(fields, arrays, Some(nulls))?)
+}
+
+///
+/// Like [`rbo_arrow`], but returns only the extrapolated RBO of every row
+///
+/// # Errors
+///
+/// See [`rbo_arrow`]
+///
+pub fn rbo_ext_arrow(
+    first: &dyn Array,
+    second: &dyn Array,
+    p: f64,
+) -> Result<Float64Array, RboError> {
+    let results = rows(first, second, p)?;
+    Ok(Float64Array::from_iter(
+        results.iter().map(|rbo| rbo.map(|rbo| rbo.extrapolated)),
+    ))
+}
+
+// RBO of every row, `None` for null rows
+fn rows(first: &dyn Array, second: &dyn Array, p: f64) -> Result<Vec<Option<Rbo>>, RboError> {
+    crate::state::validate_persistence(p)?;
+    let (first, second) = (Rankings::new(first)?, Rankings::new(second)?);
+    if first.len() != second.len() {
+        return Err(invalid(format!(
+            "ranking columns of different lengths {} and {}",
+            first.len(),
+            second.len()
+        )));
+    }
+    let (a, b) = (first.values(), second.values());
+    if a.data_type() != b.data_type() {
+        return Err(invalid(format!(
+            "rankings of different item types {} and {}",
+            a.data_type(),
+            b.data_type()
+        )));
+    }
+    match a.data_type() {
+        DataType::Utf8 => {
+            let (a, b) = (a.as_string::<i32>(), b.as_string::<i32>());
+            compare(&first, &second, p, |i| a.value(i), |i| b.value(i))
+        }
+        DataType::LargeUtf8 => {
+            let (a, b) = (a.as_string::<i64>(), b.as_string::<i64>());
+            compare(&first, &second, p, |i| a.value(i), |i| b.value(i))
+        }
+        DataType::Int8 => primitive::<Int8Type>(&first, &second, p),
+        DataType::Int16 => primitive::<Int16Type>(&first, &second, p),
+        DataType::Int32 => primitive::<Int32Type>(&first, &second, p),
+        DataType::Int64 => primitive::<Int64Type>(&first, &second, p),
+        DataType::UInt8 => primitive::<UInt8Type>(&first, &second, p),
+        DataType::UInt16 => primitive::<UInt16Type>(&first, &second, p),
+        DataType::UInt32 => primitive::<UInt32Type>(&first, &second, p),
+        DataType::UInt64 => primitive::<UInt64Type>(&first, &second, p),
+        other => Err(invalid(format!("unsupported ranking item type {other}"))),
+    }
+}
+
+fn primitive<T>(
+    first: &Rankings<'_>,
+    second: &Rankings<'_>,
+    p: f64,
+) -> Result<Vec<Option<Rbo>>, RboError>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Eq + Hash + std::fmt::Debug,
+{
+    let (a, b) = (
+        first.values().as_primitive::<T>(),
+        second.values().as_primitive::<T>(),
+    );
+    compare(first, second, p, |i| a.value(i), |i| b.value(i))
+}
+
+// RBO of every row, reading the items at a position of the first and second
+// column's values with `first_item` and `second_item`. The buffers and hash
+// tables are reused for all rows.
+fn compare<Item: Eq + Hash + Clone + std::fmt::Debug>(
+    first: &Rankings<'_>,
+    second: &Rankings<'_>,
+    p: f64,
+    first_item: impl Fn(usize) -> Item,
+    second_item: impl Fn(usize) -> Item,
+) -> Result<Vec<Option<Rbo>>, RboError> {
+    let mut engine = RboEngine::new();
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    let mut results = Vec::with_capacity(first.len());
+    for row in 0..first.len() {
+        if first.is_null(row) || second.is_null(row) {
+            results.push(None);
+            continue;
+        }
+        fill(&mut a, first, row, &first_item)?;
+        fill(&mut b, second, row, &second_item)?;
+        let rbo = engine.compute(&a, &b, p).map_err(|err| match err {
+            RboError::DuplicatesInList(duplicate) => {
+                let items = [&a, &b][duplicate.list];
+                let duplicate = duplicate.with_item_debug(items);
+                RboError::DuplicatesInList(Duplicate {
+                    list: 2 * row + duplicate.list,
+                    ..duplicate
+                })
+            }
+            err => err,
+        })?;
+        results.push(Some(rbo));
+    }
+    Ok(results)
+}
+
+// replace the contents of `buffer` with the items of `row`
+fn fill<Item>(
+    buffer: &mut Vec<Item>,
+    rankings: &Rankings<'_>,
+    row: usize,
+    item: impl Fn(usize) -> Item,
+) -> Result<(), RboError> {
+    let range = rankings.range(row);
+    let values = rankings.values();
+    if values.null_count() > 0 && range.clone().any(|i| values.is_null(i)) {
+        return Err(invalid(format!("null item in the ranking of row {row}")));
+    }
+    buffer.clear();
+    buffer.extend(range.map(item));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::builder::{Int64Builder, LargeListBuilder, ListBuilder, StringBuilder};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Float64Type;
+    use arrow_array::{Array, ListArray};
+
+    fn strings(rows: &[Option<&[&str]>]) -> ListArray {
+        let mut builder = ListBuilder::new(StringBuilder::new());
+        for row in rows {
+            for item in row.unwrap_or_default() {
+                builder.values().append_value(item);
+            }
+            builder.append(row.is_some());
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn matches_rbo_per_row() {
+        let rows: [&[&str]; 3] = [&["a", "b", "c", "d"], &["x"], &[]];
+        let other: [&[&str]; 3] = [&["b", "a", "e"], &["y", "x"], &["q"]];
+        let first = strings(&rows.map(Some));
+        let second = strings(&other.map(Some));
+        let results = super::rbo_arrow(&first, &second, 0.9).expect("valid rbo");
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.null_count(), 0);
+        for (i, (a, b)) in rows.iter().zip(&other).enumerate() {
+            let expected = crate::rbo(a, b, 0.9).expect("valid rbo");
+            let column = |name| {
+                results
+                    .column_by_name(name)
+                    .expect("field")
+                    .as_primitive::<Float64Type>()
+                    .value(i)
+            };
+            assert_eq!(column("min").to_bits(), expected.min.to_bits());
+            assert_eq!(column("residual").to_bits(), expected.residual.to_bits());
+            assert_eq!(
+                column("extrapolated").to_bits(),
+                expected.extrapolated.to_bits()
+            );
+        }
+    }
+
+    #[test]
+    fn integers_and_nulls() {
+        let mut first = LargeListBuilder::new(Int64Builder::new());
+        let mut second = LargeListBuilder::new(Int64Builder::new());
+        for (a, b) in [
+            (Some(vec![1, 2, 3]), Some(vec![3, 2, 1])),
+            (None, Some(vec![4])),
+        ] {
+            first
+                .values()
+                .append_slice(a.as_deref().unwrap_or_default());
+            first.append(a.is_some());
+            second
+                .values()
+                .append_slice(b.as_deref().unwrap_or_default());
+            second.append(b.is_some());
+        }
+        let (first, second) = (first.finish(), second.finish());
+        let ext = super::rbo_ext_arrow(&first, &second, 0.9).expect("valid rbo");
+        let expected = crate::rbo(&[1, 2, 3], &[3, 2, 1], 0.9).expect("valid rbo");
+        assert_eq!(ext.value(0), expected.extrapolated);
+        assert!(ext.is_null(1));
+        let results = super::rbo_arrow(&first, &second, 0.9).expect("valid rbo");
+        assert!(results.is_valid(0) && results.is_null(1));
+    }
+
+    #[test]
+    fn errors() {
+        let first = strings(&[Some(&["a"]), Some(&["b", "c", "b"])]);
+        let second = strings(&[Some(&["a"]), Some(&["c"])]);
+        match super::rbo_arrow(&first, &second, 0.9) {
+            Err(crate::RboError::DuplicatesInList(duplicate)) => {
+                assert_eq!(duplicate.list, 2);
+                assert_eq!(duplicate.indices, vec![0, 2]);
+                assert_eq!(duplicate.item_debug.as_deref(), Some("\"b\""));
+            }
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+        assert!(super::rbo_arrow(&second, &second, 1.0).is_err());
+        // different lengths and item types
+        assert!(super::rbo_arrow(&first, &strings(&[Some(&["a"])]), 0.9).is_err());
+        let mut integers = ListBuilder::new(Int64Builder::new());
+        integers.values().append_slice(&[1]);
+        integers.append(true);
+        integers.append(true);
+        assert!(super::rbo_arrow(&second, &integers.finish(), 0.9).is_err());
+        assert!(super::rbo_arrow(second.values(), second.values(), 0.9).is_err());
+        // null items
+        let mut nulls = ListBuilder::new(StringBuilder::new());
+        nulls.values().append_null();
+        nulls.append(true);
+        nulls.append(true);
+        assert!(super::rbo_arrow(&nulls.finish(), &second, 0.9).is_err());
+    }
+}
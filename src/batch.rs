@@ -0,0 +1,115 @@
+use crate::state::RboState;
+use crate::{check_duplicates, Rbo, RboError};
+use rayon::prelude::*;
+use std::hash::Hash;
+
+// pairs evaluated by one rayon task, as a single comparison is too cheap to
+// be worth scheduling on its own
+const PAIRS_PER_TASK: usize = 256;
+
+// the results of a parallel evaluation in input order, or the error of the
+// first input that failed. Collecting into a `Result` directly would report
+// whichever error a thread happened to hit first.
+pub(crate) fn in_order<T>(results: Vec<Result<T, RboError>>) -> Result<Vec<T>, RboError> {
+    results.into_iter().collect()
+}
+
+///
+/// RBO of many pairs of rankings, evaluated concurrently on the rayon thread
+/// pool. Pairs are handed to worker threads in chunks, so the scheduling
+/// overhead stays small even for millions of short rankings. The results are
+/// in the order of `pairs`, and both they and the error reported if several
+/// pairs fail are the same for any number of threads.
+///
+/// ```
+/// let pairs = vec![
+///     (vec!["a", "b", "c"], vec!["a", "c", "b"]),
+///     (vec!["a", "b", "c"], vec!["d", "e", "f"]),
+/// ];
+/// let results = rbo::rbo_batch(&pairs, 0.9).expect("valid rbo");
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[1].extrapolated, 0.0);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items. The reported list is
+///   `2 * i` for the first and `2 * i + 1` for the second ranking of the
+///   `i`-th pair.
+///
+pub fn rbo_batch<Item, L>(pairs: &[(L, L)], p: f64) -> Result<Vec<Rbo>, RboError>
+where
+    Item: Eq + Hash + Sync,
+    L: AsRef<[Item]> + Sync,
+{
+    crate::state::validate_persistence(p)?;
+    let results = pairs
+        .par_iter()
+        .with_min_len(PAIRS_PER_TASK)
+        .enumerate()
+        .map(|(i, (first, second))| {
+            let (first, second) = (first.as_ref(), second.as_ref());
+            check_duplicates(first, 2 * i)?;
+            check_duplicates(second, 2 * i + 1)?;
+            let state = RboState::constant_memory(p)?;
+            crate::walk(state, first, second).into_result()
+        })
+        .collect::<Vec<_>>();
+    in_order(results)
+}
+
+///
+/// Like [`rbo_batch`], but evaluates the pairs on `pool` instead of the global
+/// rayon thread pool, e.g. to keep within the threads a service budgets for it.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items, reported as by
+///   [`rbo_batch`]
+///
+pub fn rbo_batch_in<Item, L>(
+    pool: &rayon::ThreadPool,
+    pairs: &[(L, L)],
+    p: f64,
+) -> Result<Vec<Rbo>, RboError>
+where
+    Item: Eq + Hash + Sync,
+    L: AsRef<[Item]> + Sync,
+{
+    pool.install(|| rbo_batch(pairs, p))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn matches_sequential() {
+        let lists: Vec<Vec<u32>> = (0..1000u32)
+            .map(|i| (0..20).map(|j| (i * 7 + j * 13) % 50).collect::<Vec<_>>())
+            .map(|mut list| {
+                list.sort_unstable();
+                list.dedup();
+                list
+            })
+            .collect();
+        let pairs: Vec<(Vec<u32>, Vec<u32>)> = lists
+            .windows(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        let batch = super::rbo_batch(&pairs, 0.9).expect("valid rbo");
+        for ((first, second), rbo) in pairs.iter().zip(batch) {
+            assert_eq!(rbo, crate::rbo(first, second, 0.9).expect("valid rbo"));
+        }
+    }
+
+    #[test]
+    fn reports_the_list_with_duplicates() {
+        let pairs = vec![(vec![1, 2], vec![2, 1]), (vec![1, 2], vec![3, 3])];
+        match super::rbo_batch(&pairs, 0.9) {
+            Err(crate::RboError::DuplicatesInList(duplicate)) => assert_eq!(duplicate.list, 3),
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+        assert!(super::rbo_batch(&pairs, 1.0).is_err());
+    }
+}
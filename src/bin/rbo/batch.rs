@@ -0,0 +1,349 @@
+use crate::input;
+use crate::log;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const HELP: &str = "\
+rbo batch
+Compare the pairs of ranked list files named in a manifest, in parallel, and
+print one tab separated result line per pair in the order of the manifest.
+
+USAGE:
+    rbo batch [-p] [OPTIONS] <MANIFEST>
+
+Every line of MANIFEST names two ranked list files and optionally a label and
+a persistence value, separated by tabs:
+
+    FIRST<TAB>SECOND[<TAB>LABEL[<TAB>P]]
+
+Blank lines and lines starting with `#` are skipped. Pairs without a label are
+labelled by their line number. Pairs that fail are reported on stderr.
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 for pairs without
+                            one [default: 0.9]
+    --jobs <N>              Number of pairs compared at once [default: number of cores]
+    --state <FILE>          Record the result of every finished pair in FILE. Run again
+                            with the same FILE after an interruption or failure, only
+                            pairs without a recorded result are compared again
+    --log-json              Log a JSON line per finished pair to stderr, pairs reused
+                            from --state with status `reused`
+    --log-file <FILE>       Append the JSON log lines to FILE instead of stderr
+    --depth <K>             Only evaluate the first K items of every list
+    --field <N>             Compare the N-th field of every line (counting from 1)
+    --delimiter <CHAR>      Split lines into fields at CHAR rather than at whitespace
+    --trim                  Strip leading and trailing whitespace from every item
+    --lowercase             Lowercase every item
+    --normalize-unicode <F> Bring every item into Unicode normal form `nfc` or `nfkc`
+";
+
+const COLUMNS: &str =
+    "label\tfirst\tsecond\tp\tfirst_depth\tsecond_depth\tmin\tresidual\textrapolated";
+
+#[derive(Debug)]
+struct BatchArgs {
+    p: f64,
+    jobs: Option<usize>,
+    state: Option<PathBuf>,
+    log_json: bool,
+    log_file: Option<PathBuf>,
+    list: input::ListOptions,
+    manifest: PathBuf,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<BatchArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = BatchArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        jobs: pargs.opt_value_from_str("--jobs")?,
+        state: pargs.opt_value_from_str("--state")?,
+        log_json: pargs.contains("--log-json"),
+        log_file: pargs.opt_value_from_str("--log-file")?,
+        list: input::ListOptions::parse(&mut pargs)?,
+        manifest: pargs.free_from_str()?,
+    };
+    Ok(args)
+}
+
+// a pair of ranked lists named in the manifest
+#[derive(Debug)]
+struct Pair {
+    label: String,
+    first: PathBuf,
+    second: PathBuf,
+    p: f64,
+}
+
+fn read_manifest(path: &std::path::Path, default_p: f64) -> anyhow::Result<Vec<Pair>> {
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut pairs = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let (first, second, label, p) = match fields[..] {
+            [first, second] => (first, second, None, None),
+            [first, second, label] => (first, second, Some(label), None),
+            [first, second, label, p] => (first, second, Some(label), Some(p)),
+            _ => anyhow::bail!(
+                "line {}: expected FIRST<TAB>SECOND[<TAB>LABEL[<TAB>P]]",
+                number + 1
+            ),
+        };
+        let p = match p {
+            Some(p) => p
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("line {}: invalid p `{p}`", number + 1))?,
+            None => default_p,
+        };
+        pairs.push(Pair {
+            label: label.map_or_else(|| (number + 1).to_string(), String::from),
+            first: first.into(),
+            second: second.into(),
+            p,
+        });
+    }
+    Ok(pairs)
+}
+
+fn compare(pair: &Pair, list: input::ListOptions) -> anyhow::Result<rbo::RboDetailed> {
+    anyhow::ensure!(
+        pair.first.as_os_str() != "-" && pair.second.as_os_str() != "-",
+        "ranked lists of a batch can not be read from stdin"
+    );
+    let first = input::open_ranked_list(&pair.first, list, None)?;
+    let second = input::open_ranked_list(&pair.second, list, None)?;
+    Ok(rbo::io::lines::rbo_from_readers(first, second, pair.p)?)
+}
+
+// the result line of a pair, as printed and as recorded in the state file
+fn result_line(pair: &Pair, detailed: &rbo::RboDetailed) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        pair.label,
+        pair.first.display(),
+        pair.second.display(),
+        detailed.p,
+        detailed.first_depth,
+        detailed.second_depth,
+        detailed.rbo.min,
+        detailed.rbo.residual,
+        detailed.rbo.extrapolated
+    )
+}
+
+// the list depths and RBO of a result line
+fn parse_result_line(line: &str) -> Option<(usize, usize, rbo::Rbo)> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let [_, _, _, _, first_depth, second_depth, min, residual, extrapolated] = fields[..] else {
+        return None;
+    };
+    let rbo_res = rbo::Rbo {
+        min: min.parse().ok()?,
+        residual: residual.parse().ok()?,
+        extrapolated: extrapolated.parse().ok()?,
+    };
+    Some((
+        first_depth.parse().ok()?,
+        second_depth.parse().ok()?,
+        rbo_res,
+    ))
+}
+
+// log the outcome of a pair, given as its list depths and RBO
+fn log_pair(
+    log: &Mutex<log::PairLog>,
+    pair: &Pair,
+    result: Result<(usize, usize, &rbo::Rbo), String>,
+    duration: Duration,
+    reused: bool,
+) -> std::io::Result<()> {
+    let (first, second) = (pair.first.to_string_lossy(), pair.second.to_string_lossy());
+    let warnings = match result {
+        Ok((first_depth, second_depth, _)) => log::pair_warnings(first_depth, second_depth),
+        Err(_) => Vec::new(),
+    };
+    let mut log = log.lock().expect("log lock poisoned");
+    log.record(&log::PairRecord {
+        first: &first,
+        second: &second,
+        query: Some(&pair.label),
+        p: pair.p,
+        result: result.map(|(_, _, rbo_res)| rbo_res),
+        duration,
+        warnings,
+        reused,
+    })
+}
+
+// The state file starts with a header naming the list options, followed by
+// the manifest index and result line of every finished pair. Results are only
+// reused for pairs whose label, files and p are still the same.
+struct State {
+    file: Mutex<std::fs::File>,
+    done: HashMap<usize, String>,
+}
+
+impl State {
+    fn open(path: &Path, pairs: &[Pair], list: input::ListOptions) -> anyhow::Result<Self> {
+        let header = format!("# rbo batch {:?}", list);
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut done = HashMap::new();
+        let mut lines = content.split_inclusive('\n');
+        match lines.next() {
+            None => {}
+            Some(line) if line.trim_end() == header => {}
+            Some(_) => anyhow::bail!("{} was written with different list options", path.display()),
+        }
+        // a line without a newline was cut short by an interruption
+        for line in lines.filter_map(|line| line.strip_suffix('\n')) {
+            let recorded = line
+                .split_once('\t')
+                .and_then(|(i, result)| Some((i.parse::<usize>().ok()?, result)));
+            let Some((i, result)) = recorded else {
+                anyhow::bail!("{} is not a batch state file", path.display());
+            };
+            let fields: Vec<&str> = result.split('\t').collect();
+            let same = pairs.get(i).is_some_and(|pair| {
+                parse_result_line(result).is_some()
+                    && fields[0] == pair.label
+                    && fields[1] == pair.first.display().to_string()
+                    && fields[2] == pair.second.display().to_string()
+                    && fields[3].parse() == Ok(pair.p)
+            });
+            if same {
+                done.insert(i, result.to_string());
+            }
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        if content.is_empty() {
+            writeln!(file, "{header}")?;
+        } else if !content.ends_with('\n') {
+            writeln!(file)?;
+        }
+        Ok(Self {
+            file: Mutex::new(file),
+            done,
+        })
+    }
+
+    // record the result line of pair `i`, at once so an interruption keeps it
+    fn record(&self, i: usize, line: &str) -> anyhow::Result<()> {
+        let mut file = self.file.lock().expect("state file lock poisoned");
+        writeln!(file, "{i}\t{line}")?;
+        Ok(())
+    }
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    args.list.validate()?;
+    anyhow::ensure!(args.jobs != Some(0), "--jobs must be at least 1");
+    let pairs = read_manifest(&args.manifest, args.p)?;
+    let state = match &args.state {
+        Some(path) => Some(State::open(path, &pairs, args.list)?),
+        None => None,
+    };
+    let log = log::PairLog::open(args.log_json, args.log_file.as_deref())?.map(Mutex::new);
+    let jobs = args
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from))
+        .min(pairs.len().max(1));
+
+    // workers take the next pair until none are left
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<_> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(pair) = pairs.get(i) else {
+                            return results;
+                        };
+                        let recorded = state.as_ref().and_then(|state| state.done.get(&i));
+                        if let Some(line) = recorded {
+                            let logged = match (&log, parse_result_line(line)) {
+                                (Some(log), Some((first_depth, second_depth, rbo_res))) => {
+                                    let outcome = Ok((first_depth, second_depth, &rbo_res));
+                                    log_pair(log, pair, outcome, Duration::ZERO, true)
+                                }
+                                _ => Ok(()),
+                            };
+                            results.push((i, logged.map(|()| line.clone()).map_err(Into::into)));
+                            continue;
+                        }
+                        let start = Instant::now();
+                        let detailed = compare(pair, args.list);
+                        let result = match &log {
+                            Some(log) => {
+                                let outcome = match &detailed {
+                                    Ok(d) => Ok((d.first_depth, d.second_depth, &d.rbo)),
+                                    Err(e) => Err(format!("{e:#}")),
+                                };
+                                log_pair(log, pair, outcome, start.elapsed(), false)
+                                    .map_err(Into::into)
+                                    .and(detailed)
+                            }
+                            None => detailed,
+                        };
+                        let result = result.and_then(|detailed| {
+                            let line = result_line(pair, &detailed);
+                            if let Some(state) = &state {
+                                state.record(i, &line)?;
+                            }
+                            Ok(line)
+                        });
+                        results.push((i, result));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("worker panicked"))
+            .collect()
+    });
+    results.sort_unstable_by_key(|(i, _)| *i);
+
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    writeln!(out, "{COLUMNS}")?;
+    let mut failed = 0;
+    for (i, result) in results {
+        let pair = &pairs[i];
+        match result {
+            Ok(line) => writeln!(out, "{line}")?,
+            Err(err) => {
+                failed += 1;
+                eprintln!("{}: {:#}", pair.label, err);
+            }
+        }
+    }
+    out.flush()?;
+    anyhow::ensure!(failed == 0, "{} of {} pairs failed", failed, pairs.len());
+    Ok(())
+}
@@ -0,0 +1,55 @@
+const HELP: &str = "\
+rbo choose-p
+Calibrate the persistence value p: print the p placing a given fraction of the
+evaluation weight in the top ranks, or the fraction carried by the top ranks
+for a given p (equation 21 in the paper).
+
+USAGE:
+    rbo choose-p --depth <K> --weight <W>
+    rbo choose-p --depth <K> -p <PERSISTENCE>
+
+OPTIONS:
+    --depth <K>             Number of top ranks
+    --weight <W>            Fraction of the evaluation weight in the top K ranks,
+                            0 < W <= 1, e.g. 0.9 for 90%
+    -p, --p <PERSISTENCE>   Persistence value p where 0 <= p < 1.0
+";
+
+#[derive(Debug)]
+struct ChoosePArgs {
+    depth: usize,
+    weight: Option<f64>,
+    p: Option<f64>,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<ChoosePArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = ChoosePArgs {
+        depth: pargs.value_from_str("--depth")?,
+        weight: pargs.opt_value_from_str("--weight")?,
+        p: pargs.opt_value_from_str(["-p", "--p"])?,
+    };
+    Ok(args)
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    match (args.weight, args.p) {
+        (Some(weight), None) => {
+            let p = rbo::persistence_for_weight(args.depth, weight)?;
+            println!("{:.4}", p);
+        }
+        (None, Some(p)) => println!("{:.4}", rbo::top_weight(p, args.depth)?),
+        _ => anyhow::bail!("rbo choose-p needs exactly one of --weight and -p"),
+    }
+    Ok(())
+}
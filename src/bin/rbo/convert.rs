@@ -0,0 +1,83 @@
+use rbo::io::binary::{Corpus, MAGIC};
+use std::io::{BufRead, Read, Write};
+
+const HELP: &str = "\
+rbo convert
+Convert a ranking corpus between the text and the compact binary format. The
+direction is detected from the input: binary input is written as text and text
+input is written as binary.
+
+USAGE:
+    rbo convert <INPUT> <OUTPUT>
+
+The text format has one ranking per line: a label, a tab, and the ranked items
+separated by whitespace. Lines without a tab are labelled by their line number.
+";
+
+fn read_text(file: std::fs::File) -> anyhow::Result<Corpus> {
+    let mut corpus = Corpus::new();
+    for (number, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let (label, items) = match line.split_once('\t') {
+            Some((label, items)) => (label.to_string(), items),
+            None => ((number + 1).to_string(), line.as_str()),
+        };
+        corpus.push(&label, &items.split_whitespace().collect::<Vec<_>>());
+    }
+    Ok(corpus)
+}
+
+fn write_text(corpus: &Corpus, file: std::fs::File) -> anyhow::Result<()> {
+    let mut out = std::io::BufWriter::new(file);
+    for (label, ranking) in corpus.iter() {
+        writeln!(out, "{}\t{}", label, ranking.join(" "))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[derive(Debug)]
+struct ConvertArgs {
+    input: std::path::PathBuf,
+    output: std::path::PathBuf,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<ConvertArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    Ok(ConvertArgs {
+        input: pargs.free_from_str()?,
+        output: pargs.free_from_str()?,
+    })
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let ConvertArgs { input, output } = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut magic = [0u8; 8];
+    let is_binary = std::fs::File::open(&input)?
+        .read_exact(&mut magic)
+        .is_ok_and(|()| &magic == MAGIC);
+    if is_binary {
+        let corpus = Corpus::load(&input)?;
+        write_text(&corpus, std::fs::File::create(&output)?)?;
+        eprintln!("wrote {} rankings as text", corpus.len());
+    } else {
+        let corpus = read_text(std::fs::File::open(&input)?)?;
+        corpus.save(&output)?;
+        eprintln!(
+            "wrote {} rankings over {} distinct items as binary",
+            corpus.len(),
+            corpus.item_count()
+        );
+    }
+    Ok(())
+}
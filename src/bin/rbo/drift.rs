@@ -0,0 +1,111 @@
+use crate::input;
+use crate::output::csv_field;
+use rbo::DriftBaseline;
+use std::io::BufRead;
+
+const HELP: &str = "\
+rbo drift
+Track the volatility of a ranking over snapshot files given in chronological
+order: print the extrapolated RBO between every snapshot and the one before it
+as a CSV time series, and optionally against the first snapshot.
+
+USAGE:
+    rbo drift [-p] [OPTIONS] <SNAPSHOT> <SNAPSHOT>...
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 [default: 0.9]
+    --first                 Add a column with the RBO against the first snapshot
+    --depth <K>             Only evaluate the first K items of every snapshot
+    --field <N>             Compare the N-th field of every line (counting from 1)
+    --delimiter <CHAR>      Split lines into fields at CHAR rather than at whitespace
+    --trim                  Strip leading and trailing whitespace from every item
+    --lowercase             Lowercase every item
+    --normalize-unicode <F> Bring every item into Unicode normal form `nfc` or `nfkc`
+";
+
+#[derive(Debug)]
+struct DriftArgs {
+    p: f64,
+    first: bool,
+    list: input::ListOptions,
+    snapshots: Vec<std::path::PathBuf>,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<DriftArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let mut args = DriftArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        first: pargs.contains("--first"),
+        list: input::ListOptions::parse(&mut pargs)?,
+        snapshots: Vec::new(),
+    };
+    while let Some(snapshot) = pargs.opt_free_from_str()? {
+        args.snapshots.push(snapshot);
+    }
+    Ok(args)
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    anyhow::ensure!(
+        args.snapshots.len() >= 2,
+        "rbo drift needs at least two snapshot files"
+    );
+    anyhow::ensure!(
+        args.snapshots.iter().filter(|file| *file == "-").count() <= 1,
+        "only one snapshot can be read from stdin"
+    );
+    args.list.validate()?;
+
+    let snapshots = args
+        .snapshots
+        .iter()
+        .map(|file| {
+            Ok(input::open_ranked_list(file, args.list, None)?
+                .lines()
+                .collect::<Result<Vec<String>, _>>()?)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let drift = |baseline| {
+        rbo::rbo_drift(&snapshots, args.p, baseline).map_err(|err| match err {
+            rbo::RboError::DuplicatesInList(duplicate) => anyhow::anyhow!(
+                "{} repeats {:?} at ranks {:?}",
+                args.snapshots[duplicate.list].display(),
+                duplicate.item(&snapshots[duplicate.list]),
+                duplicate.indices
+            ),
+            err => err.into(),
+        })
+    };
+    let previous = drift(DriftBaseline::Previous)?;
+    let first = if args.first {
+        Some(drift(DriftBaseline::First)?)
+    } else {
+        None
+    };
+
+    println!(
+        "snapshot,rbo_previous{}",
+        if args.first { ",rbo_first" } else { "" }
+    );
+    for (i, rbo_previous) in previous.iter().enumerate() {
+        let name = csv_field(&args.snapshots[i + 1].to_string_lossy());
+        match &first {
+            Some(first) => println!(
+                "{},{},{}",
+                name, rbo_previous.extrapolated, first[i].extrapolated
+            ),
+            None => println!("{},{}", name, rbo_previous.extrapolated),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const HELP: &str = "\
+rbo gen
+Generate a random base ranking and perturbed variants of it as ranked list
+files, to calibrate the RBO to expect for a level of perturbation or to
+benchmark comparisons.
+
+USAGE:
+    rbo gen --len <N> [OPTIONS]
+
+OPTIONS:
+    --len <N>               Number of items of the base ranking
+    --noise <F>             Fraction of the ranking perturbed, 0 <= F <= 1 [default: 0.1]
+    --seed <SEED>           Seed of the perturbations, to reproduce them [default: random]
+    --out-dir <DIR>         Directory the files are written to [default: .]
+    -p <PERSISTENCE>        Persistence value p of the reported RBO [default: 0.9]
+
+Writes base.txt and three variants: swaps.txt swaps random pairs of items until
+about F of the ranks changed, insertions.txt inserts F N new items at random
+ranks keeping the first N, and truncation.txt keeps only the first (1 - F) N
+items, at least one. Prints the extrapolated RBO of every variant against the
+base.
+";
+
+#[derive(Debug)]
+struct GenArgs {
+    len: usize,
+    noise: f64,
+    seed: Option<u64>,
+    out_dir: PathBuf,
+    p: f64,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<GenArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = GenArgs {
+        len: pargs.value_from_str("--len")?,
+        noise: pargs.opt_value_from_str("--noise")?.unwrap_or(0.1),
+        seed: pargs.opt_value_from_str("--seed")?,
+        out_dir: pargs
+            .opt_value_from_str("--out-dir")?
+            .unwrap_or_else(|| PathBuf::from(".")),
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+    };
+    Ok(args)
+}
+
+// `base` with `count` random pairs of ranks swapped
+fn swaps(base: &[String], count: usize, rng: &mut StdRng) -> Vec<String> {
+    let mut ranking = base.to_vec();
+    for _ in 0..count {
+        let (a, b) = (
+            rng.gen_range(0..ranking.len()),
+            rng.gen_range(0..ranking.len()),
+        );
+        ranking.swap(a, b);
+    }
+    ranking
+}
+
+// `base` with `count` new items inserted at random ranks, cut to its length
+fn insertions(base: &[String], count: usize, rng: &mut StdRng) -> Vec<String> {
+    let mut ranking = base.to_vec();
+    for i in 0..count {
+        let rank = rng.gen_range(0..=ranking.len());
+        ranking.insert(rank, format!("new{}", i + 1));
+    }
+    ranking.truncate(base.len());
+    ranking
+}
+
+fn write(path: &Path, ranking: &[String]) -> anyhow::Result<()> {
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for item in ranking {
+        writeln!(out, "{}", item)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    anyhow::ensure!(args.len > 0, "--len must be at least 1");
+    anyhow::ensure!(
+        (0.0..=1.0).contains(&args.noise),
+        "--noise must be between 0 and 1"
+    );
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let perturbed = (args.noise * args.len as f64).round() as usize;
+
+    let base: Vec<String> = (1..=args.len).map(|i| format!("d{i}")).collect();
+    let variants = [
+        ("swaps", swaps(&base, perturbed.div_ceil(2), &mut rng)),
+        ("insertions", insertions(&base, perturbed, &mut rng)),
+        // at least one item is kept
+        (
+            "truncation",
+            base[..args.len - perturbed.min(args.len - 1)].to_vec(),
+        ),
+    ];
+    std::fs::create_dir_all(&args.out_dir)?;
+    write(&args.out_dir.join("base.txt"), &base)?;
+    for (name, ranking) in &variants {
+        let path = args.out_dir.join(format!("{name}.txt"));
+        write(&path, ranking)?;
+        let rbo = rbo::rbo(&base, ranking, args.p)?;
+        println!("{}\t{}\t{:.4}", name, path.display(), rbo.extrapolated);
+    }
+    println!("seed\t{}", seed);
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+use std::io::{BufRead, Read};
+use std::path::Path;
+use std::rc::Rc;
+
+// the first bytes of gzip and zstd streams
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// how the lines of a ranked list file are turned into its items
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ListOptions {
+    // only the first `depth` items are read
+    pub(crate) depth: Option<usize>,
+    // the 1-based field of every line holding its item, lines are split at
+    // `delimiter` or else at runs of whitespace
+    pub(crate) field: Option<usize>,
+    pub(crate) delimiter: Option<u8>,
+    // items are trimmed, lowercased and brought into a Unicode normal form,
+    // in that order, before they are compared or checked for duplicates
+    pub(crate) trim: bool,
+    pub(crate) lowercase: bool,
+    pub(crate) normalize: Option<UnicodeForm>,
+}
+
+// the Unicode normal form of `--normalize-unicode`
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum UnicodeForm {
+    Nfc,
+    Nfkc,
+}
+
+impl UnicodeForm {
+    #[cfg(feature = "unicode")]
+    fn normalize(self, text: &str) -> String {
+        let normalizer = match self {
+            UnicodeForm::Nfc => icu_normalizer::ComposingNormalizer::new_nfc(),
+            UnicodeForm::Nfkc => icu_normalizer::ComposingNormalizer::new_nfkc(),
+        };
+        normalizer.normalize(text).into_owned()
+    }
+}
+
+pub(crate) fn parse_unicode_form(value: &str) -> Result<UnicodeForm, &'static str> {
+    match value {
+        "nfc" => Ok(UnicodeForm::Nfc),
+        "nfkc" => Ok(UnicodeForm::Nfkc),
+        _ => Err("expected nfc or nfkc"),
+    }
+}
+
+impl ListOptions {
+    // the options shared by every command reading ranked list files
+    pub(crate) fn parse(pargs: &mut pico_args::Arguments) -> Result<Self, pico_args::Error> {
+        Ok(ListOptions {
+            depth: pargs.opt_value_from_str("--depth")?,
+            field: pargs.opt_value_from_str("--field")?,
+            delimiter: pargs.opt_value_from_fn("--delimiter", crate::parse_delimiter)?,
+            trim: pargs.contains("--trim"),
+            lowercase: pargs.contains("--lowercase"),
+            normalize: pargs.opt_value_from_fn("--normalize-unicode", parse_unicode_form)?,
+        })
+    }
+
+    // refuse options that select no items
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(self.depth != Some(0), "--depth must be at least 1");
+        anyhow::ensure!(self.field != Some(0), "--field counts from 1");
+        anyhow::ensure!(
+            self.field.is_some() || self.delimiter.is_none(),
+            "--delimiter needs --field"
+        );
+        #[cfg(not(feature = "unicode"))]
+        anyhow::ensure!(
+            self.normalize.is_none(),
+            "--normalize-unicode needs the `unicode` feature"
+        );
+        Ok(())
+    }
+
+    fn rewrites_items(&self) -> bool {
+        self.trim || self.lowercase || self.normalize.is_some()
+    }
+}
+
+// the ranked list file at `path`, or stdin for `-`, with one item per line as
+// selected by `options`. Files compressed with gzip or zstd are detected by
+// their first bytes and decompressed as they are read. With a `duplicates`
+// counter, repeated items are skipped, keeping their first occurrence, and
+// counted.
+pub(crate) fn open_ranked_list(
+    path: &Path,
+    options: ListOptions,
+    duplicates: Option<&Rc<Cell<usize>>>,
+) -> anyhow::Result<Box<dyn BufRead>> {
+    let reader = open(path)?;
+    if options.depth.is_none()
+        && options.field.is_none()
+        && !options.rewrites_items()
+        && duplicates.is_none()
+    {
+        return Ok(reader);
+    }
+    Ok(Box::new(Items {
+        inner: reader,
+        select: selector(path, options, duplicates),
+        remaining: options.depth,
+        number: 0,
+        line: Vec::new(),
+        pos: 0,
+    }))
+}
+
+// the items of a ranked list read in full rather than from a file, e.g. from
+// Redis, selected by `options` as the lines of a file would be
+#[cfg(feature = "redis")]
+pub(crate) fn select_items(
+    path: &Path,
+    lines: Vec<String>,
+    options: ListOptions,
+    duplicates: Option<&Rc<Cell<usize>>>,
+) -> anyhow::Result<Vec<String>> {
+    let mut select = selector(path, options, duplicates);
+    let mut items = Vec::new();
+    for (number, line) in lines.into_iter().enumerate() {
+        if options.depth == Some(items.len()) {
+            break;
+        }
+        let mut line = line.into_bytes();
+        if select(number + 1, &mut line)? {
+            items.push(String::from_utf8(line)?);
+        }
+    }
+    Ok(items)
+}
+
+// rewrites the line `number` of the ranked list at `path` into its item as
+// selected by `options`, telling whether to keep it
+fn selector(
+    path: &Path,
+    options: ListOptions,
+    duplicates: Option<&Rc<Cell<usize>>>,
+) -> impl FnMut(usize, &mut Vec<u8>) -> std::io::Result<bool> {
+    // 128-bit hashes of the items seen so far rather than the items, as in
+    // `rbo::io::lines`, so huge lists can be deduplicated in little memory
+    let mut seen = HashSet::new();
+    let keys = [RandomState::new(), RandomState::new()];
+    let duplicates = duplicates.cloned();
+    let name = path.display().to_string();
+    let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    move |number: usize, line: &mut Vec<u8>| {
+        if let Some(field) = options.field {
+            let item = split(line, options.delimiter)
+                .nth(field - 1)
+                .ok_or_else(|| invalid(format!("{name}: line {number} has no field {field}")))?;
+            *line = item.to_vec();
+        }
+        if options.rewrites_items() {
+            let item = std::str::from_utf8(line)
+                .map_err(|_| invalid(format!("{name}: line {number} is not valid UTF-8")))?;
+            *line = rewrite(item, options).into_bytes();
+        }
+        if let Some(duplicates) = &duplicates {
+            let [high, low] = &keys;
+            let fingerprint =
+                u128::from(high.hash_one(&line)) << 64 | u128::from(low.hash_one(&line));
+            if !seen.insert(fingerprint) {
+                duplicates.set(duplicates.get() + 1);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn rewrite(item: &str, options: ListOptions) -> String {
+    let mut item = if options.trim { item.trim() } else { item }.to_string();
+    if options.lowercase {
+        item = item.to_lowercase();
+    }
+    #[cfg(feature = "unicode")]
+    if let Some(form) = options.normalize {
+        item = form.normalize(&item);
+    }
+    item
+}
+
+// the fields of `line` separated by `delimiter`, or by runs of whitespace
+fn split(line: &[u8], delimiter: Option<u8>) -> Box<dyn Iterator<Item = &[u8]> + '_> {
+    match delimiter {
+        Some(delimiter) => Box::new(line.split(move |b| *b == delimiter)),
+        None => Box::new(
+            line.split(u8::is_ascii_whitespace)
+                .filter(|field| !field.is_empty()),
+        ),
+    }
+}
+
+fn open(path: &Path) -> anyhow::Result<Box<dyn BufRead>> {
+    let mut reader: Box<dyn BufRead> = if path == Path::new("-") {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(std::io::BufReader::new(std::fs::File::open(path)?))
+    };
+    let start = reader.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(std::io::BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        )));
+        #[cfg(not(feature = "gzip"))]
+        anyhow::bail!(
+            "{} is compressed with gzip, which needs the `gzip` feature",
+            path.display()
+        );
+    }
+    if start.starts_with(ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return Ok(Box::new(std::io::BufReader::new(
+            zstd::stream::read::Decoder::with_buffer(reader)?,
+        )));
+        #[cfg(not(feature = "zstd"))]
+        anyhow::bail!(
+            "{} is compressed with zstd, which needs the `zstd` feature",
+            path.display()
+        );
+    }
+    Ok(reader)
+}
+
+// a reader passing on the lines of `inner` one at a time, as rewritten by
+// `select`, which tells whether to keep the line, and ending after
+// `remaining` lines were kept
+struct Items<R, F> {
+    inner: R,
+    select: F,
+    remaining: Option<usize>,
+    // the 1-based number of the current line of `inner`
+    number: usize,
+    // the current line and how much of it was consumed
+    line: Vec<u8>,
+    pos: usize,
+}
+
+impl<R, F> Read for Items<R, F>
+where
+    R: BufRead,
+    F: FnMut(usize, &mut Vec<u8>) -> std::io::Result<bool>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R, F> BufRead for Items<R, F>
+where
+    R: BufRead,
+    F: FnMut(usize, &mut Vec<u8>) -> std::io::Result<bool>,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        while self.pos == self.line.len() && self.remaining != Some(0) {
+            self.line.clear();
+            self.pos = 0;
+            if self.inner.read_until(b'\n', &mut self.line)? == 0 {
+                break;
+            }
+            self.number += 1;
+            // selected as `lines` yields them, without `\n` or `\r\n`
+            if self.line.ends_with(b"\n") {
+                self.line.pop();
+            }
+            if self.line.ends_with(b"\r") {
+                self.line.pop();
+            }
+            if (self.select)(self.number, &mut self.line)? {
+                self.line.push(b'\n');
+                self.remaining = self.remaining.map(|remaining| remaining - 1);
+            } else {
+                self.line.clear();
+            }
+        }
+        Ok(&self.line[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
@@ -0,0 +1,145 @@
+use crate::log;
+use rbo::io::jsonl::{load_jsonl, JsonlRanking};
+use std::collections::HashMap;
+
+const HELP: &str = "\
+rbo jsonl
+Compare the rankings of two JSON Lines files pair by pair and report the RBO
+of every pair and on average.
+
+USAGE:
+    rbo jsonl [-p] [--field <FIELD>] [--key <KEY>] <FIRST_FILE> <SECOND_FILE>
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 [default: 0.9]
+    --field <FIELD>         Field of every line's object holding the ranked items, or
+                            a JSON pointer such as /response/docs. Without it every
+                            line is an array of items
+    --key <KEY>             Field pairing up the rankings of both files, e.g. a query
+                            id. Without it rankings are paired by line
+    --log-json              Log a JSON line per processed pair to stderr
+    --log-file <FILE>       Append the JSON log lines to FILE instead of stderr
+";
+
+#[derive(Debug)]
+struct JsonlArgs {
+    p: f64,
+    field: Option<String>,
+    key: Option<String>,
+    log_json: bool,
+    log_file: Option<std::path::PathBuf>,
+    first_file: std::path::PathBuf,
+    second_file: std::path::PathBuf,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<JsonlArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = JsonlArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        field: pargs.opt_value_from_str("--field")?,
+        key: pargs.opt_value_from_str("--key")?,
+        log_json: pargs.contains("--log-json"),
+        log_file: pargs.opt_value_from_str("--log-file")?,
+        first_file: pargs.free_from_str()?,
+        second_file: pargs.free_from_str()?,
+    };
+    Ok(args)
+}
+
+// a label and the rankings of both files compared under it
+type Pair = (String, Vec<String>, Vec<String>);
+
+// the label and rankings of every pair, by `key` or by line
+fn pairs(
+    first: Vec<JsonlRanking>,
+    second: Vec<JsonlRanking>,
+    key: Option<&str>,
+) -> anyhow::Result<Vec<Pair>> {
+    let Some(key) = key else {
+        anyhow::ensure!(
+            first.len() == second.len(),
+            "the files hold {} and {} rankings",
+            first.len(),
+            second.len()
+        );
+        return Ok(first
+            .into_iter()
+            .zip(second)
+            .enumerate()
+            .map(|(i, (a, b))| ((i + 1).to_string(), a.ranking, b.ranking))
+            .collect());
+    };
+    let label = |ranking: &JsonlRanking| {
+        ranking
+            .key(key)
+            .ok_or_else(|| anyhow::anyhow!("a ranking has no field `{key}`"))
+    };
+    let mut second = second
+        .into_iter()
+        .map(|ranking| Ok((label(&ranking)?, ranking.ranking)))
+        .collect::<anyhow::Result<HashMap<_, _>>>()?;
+    first
+        .into_iter()
+        .map(|ranking| {
+            let label = label(&ranking)?;
+            let other = second
+                .remove(&label)
+                .ok_or_else(|| rbo::RboError::MissingQuery(label.clone()))?;
+            Ok((label, ranking.ranking, other))
+        })
+        .collect()
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    let first = load_jsonl(&args.first_file, args.field.as_deref())?;
+    let second = load_jsonl(&args.second_file, args.field.as_deref())?;
+    let pairs = pairs(first, second, args.key.as_deref())?;
+
+    let mut log = log::PairLog::open(args.log_json, args.log_file.as_deref())?;
+    let (first_name, second_name) = (
+        args.first_file.to_string_lossy(),
+        args.second_file.to_string_lossy(),
+    );
+    let mut results = Vec::with_capacity(pairs.len());
+    for (label, a, b) in &pairs {
+        let start = std::time::Instant::now();
+        let warnings = log::pair_warnings(a.len(), b.len());
+        let result = rbo::rbo(a, b, args.p);
+        if let Some(log) = log.as_mut() {
+            log.record(&log::PairRecord {
+                first: &first_name,
+                second: &second_name,
+                query: Some(label),
+                p: args.p,
+                result: result.as_ref().map_err(ToString::to_string),
+                duration: start.elapsed(),
+                warnings: if result.is_ok() { warnings } else { Vec::new() },
+                reused: false,
+            })?;
+        }
+        let rbo_res = result?;
+        println!("{}\t{}", label, rbo_res);
+        results.push(rbo_res);
+    }
+    if !results.is_empty() {
+        let mean =
+            |f: fn(&rbo::Rbo) -> f64| results.iter().map(f).sum::<f64>() / results.len() as f64;
+        println!(
+            "mean\tRBO(min={:.3},residual={:.3}, extrapolated={:.3})",
+            mean(|r| r.min),
+            mean(|r| r.residual),
+            mean(|r| r.extrapolated)
+        );
+    }
+    Ok(())
+}
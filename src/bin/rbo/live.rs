@@ -0,0 +1,123 @@
+use crate::log;
+use rbo::io::source::{CommandSource, RankingSource};
+
+const HELP: &str = "\
+rbo live
+Run a set of queries against two live ranking sources and report the RBO
+between their results per query and on average.
+
+USAGE:
+    rbo live [-p] [--depth] --queries <FILE> --first <NAME=COMMAND> --second <NAME=COMMAND>
+
+OPTIONS:
+    -p <PERSISTENCE>           Persistence value p where 0 <= p < 1.0 [default: 0.9]
+    --depth <DEPTH>            Number of results to fetch per query [default: 100]
+    --queries <FILE>           File with one query per line
+    --first <NAME=COMMAND>     First source: a shell command printing one item per line
+    --second <NAME=COMMAND>    Second source: a shell command printing one item per line
+    --log-json                 Log a JSON line per processed query to stderr
+    --log-file <FILE>          Append the JSON log lines to FILE instead of stderr
+
+In COMMAND, `{query}` and `{depth}` are replaced by the shell-quoted query and the
+depth, which are also available as the environment variables RBO_QUERY and RBO_DEPTH:
+
+    rbo live --queries q.txt \\
+        --first 'prod=curl -s \"https://prod/search?n=$RBO_DEPTH\" --data-urlencode \"q=$RBO_QUERY\" | jq -r .hits[].id' \\
+        --second 'staging=curl -s \"https://staging/search?n=$RBO_DEPTH\" --data-urlencode \"q=$RBO_QUERY\" | jq -r .hits[].id'
+";
+
+#[derive(Debug)]
+struct LiveArgs {
+    p: f64,
+    depth: usize,
+    log_json: bool,
+    log_file: Option<std::path::PathBuf>,
+    queries_file: std::path::PathBuf,
+    first: CommandSource,
+    second: CommandSource,
+}
+
+fn parse_source(value: &str) -> Result<CommandSource, &'static str> {
+    value
+        .split_once('=')
+        .map(|(name, command)| CommandSource::new(name, command))
+        .ok_or("expected NAME=COMMAND")
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<LiveArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = LiveArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        depth: pargs.opt_value_from_str("--depth")?.unwrap_or(100),
+        log_json: pargs.contains("--log-json"),
+        log_file: pargs.opt_value_from_str("--log-file")?,
+        queries_file: pargs.value_from_str("--queries")?,
+        first: pargs.value_from_fn("--first", parse_source)?,
+        second: pargs.value_from_fn("--second", parse_source)?,
+    };
+    Ok(args)
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    let queries = std::fs::read_to_string(&args.queries_file)?;
+    let queries: Vec<&str> = queries.lines().filter(|q| !q.trim().is_empty()).collect();
+
+    let mut log = log::PairLog::open(args.log_json, args.log_file.as_deref())?;
+
+    println!(
+        "# {} vs {} (p={}, depth={})",
+        args.first.name(),
+        args.second.name(),
+        args.p,
+        args.depth
+    );
+    let mut results = Vec::with_capacity(queries.len());
+    for query in &queries {
+        let start = std::time::Instant::now();
+        let result = args.first.fetch(query, args.depth).and_then(|a| {
+            let b = args.second.fetch(query, args.depth)?;
+            let warnings = log::pair_warnings(a.len(), b.len());
+            Ok((rbo::rbo(&a, &b, args.p)?, warnings))
+        });
+        if let Some(log) = log.as_mut() {
+            let (result, warnings) = match &result {
+                Ok((rbo_res, warnings)) => (Ok(rbo_res), warnings.clone()),
+                Err(e) => (Err(e.to_string()), Vec::new()),
+            };
+            log.record(&log::PairRecord {
+                first: args.first.name(),
+                second: args.second.name(),
+                query: Some(query),
+                p: args.p,
+                result,
+                duration: start.elapsed(),
+                warnings,
+                reused: false,
+            })?;
+        }
+        let (rbo_res, _) = result?;
+        println!("{}\t{}", query, rbo_res);
+        results.push(rbo_res);
+    }
+    if !results.is_empty() {
+        let mean =
+            |f: fn(&rbo::Rbo) -> f64| results.iter().map(f).sum::<f64>() / results.len() as f64;
+        println!(
+            "mean\tRBO(min={:.3},residual={:.3}, extrapolated={:.3})",
+            mean(|r| r.min),
+            mean(|r| r.residual),
+            mean(|r| r.extrapolated)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::io::Write;
+use std::time::Duration;
+
+/// Writes one JSON object per processed pair, for consumption by log aggregation
+pub(crate) struct PairLog {
+    out: Box<dyn Write + Send>,
+}
+
+/// Everything we know about one processed pair
+pub(crate) struct PairRecord<'a> {
+    pub(crate) first: &'a str,
+    pub(crate) second: &'a str,
+    pub(crate) query: Option<&'a str>,
+    pub(crate) p: f64,
+    pub(crate) result: Result<&'a rbo::Rbo, String>,
+    pub(crate) duration: Duration,
+    pub(crate) warnings: Vec<String>,
+    /// The result was recorded by an earlier run rather than computed again
+    pub(crate) reused: bool,
+}
+
+impl PairLog {
+    // log to `file` if given, to stderr if only `--log-json` was passed
+    pub(crate) fn open(
+        log_json: bool,
+        file: Option<&std::path::Path>,
+    ) -> std::io::Result<Option<Self>> {
+        let out: Box<dyn Write + Send> = match file {
+            Some(path) => Box::new(std::io::BufWriter::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?,
+            )),
+            None if log_json => Box::new(std::io::stderr()),
+            None => return Ok(None),
+        };
+        Ok(Some(Self { out }))
+    }
+
+    pub(crate) fn record(&mut self, record: &PairRecord<'_>) -> std::io::Result<()> {
+        let mut line = serde_json::json!({
+            "first": record.first,
+            "second": record.second,
+            "p": record.p,
+            "duration_ms": record.duration.as_secs_f64() * 1000.0,
+            "warnings": record.warnings,
+        });
+        if let Some(query) = record.query {
+            line["query"] = query.into();
+        }
+        match &record.result {
+            Ok(rbo_res) => {
+                line["status"] = if record.reused { "reused" } else { "ok" }.into();
+                line["min"] = rbo_res.min.into();
+                line["residual"] = rbo_res.residual.into();
+                line["extrapolated"] = rbo_res.extrapolated.into();
+            }
+            Err(e) => {
+                line["status"] = "error".into();
+                line["error"] = e.as_str().into();
+            }
+        }
+        writeln!(self.out, "{}", line)?;
+        self.out.flush()
+    }
+}
+
+/// Conditions worth flagging in the log that do not prevent computing RBO
+pub(crate) fn pair_warnings(first_len: usize, second_len: usize) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if first_len == 0 || second_len == 0 {
+        warnings.push("empty ranked list".to_string());
+    } else if first_len != second_len {
+        warnings.push(format!(
+            "ranked lists differ in length ({} vs {})",
+            first_len, second_len
+        ));
+    }
+    warnings
+}
@@ -0,0 +1,508 @@
+// Note: this requires the `derive` feature
+
+mod batch;
+mod choose_p;
+mod convert;
+mod drift;
+mod gen;
+mod input;
+#[cfg(feature = "jsonl")]
+mod jsonl;
+mod live;
+mod log;
+mod matrix;
+mod output;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod progress;
+#[cfg(feature = "toml")]
+mod run;
+mod serve;
+mod test;
+mod trec;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+const HELP: &str = "\
+rbo
+Rank-Biased Overlap (RBO): a similarity measure for indefinite ranked lists. see
+
+@article{wmz10:acmtois,
+    author = \"Webber, William and Moffat, Alistair and Zobel, Justin\",
+    title = \"A similarity measure for indefinite rankings\",
+    journal = \"ACM Transactions on Information Systems\",
+    year = {2010},
+}
+
+for details.
+
+USAGE:
+    rbo [-p] <FIRST_RANKED_LIST_FILE> <SECOND_RANKED_LIST_FILE>
+    rbo <SUBCOMMAND> [OPTIONS]
+
+ARGS:
+    <FIRST_RANKED_LIST_FILE>     first ranked list, or `-` to read it from stdin
+    <SECOND_RANKED_LIST_FILE>    second ranked list, or `-` to read it from stdin
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 [default: 0.9], or a
+                            comma-separated list such as 0.9,0.98,0.995 to report one
+                            result per value from a single pass over both lists
+    --depth <K>             Only evaluate the first K items of both lists
+    --field <N>             Compare the N-th field of every line (counting from 1), e.g. the
+                            docid column of a run export, rather than the whole line
+    --delimiter <CHAR>      Split lines into fields at CHAR (`\t` for tabs) rather than at
+                            runs of whitespace. Quotes are not interpreted
+    --trim                  Strip leading and trailing whitespace from every item
+    --lowercase             Lowercase every item, for lists that only differ in case
+    --normalize-unicode <F> Bring every item into Unicode normal form `nfc` or `nfkc`, for
+                            lists exported in different forms. Items are trimmed,
+                            lowercased and normalized before duplicates are checked
+    --dedup                 Skip repeated items, keeping their first occurrence, instead
+                            of failing, and report how many were skipped per list
+    --output <FORMAT>       Print the result as text, json, csv or tsv [default: text]
+    --curve                 Print the RBO at every depth as CSV instead of the result, e.g.
+                            to plot how it converges
+    --every <N>             With --curve, only print every N-th depth and the last one
+    --explain               After the result, list the items only in one list and the most
+                            displaced items, with the weight each costs the RBO
+    --fail-below <VALUE>    Exit with status 2 if the RBO of any p is below VALUE, to gate
+                            ranking regression checks
+    --fail-on <MEASURE>     The RBO value checked by --fail-below, `extrapolated` or `min`
+                            [default: extrapolated]
+    --progress              Report the depth reached and the RBO so far on stderr every
+                            second while comparing large files
+    --log-json              Log a JSON line per processed pair to stderr
+    --log-file <FILE>       Append the JSON log lines to FILE instead of stderr
+
+SUBCOMMANDS:
+    batch                   Compare the pairs of ranked list files named in a manifest
+    choose-p                Calibrate p from the weight given to the top ranks
+    convert                 Convert a ranking corpus between text and binary format
+    drift                   Print the RBO between consecutive snapshots as a CSV time series
+    gen                     Generate a random ranking and perturbed variants of it
+    jsonl                   Compare the rankings of two JSON Lines files pair by pair
+    live                    Compare two live ranking sources over a set of queries
+    matrix                  Print the pairwise RBO matrix of many ranked list files as CSV
+    parquet                 Compare the pairs of lists stored in the rows of a Parquet file
+    run                     Run the comparisons declared in a TOML job file
+    serve                   Answer comparison requests read from stdin as JSON lines
+    test                    Test whether the RBO of two ranked lists is above chance
+    trec                    Compare two TREC run files per query, with the mean and median
+";
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+const COMPRESSION_HELP: &str = "
+Ranked list files compressed with gzip or zstd are decompressed as they are read.
+";
+
+#[cfg(feature = "redis")]
+const REDIS_HELP: &str = "
+A ranked list can also be read from a Redis sorted set (highest score first)
+by passing `redis://HOST[:PORT][/DB]#KEY` instead of a file.
+";
+
+#[derive(Debug)]
+struct AppArgs {
+    ps: Vec<f64>,
+    list: input::ListOptions,
+    dedup: bool,
+    output: output::OutputFormat,
+    curve: bool,
+    every: usize,
+    explain: bool,
+    fail_below: Option<f64>,
+    fail_on: Measure,
+    progress: bool,
+    log_json: bool,
+    log_file: Option<std::path::PathBuf>,
+    first_ranked_list_file: std::path::PathBuf,
+    second_ranked_list_file: std::path::PathBuf,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<AppArgs, pico_args::Error> {
+    // Help has a higher priority and should be handled separately.
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        print!("{}", COMPRESSION_HELP);
+        #[cfg(feature = "redis")]
+        print!("{}", REDIS_HELP);
+        std::process::exit(0);
+    }
+    let args = AppArgs {
+        // Parses a required value that implements `FromStr`.
+        // Returns an error if not present.
+        ps: pargs
+            .opt_value_from_fn("-p", parse_persistences)?
+            .unwrap_or_else(|| vec![0.9]),
+        list: input::ListOptions::parse(&mut pargs)?,
+        dedup: pargs.contains("--dedup"),
+        output: pargs.opt_value_from_str("--output")?.unwrap_or_default(),
+        curve: pargs.contains("--curve"),
+        every: pargs.opt_value_from_str("--every")?.unwrap_or(1),
+        explain: pargs.contains("--explain"),
+        fail_below: pargs.opt_value_from_str("--fail-below")?,
+        fail_on: pargs
+            .opt_value_from_fn("--fail-on", parse_measure)?
+            .unwrap_or(Measure::Extrapolated),
+        progress: pargs.contains("--progress"),
+        log_json: pargs.contains("--log-json"),
+        log_file: pargs.opt_value_from_str("--log-file")?,
+        // Parses an optional value from `&OsStr` using a specified function.
+        first_ranked_list_file: pargs.free_from_str()?,
+        // Parses a required free-standing/positional argument.
+        second_ranked_list_file: pargs.free_from_str()?,
+    };
+    Ok(args)
+}
+
+// the number of items listed per section of --explain
+const EXPLAINED_ITEMS: usize = 10;
+
+// the RBO value checked by --fail-below
+#[derive(Debug, Clone, Copy)]
+enum Measure {
+    Min,
+    Extrapolated,
+}
+
+fn parse_measure(value: &str) -> Result<Measure, &'static str> {
+    match value {
+        "min" => Ok(Measure::Min),
+        "extrapolated" => Ok(Measure::Extrapolated),
+        _ => Err("expected extrapolated or min"),
+    }
+}
+
+// the comma-separated persistence values of `-p`
+fn parse_persistences(value: &str) -> Result<Vec<f64>, std::num::ParseFloatError> {
+    value.split(',').map(|p| p.trim().parse()).collect()
+}
+
+// the single ASCII character of `--delimiter`, or `\t` for a tab
+fn parse_delimiter(value: &str) -> Result<u8, &'static str> {
+    match value.as_bytes() {
+        [delimiter] if delimiter.is_ascii() => Ok(*delimiter),
+        b"\\t" => Ok(b'\t'),
+        _ => Err("expected a single ASCII character"),
+    }
+}
+
+// the server url and key of a ranked list stored in Redis
+fn redis_location(path: &std::path::Path) -> Option<(&str, &str)> {
+    path.to_str()
+        .filter(|s| s.starts_with("redis://"))
+        .and_then(|s| s.split_once('#'))
+}
+
+fn read_ranked_list(
+    path: &std::path::Path,
+    options: input::ListOptions,
+    duplicates: Option<&Rc<Cell<usize>>>,
+) -> anyhow::Result<Vec<String>> {
+    if let Some((url, key)) = redis_location(path) {
+        #[cfg(feature = "redis")]
+        {
+            let order = rbo::io::redis::ScoreOrder::Descending;
+            // with --dedup more members than the depth may be needed
+            let depth = options.depth.filter(|_| duplicates.is_none());
+            let members = rbo::io::redis::fetch_sorted_set(url, key, order, depth)?;
+            return input::select_items(path, members, options, duplicates);
+        }
+        #[cfg(not(feature = "redis"))]
+        anyhow::bail!("reading {key} from {url} needs the `redis` feature");
+    }
+    use std::io::BufRead;
+
+    Ok(input::open_ranked_list(path, options, duplicates)?
+        .lines()
+        .collect::<Result<Vec<String>, _>>()?)
+}
+
+// the outcome of comparing the two ranked lists
+struct Comparison {
+    // the result for every persistence value
+    results: Vec<rbo::RboDetailed>,
+    // with --curve, the result at every depth for every persistence value
+    curves: Vec<Vec<rbo::RboPoint>>,
+    // with --explain, the item report for every persistence value
+    explanations: Vec<String>,
+    // conditions worth logging
+    warnings: Vec<String>,
+}
+
+// RBO between the two ranked lists for every persistence value. Files are
+// compared as they are read, without loading them first, unless the curve
+// or the explanation is needed.
+fn compare(args: &AppArgs) -> anyhow::Result<Comparison> {
+    let paths = [&args.first_ranked_list_file, &args.second_ranked_list_file];
+    anyhow::ensure!(
+        paths[0] != paths[1] || paths[0] != std::path::Path::new("-"),
+        "only one ranked list can be read from stdin"
+    );
+    args.list.validate()?;
+    anyhow::ensure!(args.every != 0, "--every must be at least 1");
+    anyhow::ensure!(
+        !args.explain || (!args.curve && args.output == output::OutputFormat::Text),
+        "--explain needs the text output"
+    );
+    anyhow::ensure!(
+        !args.progress || (args.ps.len() == 1 && !args.curve && !args.explain),
+        "--progress needs a single p and can not be combined with --curve or --explain"
+    );
+    // the number of repeated items skipped in each list with --dedup
+    let duplicates: Option<[Rc<Cell<usize>>; 2]> = args.dedup.then(Default::default);
+    let mut comparison = compare_lists(args, duplicates.as_ref())?;
+
+    let results = &comparison.results;
+    let warnings = &mut comparison.warnings;
+    warnings.extend(log::pair_warnings(
+        results[0].first_depth,
+        results[0].second_depth,
+    ));
+    for (list, (path, counter)) in paths.iter().zip(duplicates.iter().flatten()).enumerate() {
+        let skipped = counter.get();
+        if skipped > 0 {
+            eprintln!("dropped {} duplicates from {}", skipped, path.display());
+            let name = ["first", "second"][list];
+            warnings.push(format!(
+                "dropped {skipped} duplicates from the {name} ranked list"
+            ));
+        }
+    }
+    Ok(comparison)
+}
+
+// the comparison of `compare` without warnings, counting the repeated items
+// skipped in each list in `duplicates`
+fn compare_lists(
+    args: &AppArgs,
+    duplicates: Option<&[Rc<Cell<usize>>; 2]>,
+) -> anyhow::Result<Comparison> {
+    let (first, second) = (&args.first_ranked_list_file, &args.second_ranked_list_file);
+    let counter = |list: usize| duplicates.map(|counters| &counters[list]);
+    if args.curve
+        || args.explain
+        || redis_location(first).is_some()
+        || redis_location(second).is_some()
+    {
+        let first = read_ranked_list(first, args.list, counter(0))?;
+        let second = read_ranked_list(second, args.list, counter(1))?;
+        let results = args
+            .ps
+            .iter()
+            .map(|p| rbo::rbo_detailed(&first, &second, *p))
+            .collect::<Result<_, rbo::RboError>>()
+            .map_err(|err| err.with_item_debug(&first, &second))?;
+        let curves = if args.curve {
+            args.ps
+                .iter()
+                .map(|p| rbo::rbo_curve(&first, &second, *p))
+                .collect::<Result<_, _>>()?
+        } else {
+            Vec::new()
+        };
+        let explanations = if args.explain {
+            args.ps
+                .iter()
+                .map(|p| {
+                    let explanation = rbo::rbo_explain(&first, &second, *p)?;
+                    Ok(output::explanation(*p, &explanation, EXPLAINED_ITEMS))
+                })
+                .collect::<Result<_, rbo::RboError>>()?
+        } else {
+            Vec::new()
+        };
+        return Ok(Comparison {
+            results,
+            curves,
+            explanations,
+            warnings: Vec::new(),
+        });
+    }
+    let results = match args.ps[..] {
+        [p] if args.progress => {
+            let (first, second) = (
+                input::open_ranked_list(first, args.list, counter(0))?,
+                input::open_ranked_list(second, args.list, counter(1))?,
+            );
+            let mut progress = progress::Progress::new();
+            let detailed = rbo::io::lines::rbo_from_readers_with_progress(
+                first,
+                second,
+                p,
+                progress::INTERVAL,
+                |depth, rbo| progress.report(depth, rbo),
+            )?;
+            progress.finish();
+            vec![detailed]
+        }
+        _ => compare_files(first, second, &args.ps, args.list, duplicates)?,
+    };
+    Ok(Comparison {
+        results,
+        curves: Vec::new(),
+        explanations: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// RBO between the ranked list files `first` and `second` for every
+// persistence value in `ps`, compared as they are read, counting the
+// repeated items skipped in each list in `duplicates`
+fn compare_files(
+    first: &std::path::Path,
+    second: &std::path::Path,
+    ps: &[f64],
+    list: input::ListOptions,
+    duplicates: Option<&[Rc<Cell<usize>>; 2]>,
+) -> anyhow::Result<Vec<rbo::RboDetailed>> {
+    let counter = |list: usize| duplicates.map(|counters| &counters[list]);
+    let (first, second) = (
+        input::open_ranked_list(first, list, counter(0))?,
+        input::open_ranked_list(second, list, counter(1))?,
+    );
+    Ok(match ps {
+        [p] => vec![rbo::io::lines::rbo_from_readers(first, second, *p)?],
+        _ => rbo::io::lines::rbo_multi_p_from_readers(first, second, ps)?,
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut raw_args: Vec<std::ffi::OsString> = std::env::args_os().skip(1).collect();
+    let subcommand = raw_args.first().and_then(|a| a.to_str()).map(String::from);
+    let pargs = match subcommand.as_deref() {
+        Some("batch") => {
+            raw_args.remove(0);
+            return batch::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("choose-p") => {
+            raw_args.remove(0);
+            return choose_p::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("convert") => {
+            raw_args.remove(0);
+            return convert::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("drift") => {
+            raw_args.remove(0);
+            return drift::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("gen") => {
+            raw_args.remove(0);
+            return gen::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("jsonl") => {
+            raw_args.remove(0);
+            #[cfg(feature = "jsonl")]
+            return jsonl::run(pico_args::Arguments::from_vec(raw_args));
+            #[cfg(not(feature = "jsonl"))]
+            anyhow::bail!("rbo jsonl needs the `jsonl` feature");
+        }
+        Some("live") => {
+            raw_args.remove(0);
+            return live::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("matrix") => {
+            raw_args.remove(0);
+            return matrix::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("parquet") => {
+            raw_args.remove(0);
+            #[cfg(feature = "parquet")]
+            return parquet::run(pico_args::Arguments::from_vec(raw_args));
+            #[cfg(not(feature = "parquet"))]
+            anyhow::bail!("rbo parquet needs the `parquet` feature");
+        }
+        Some("run") => {
+            raw_args.remove(0);
+            #[cfg(feature = "toml")]
+            return run::run(pico_args::Arguments::from_vec(raw_args));
+            #[cfg(not(feature = "toml"))]
+            anyhow::bail!("rbo run needs the `toml` feature");
+        }
+        Some("serve") => {
+            raw_args.remove(0);
+            return serve::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("test") => {
+            raw_args.remove(0);
+            return test::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        Some("trec") => {
+            raw_args.remove(0);
+            return trec::run(pico_args::Arguments::from_vec(raw_args));
+        }
+        _ => pico_args::Arguments::from_vec(raw_args),
+    };
+
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut log = log::PairLog::open(args.log_json, args.log_file.as_deref())?;
+    let start = std::time::Instant::now();
+    let result = compare(&args);
+    let (first, second) = (
+        args.first_ranked_list_file.to_string_lossy(),
+        args.second_ranked_list_file.to_string_lossy(),
+    );
+    if let Some(log) = log.as_mut() {
+        let duration = start.elapsed();
+        for (i, p) in args.ps.iter().enumerate() {
+            let (result, warnings) = match &result {
+                Ok(comparison) => (Ok(&comparison.results[i].rbo), comparison.warnings.clone()),
+                Err(e) => (Err(e.to_string()), Vec::new()),
+            };
+            log.record(&log::PairRecord {
+                first: &first,
+                second: &second,
+                query: None,
+                p: *p,
+                result,
+                duration,
+                warnings,
+                reused: false,
+            })?;
+        }
+    }
+    let Comparison {
+        results,
+        curves,
+        explanations,
+        ..
+    } = result?;
+
+    if args.curve {
+        println!("{}", output::curves(&results, &curves, args.every));
+    } else {
+        println!("{}", output::format(args.output, &first, &second, &results));
+    }
+    for explanation in &explanations {
+        println!("{}", explanation);
+    }
+
+    if let Some(threshold) = args.fail_below {
+        for detailed in &results {
+            let (name, value) = match args.fail_on {
+                Measure::Min => ("min", detailed.rbo.min),
+                Measure::Extrapolated => ("extrapolated", detailed.rbo.extrapolated),
+            };
+            // NaN fails too
+            if value.is_nan() || value < threshold {
+                eprintln!(
+                    "RBO {}={} at p={} is below {}",
+                    name, value, detailed.p, threshold
+                );
+                std::process::exit(2);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use crate::input;
+use crate::output::csv_field;
+use std::io::BufRead;
+
+const HELP: &str = "\
+rbo matrix
+Compute the extrapolated RBO between every pair of the given ranked list files
+and print the matrix as CSV, with the file names as row and column headers.
+
+USAGE:
+    rbo matrix [-p] [OPTIONS] <FILE> <FILE>...
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 [default: 0.9]
+    --state <DIR>           Record every finished block of the matrix in DIR. Run again
+                            with the same DIR after an interruption, only blocks not
+                            recorded yet are computed
+    --depth <K>             Only evaluate the first K items of every list
+    --field <N>             Compare the N-th field of every line (counting from 1)
+    --delimiter <CHAR>      Split lines into fields at CHAR rather than at whitespace
+    --trim                  Strip leading and trailing whitespace from every item
+    --lowercase             Lowercase every item
+    --normalize-unicode <F> Bring every item into Unicode normal form `nfc` or `nfkc`
+";
+
+// rankings per side of a block of the matrix recorded by `--state`
+const STATE_BLOCK_SIZE: usize = 64;
+
+#[derive(Debug)]
+struct MatrixArgs {
+    p: f64,
+    state: Option<std::path::PathBuf>,
+    list: input::ListOptions,
+    files: Vec<std::path::PathBuf>,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<MatrixArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let mut args = MatrixArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        state: pargs.opt_value_from_str("--state")?,
+        list: input::ListOptions::parse(&mut pargs)?,
+        files: Vec::new(),
+    };
+    while let Some(file) = pargs.opt_free_from_str()? {
+        args.files.push(file);
+    }
+    Ok(args)
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    anyhow::ensure!(
+        args.files.len() >= 2,
+        "rbo matrix needs at least two ranked list files"
+    );
+    anyhow::ensure!(
+        args.files.iter().filter(|file| *file == "-").count() <= 1,
+        "only one ranked list can be read from stdin"
+    );
+    args.list.validate()?;
+
+    let lists = args
+        .files
+        .iter()
+        .map(|file| {
+            Ok(input::open_ranked_list(file, args.list, None)?
+                .lines()
+                .collect::<Result<Vec<String>, _>>()?)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let matrix = match &args.state {
+        // the lists themselves are fingerprinted, a changed file is not resumed
+        Some(dir) => rbo::matrix::rbo_matrix_checkpointed(&lists, args.p, STATE_BLOCK_SIZE, dir),
+        #[cfg(feature = "rayon")]
+        None => rbo::matrix::rbo_matrix_par(&lists, args.p),
+        #[cfg(not(feature = "rayon"))]
+        None => rbo::matrix::rbo_matrix(&lists, args.p),
+    };
+    let matrix = matrix.map_err(|err| match err {
+        rbo::RboError::DuplicatesInList(duplicate) => anyhow::anyhow!(
+            "{} repeats {:?} at ranks {:?}",
+            args.files[duplicate.list].display(),
+            duplicate.item(&lists[duplicate.list]),
+            duplicate.indices
+        ),
+        err => err.into(),
+    })?;
+
+    let names: Vec<String> = args
+        .files
+        .iter()
+        .map(|file| csv_field(&file.to_string_lossy()))
+        .collect();
+    println!(",{}", names.join(","));
+    for (name, row) in names.iter().zip(matrix.extrapolated()) {
+        let row: Vec<String> = row.iter().map(ToString::to_string).collect();
+        println!("{},{}", name, row.join(","));
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+// How the result of comparing two ranked lists is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum OutputFormat {
+    // the human readable `RBO(min=..,...)` line
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            _ => Err("expected text, json, csv or tsv"),
+        }
+    }
+}
+
+const COLUMNS: [&str; 8] = [
+    "first",
+    "second",
+    "p",
+    "first_depth",
+    "second_depth",
+    "min",
+    "residual",
+    "extrapolated",
+];
+
+// quote a CSV field if it contains a delimiter, quote or line break
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// the values of a result, following the file names in `COLUMNS`
+fn values(detailed: &rbo::RboDetailed) -> [String; 6] {
+    [
+        detailed.p.to_string(),
+        detailed.first_depth.to_string(),
+        detailed.second_depth.to_string(),
+        detailed.rbo.min.to_string(),
+        detailed.rbo.residual.to_string(),
+        detailed.rbo.extrapolated.to_string(),
+    ]
+}
+
+// the results of comparing the lists `first` and `second` in `format`, one
+// line or row per persistence value
+pub(crate) fn format(
+    format: OutputFormat,
+    first: &str,
+    second: &str,
+    results: &[rbo::RboDetailed],
+) -> String {
+    let lines: Vec<String> = match format {
+        OutputFormat::Text if results.len() == 1 => vec![results[0].rbo.to_string()],
+        OutputFormat::Text => results
+            .iter()
+            .map(|detailed| format!("p={}\t{}", detailed.p, detailed.rbo))
+            .collect(),
+        OutputFormat::Json => results
+            .iter()
+            .map(|detailed| {
+                serde_json::json!({
+                    "first": first,
+                    "second": second,
+                    "p": detailed.p,
+                    "first_depth": detailed.first_depth,
+                    "second_depth": detailed.second_depth,
+                    "min": detailed.rbo.min,
+                    "residual": detailed.rbo.residual,
+                    "extrapolated": detailed.rbo.extrapolated,
+                })
+                .to_string()
+            })
+            .collect(),
+        OutputFormat::Csv => std::iter::once(COLUMNS.join(","))
+            .chain(results.iter().map(|detailed| {
+                let row: Vec<String> = [csv_field(first), csv_field(second)]
+                    .into_iter()
+                    .chain(values(detailed))
+                    .collect();
+                row.join(",")
+            }))
+            .collect(),
+        OutputFormat::Tsv => std::iter::once(COLUMNS.join("\t"))
+            .chain(results.iter().map(|detailed| {
+                let row: Vec<String> = [first.to_string(), second.to_string()]
+                    .into_iter()
+                    .chain(values(detailed))
+                    .collect();
+                row.join("\t")
+            }))
+            .collect(),
+    };
+    lines.join("\n")
+}
+
+// the RBO at every `every`-th depth and at the last depth of the curve of
+// every persistence value, as CSV
+pub(crate) fn curves(
+    results: &[rbo::RboDetailed],
+    curves: &[Vec<rbo::RboPoint>],
+    every: usize,
+) -> String {
+    let mut lines = vec!["p,depth,overlap,min,residual,extrapolated".to_string()];
+    for (detailed, curve) in results.iter().zip(curves) {
+        for point in curve
+            .iter()
+            .filter(|point| point.depth % every == 0 || point.depth == curve.len())
+        {
+            lines.push(format!(
+                "{},{},{},{},{},{}",
+                detailed.p,
+                point.depth,
+                point.overlap,
+                point.rbo.min,
+                point.rbo.residual,
+                point.rbo.extrapolated
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+// the items driving the disagreement behind the RBO at `p`: the costliest
+// items of only one list and the costliest displaced items, at most `limit`
+// of each, with the weight they cost the score
+pub(crate) fn explanation(
+    p: f64,
+    explanation: &rbo::RboExplanation<String>,
+    limit: usize,
+) -> String {
+    let costliest = |keep: &dyn Fn(&rbo::ItemContribution<String>) -> bool| {
+        let mut items: Vec<_> = explanation.items.iter().filter(|item| keep(item)).collect();
+        items.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+        items.truncate(limit);
+        items
+    };
+    let rank = |rank: Option<usize>| rank.map_or_else(|| "-".to_string(), |r| r.to_string());
+    let gap = 1.0 - explanation.rbo.min;
+    let mut lines = vec![format!("explanation at p={p} (1 - min = {gap:.4})")];
+    for (title, items) in [
+        (
+            "only in the first list",
+            costliest(&|item| item.second_rank.is_none()),
+        ),
+        (
+            "only in the second list",
+            costliest(&|item| item.first_rank.is_none()),
+        ),
+        (
+            "largest displacements",
+            costliest(
+                &|item| matches!((item.first_rank, item.second_rank), (Some(a), Some(b)) if a != b),
+            ),
+        ),
+    ] {
+        lines.push(format!("{title}:"));
+        if items.is_empty() {
+            lines.push("    none".to_string());
+        }
+        for item in items {
+            lines.push(format!(
+                "    first={}\tsecond={}\tcontribution={:.4}\tcost={:.4}\t{}",
+                rank(item.first_rank),
+                rank(item.second_rank),
+                item.contribution,
+                item.cost,
+                item.item
+            ));
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,264 @@
+use ::parquet::basic::Compression;
+use ::parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::reader::SerializedFileReader;
+use ::parquet::file::writer::SerializedFileWriter;
+use ::parquet::record::Field;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const HELP: &str = "\
+rbo parquet
+Compare the pairs of rankings stored in the rows of a Parquet file, e.g. the
+slates shown to every user, in parallel, and print one result per row.
+
+USAGE:
+    rbo parquet [-p] [OPTIONS] <FILE>
+
+Every row holds a group id and two lists of items, by default in the columns
+group_id, list_a and list_b. Rows that fail are reported on stderr.
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 [default: 0.9]
+    --group <COLUMN>        Column holding the group id of a row [default: group_id]
+    --first <COLUMN>        Column holding the first list [default: list_a]
+    --second <COLUMN>       Column holding the second list [default: list_b]
+    --depth <K>             Only evaluate the first K items of every list
+    --jobs <N>              Number of threads comparing rows [default: number of cores]
+    --out <FILE>            Write the results to FILE instead of stdout, as Parquet if
+                            FILE ends in `.parquet` and as CSV otherwise
+";
+
+const COLUMNS: [&str; 6] = [
+    "group_id",
+    "first_depth",
+    "second_depth",
+    "min",
+    "residual",
+    "extrapolated",
+];
+
+#[derive(Debug)]
+struct ParquetArgs {
+    p: f64,
+    group: String,
+    first: String,
+    second: String,
+    depth: Option<usize>,
+    jobs: Option<usize>,
+    out: Option<PathBuf>,
+    file: PathBuf,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<ParquetArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = ParquetArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        group: pargs
+            .opt_value_from_str("--group")?
+            .unwrap_or_else(|| "group_id".to_string()),
+        first: pargs
+            .opt_value_from_str("--first")?
+            .unwrap_or_else(|| "list_a".to_string()),
+        second: pargs
+            .opt_value_from_str("--second")?
+            .unwrap_or_else(|| "list_b".to_string()),
+        depth: pargs.opt_value_from_str("--depth")?,
+        jobs: pargs.opt_value_from_str("--jobs")?,
+        out: pargs.opt_value_from_str("--out")?,
+        file: pargs.free_from_str()?,
+    };
+    Ok(args)
+}
+
+// the group id and the two lists of a row
+struct Slates {
+    group: String,
+    first: Vec<String>,
+    second: Vec<String>,
+}
+
+// a scalar as text, strings without quotes
+fn text(field: &Field) -> String {
+    match field {
+        Field::Str(s) => s.clone(),
+        field => field.to_string(),
+    }
+}
+
+fn read_rows(path: &Path, args: &ParquetArgs) -> anyhow::Result<Vec<Slates>> {
+    let reader = SerializedFileReader::try_from(path)?;
+    let mut rows = Vec::new();
+    for (number, row) in reader.into_iter().enumerate() {
+        let row = row?;
+        let column = |name: &str| {
+            row.get_column_iter()
+                .find(|(column, _)| *column == name)
+                .map(|(_, field)| field)
+                .ok_or_else(|| anyhow::anyhow!("{} has no column {}", path.display(), name))
+        };
+        let list = |name: &str| -> anyhow::Result<Vec<String>> {
+            match column(name)? {
+                Field::ListInternal(list) => {
+                    let items = list.elements().iter().map(text);
+                    Ok(items.take(args.depth.unwrap_or(usize::MAX)).collect())
+                }
+                _ => anyhow::bail!("row {}: column {} is not a list", number + 1, name),
+            }
+        };
+        rows.push(Slates {
+            group: text(column(&args.group)?),
+            first: list(&args.first)?,
+            second: list(&args.second)?,
+        });
+    }
+    Ok(rows)
+}
+
+fn write_csv(out: &mut dyn Write, results: &[(&str, rbo::RboDetailed)]) -> anyhow::Result<()> {
+    writeln!(out, "{}", COLUMNS.join(","))?;
+    for (group, detailed) in results {
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            crate::output::csv_field(group),
+            detailed.first_depth,
+            detailed.second_depth,
+            detailed.rbo.min,
+            detailed.rbo.residual,
+            detailed.rbo.extrapolated
+        )?;
+    }
+    Ok(())
+}
+
+fn write_parquet(path: &Path, results: &[(&str, rbo::RboDetailed)]) -> anyhow::Result<()> {
+    let schema = ::parquet::schema::parser::parse_message_type(
+        "message rbo {
+            REQUIRED BYTE_ARRAY group_id (UTF8);
+            REQUIRED INT64 first_depth;
+            REQUIRED INT64 second_depth;
+            REQUIRED DOUBLE min;
+            REQUIRED DOUBLE residual;
+            REQUIRED DOUBLE extrapolated;
+        }",
+    )?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let file = std::fs::File::create(path)?;
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))?;
+    let mut row_group = writer.next_row_group()?;
+    let depth = |depth: usize| i64::try_from(depth).unwrap_or(i64::MAX);
+    for column in COLUMNS {
+        let mut writer = row_group
+            .next_column()?
+            .ok_or_else(|| anyhow::anyhow!("missing column {column}"))?;
+        match column {
+            "group_id" => {
+                let values: Vec<ByteArray> =
+                    results.iter().map(|(group, _)| (*group).into()).collect();
+                writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(&values, None, None)?;
+            }
+            "first_depth" | "second_depth" => {
+                let values: Vec<i64> = results
+                    .iter()
+                    .map(|(_, detailed)| match column {
+                        "first_depth" => depth(detailed.first_depth),
+                        _ => depth(detailed.second_depth),
+                    })
+                    .collect();
+                writer
+                    .typed::<Int64Type>()
+                    .write_batch(&values, None, None)?;
+            }
+            _ => {
+                let values: Vec<f64> = results
+                    .iter()
+                    .map(|(_, detailed)| match column {
+                        "min" => detailed.rbo.min,
+                        "residual" => detailed.rbo.residual,
+                        _ => detailed.rbo.extrapolated,
+                    })
+                    .collect();
+                writer
+                    .typed::<DoubleType>()
+                    .write_batch(&values, None, None)?;
+            }
+        }
+        writer.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    anyhow::ensure!(args.depth != Some(0), "--depth must be at least 1");
+    anyhow::ensure!(args.jobs != Some(0), "--jobs must be at least 1");
+    let rows = read_rows(&args.file, &args)?;
+    let jobs = args
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from));
+
+    // every thread compares a contiguous chunk of rows, kept in order
+    let chunk = rows.len().div_ceil(jobs).max(1);
+    let compared: Vec<_> = std::thread::scope(|scope| {
+        let workers: Vec<_> = rows
+            .chunks(chunk)
+            .map(|rows| {
+                scope.spawn(|| {
+                    rows.iter()
+                        .map(|row| rbo::rbo_detailed(&row.first, &row.second, args.p))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("worker panicked"))
+            .collect()
+    });
+
+    let mut results = Vec::with_capacity(rows.len());
+    let mut failed = 0;
+    for (row, result) in rows.iter().zip(compared) {
+        match result {
+            Ok(detailed) => results.push((row.group.as_str(), detailed)),
+            Err(err) => {
+                failed += 1;
+                eprintln!("{}: {}", row.group, err);
+            }
+        }
+    }
+    match &args.out {
+        Some(path) if path.extension().is_some_and(|ext| ext == "parquet") => {
+            write_parquet(path, &results)?
+        }
+        Some(path) => {
+            let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+            write_csv(&mut out, &results)?;
+            out.flush()?;
+        }
+        None => {
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            write_csv(&mut out, &results)?;
+            out.flush()?;
+        }
+    }
+    anyhow::ensure!(failed == 0, "{} of {} rows failed", failed, rows.len());
+    Ok(())
+}
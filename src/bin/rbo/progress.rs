@@ -0,0 +1,46 @@
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+// how often a report is printed at most
+pub(crate) const INTERVAL: Duration = Duration::from_secs(1);
+
+// the depth and RBO of a comparison so far, reported on stderr in place on a
+// terminal and one line per report otherwise
+pub(crate) struct Progress {
+    start: Instant,
+    terminal: bool,
+    reported: bool,
+}
+
+impl Progress {
+    pub(crate) fn new() -> Self {
+        Progress {
+            start: Instant::now(),
+            terminal: std::io::stderr().is_terminal(),
+            reported: false,
+        }
+    }
+
+    pub(crate) fn report(&mut self, depth: usize, rbo: rbo::Rbo) {
+        let line = format!(
+            "depth {}: min={:.4} residual={:.4} after {}s",
+            depth,
+            rbo.min,
+            rbo.residual,
+            self.start.elapsed().as_secs()
+        );
+        if self.terminal {
+            eprint!("\r{}", line);
+        } else {
+            eprintln!("{}", line);
+        }
+        self.reported = true;
+    }
+
+    // end the report line left on a terminal
+    pub(crate) fn finish(self) {
+        if self.terminal && self.reported {
+            eprintln!();
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use crate::input;
+use crate::output::{csv_field, OutputFormat};
+use serde::Deserialize;
+use std::cell::Cell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const HELP: &str = "\
+rbo run
+Run the comparisons declared in a TOML job file and write all results to one
+destination, one row per job, pair of files and persistence value.
+
+USAGE:
+    rbo run <JOBS_FILE>
+
+Example job file:
+
+    output = \"nightly.csv\"          # destination, stdout if left out
+    format = \"csv\"                  # text, json, csv or tsv [default: csv]
+
+    [defaults]                      # options of every job, unless it overrides them
+    p = 0.9
+    lowercase = true
+
+    [[job]]
+    name = \"bm25-vs-dense\"
+    first = \"runs/bm25.txt\"
+    second = \"runs/dense.txt\"
+    p = [0.9, 0.98]
+
+    [[job]]
+    name = \"daily\"
+    first = \"snapshots/today/*.txt\"   # `*` and `?` match within a path component
+    second = \"snapshots/yesterday/*.txt\"
+
+Files matched by two patterns are paired by file name; a single file is compared
+with every file matched by the other pattern. Jobs and [defaults] take the options
+p, depth, field, delimiter, trim, lowercase, normalize_unicode and dedup, named as
+the options of `rbo`. Comparisons that fail are reported on stderr.
+";
+
+// the options of a job, or of every job in `[defaults]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Options {
+    p: Option<Persistences>,
+    depth: Option<usize>,
+    field: Option<usize>,
+    delimiter: Option<String>,
+    trim: Option<bool>,
+    lowercase: Option<bool>,
+    normalize_unicode: Option<String>,
+    dedup: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Persistences {
+    One(f64),
+    Many(Vec<f64>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Job {
+    name: Option<String>,
+    first: String,
+    second: String,
+    p: Option<Persistences>,
+    depth: Option<usize>,
+    field: Option<usize>,
+    delimiter: Option<String>,
+    trim: Option<bool>,
+    lowercase: Option<bool>,
+    normalize_unicode: Option<String>,
+    dedup: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobFile {
+    output: Option<PathBuf>,
+    format: Option<String>,
+    #[serde(default)]
+    defaults: Options,
+    #[serde(rename = "job", default)]
+    jobs: Vec<Job>,
+}
+
+// a job with the defaults filled in
+struct Settings {
+    ps: Vec<f64>,
+    list: input::ListOptions,
+    dedup: bool,
+}
+
+fn settings(job: &Job, defaults: &Options) -> anyhow::Result<Settings> {
+    let ps = match job.p.clone().or_else(|| defaults.p.clone()) {
+        None => vec![0.9],
+        Some(Persistences::One(p)) => vec![p],
+        Some(Persistences::Many(ps)) => ps,
+    };
+    anyhow::ensure!(!ps.is_empty(), "p lists no persistence value");
+    let delimiter = job.delimiter.as_ref().or(defaults.delimiter.as_ref());
+    let normalize = job
+        .normalize_unicode
+        .as_ref()
+        .or(defaults.normalize_unicode.as_ref());
+    let list = input::ListOptions {
+        depth: job.depth.or(defaults.depth),
+        field: job.field.or(defaults.field),
+        delimiter: delimiter
+            .map(|delimiter| crate::parse_delimiter(delimiter))
+            .transpose()
+            .map_err(|err| anyhow::anyhow!("delimiter: {err}"))?,
+        trim: job.trim.or(defaults.trim).unwrap_or(false),
+        lowercase: job.lowercase.or(defaults.lowercase).unwrap_or(false),
+        normalize: normalize
+            .map(|form| input::parse_unicode_form(form))
+            .transpose()
+            .map_err(|err| anyhow::anyhow!("normalize_unicode: {err}"))?,
+    };
+    list.validate()?;
+    Ok(Settings {
+        ps,
+        list,
+        dedup: job.dedup.or(defaults.dedup).unwrap_or(false),
+    })
+}
+
+// whether `name` matches `pattern`, where `*` matches any run of characters
+// and `?` any single character
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, _) => name.is_empty(),
+        (Some('*'), _) => {
+            matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => matches(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+// the files matching `pattern`, sorted. A pattern without wildcards is
+// taken as it is.
+fn expand(pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
+    if !pattern.contains(['*', '?']) {
+        return Ok(vec![PathBuf::from(pattern)]);
+    }
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let part = component.as_os_str().to_string_lossy();
+        if !part.contains(['*', '?']) {
+            paths.iter_mut().for_each(|path| path.push(component));
+            continue;
+        }
+        let part: Vec<char> = part.chars().collect();
+        let mut matched = Vec::new();
+        for path in &paths {
+            let dir = if path.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                path.as_path()
+            };
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries {
+                let name = entry?.file_name();
+                let chars: Vec<char> = name.to_string_lossy().chars().collect();
+                if matches(&part, &chars) {
+                    matched.push(path.join(name));
+                }
+            }
+        }
+        paths = matched;
+    }
+    paths.sort();
+    anyhow::ensure!(!paths.is_empty(), "no file matches {pattern}");
+    Ok(paths)
+}
+
+// the pairs of files of a job, matched by file name if both sides are
+// patterns matching several files
+fn pairs(job: &Job) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    let (first, second) = (expand(&job.first)?, expand(&job.second)?);
+    Ok(match (&first[..], &second[..]) {
+        ([first], _) => second.iter().map(|s| (first.clone(), s.clone())).collect(),
+        (_, [second]) => first.iter().map(|f| (f.clone(), second.clone())).collect(),
+        _ => {
+            let mut pairs = Vec::new();
+            for f in &first {
+                match second.iter().find(|s| s.file_name() == f.file_name()) {
+                    Some(s) => pairs.push((f.clone(), s.clone())),
+                    None => eprintln!("{} has no counterpart in {}", f.display(), job.second),
+                }
+            }
+            pairs
+        }
+    })
+}
+
+fn compare(
+    first: &Path,
+    second: &Path,
+    settings: &Settings,
+) -> anyhow::Result<Vec<rbo::RboDetailed>> {
+    let duplicates: Option<[Rc<Cell<usize>>; 2]> = settings.dedup.then(Default::default);
+    let results = crate::compare_files(
+        first,
+        second,
+        &settings.ps,
+        settings.list,
+        duplicates.as_ref(),
+    )?;
+    for (path, counter) in [first, second].iter().zip(duplicates.iter().flatten()) {
+        eprintln!(
+            "dropped {} duplicates from {}",
+            counter.get(),
+            path.display()
+        );
+    }
+    Ok(results)
+}
+
+const COLUMNS: [&str; 9] = [
+    "job",
+    "first",
+    "second",
+    "p",
+    "first_depth",
+    "second_depth",
+    "min",
+    "residual",
+    "extrapolated",
+];
+
+// a result row of the job `name` in `format`
+fn row(
+    format: OutputFormat,
+    name: &str,
+    first: &Path,
+    second: &Path,
+    detailed: &rbo::RboDetailed,
+) -> String {
+    let (first, second) = (first.to_string_lossy(), second.to_string_lossy());
+    let values = [
+        detailed.p.to_string(),
+        detailed.first_depth.to_string(),
+        detailed.second_depth.to_string(),
+        detailed.rbo.min.to_string(),
+        detailed.rbo.residual.to_string(),
+        detailed.rbo.extrapolated.to_string(),
+    ];
+    match format {
+        OutputFormat::Text => format!(
+            "{}\t{}\t{}\tp={}\t{}",
+            name, first, second, detailed.p, detailed.rbo
+        ),
+        OutputFormat::Json => serde_json::json!({
+            "job": name,
+            "first": first,
+            "second": second,
+            "p": detailed.p,
+            "first_depth": detailed.first_depth,
+            "second_depth": detailed.second_depth,
+            "min": detailed.rbo.min,
+            "residual": detailed.rbo.residual,
+            "extrapolated": detailed.rbo.extrapolated,
+        })
+        .to_string(),
+        OutputFormat::Csv => [csv_field(name), csv_field(&first), csv_field(&second)]
+            .into_iter()
+            .chain(values)
+            .collect::<Vec<_>>()
+            .join(","),
+        OutputFormat::Tsv => [name.to_string(), first.into(), second.into()]
+            .into_iter()
+            .chain(values)
+            .collect::<Vec<_>>()
+            .join("\t"),
+    }
+}
+
+pub(crate) fn run(mut pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let path: PathBuf = match pargs.free_from_str() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    let jobs: JobFile = toml::from_str(&std::fs::read_to_string(&path)?)
+        .map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))?;
+    let format: OutputFormat = match &jobs.format {
+        Some(format) => format
+            .parse()
+            .map_err(|err| anyhow::anyhow!("format: {err}"))?,
+        None => OutputFormat::Csv,
+    };
+    // every job is checked before the first one runs
+    let planned = jobs
+        .jobs
+        .iter()
+        .enumerate()
+        .map(|(i, job)| {
+            let name = job.name.clone().unwrap_or_else(|| format!("job {}", i + 1));
+            let settings = settings(job, &jobs.defaults);
+            let pairs = settings.and_then(|settings| Ok((settings, pairs(job)?)));
+            pairs
+                .map(|(settings, pairs)| (name.clone(), settings, pairs))
+                .map_err(|err| anyhow::anyhow!("{name}: {err:#}"))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut out: Box<dyn Write> = match &jobs.output {
+        Some(output) if output.as_os_str() != "-" => {
+            Box::new(std::io::BufWriter::new(std::fs::File::create(output)?))
+        }
+        _ => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    match format {
+        OutputFormat::Csv => writeln!(out, "{}", COLUMNS.join(","))?,
+        OutputFormat::Tsv => writeln!(out, "{}", COLUMNS.join("\t"))?,
+        OutputFormat::Text | OutputFormat::Json => {}
+    }
+    let (mut failed, mut total) = (0, 0);
+    for (name, settings, pairs) in &planned {
+        for (first, second) in pairs {
+            total += 1;
+            match compare(first, second, settings) {
+                Ok(results) => {
+                    for detailed in &results {
+                        writeln!(out, "{}", row(format, name, first, second, detailed))?;
+                    }
+                }
+                Err(err) => {
+                    failed += 1;
+                    eprintln!(
+                        "{}: {} vs {}: {:#}",
+                        name,
+                        first.display(),
+                        second.display(),
+                        err
+                    );
+                }
+            }
+        }
+    }
+    out.flush()?;
+    anyhow::ensure!(failed == 0, "{} of {} comparisons failed", failed, total);
+    Ok(())
+}
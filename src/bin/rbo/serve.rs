@@ -0,0 +1,291 @@
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+const HELP: &str = "\
+rbo serve
+Answer comparison requests read from stdin, one JSON object per line, with one
+JSON result per line on stdout, so that another process can run many
+comparisons without spawning rbo for every one.
+
+USAGE:
+    rbo serve [-p] [--http <ADDR>]
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p of requests that give none [default: 0.9]
+    --http <ADDR>           Answer requests POSTed to http://ADDR/rbo instead, e.g.
+                            --http 0.0.0.0:8080. Needs the `http` feature
+
+Every request holds the two lists as arrays of strings or numbers, and
+optionally p and an id echoed in the result:
+
+    {\"id\": 7, \"first\": [\"a\", \"b\", \"c\"], \"second\": [\"b\", \"a\"], \"p\": 0.9}
+
+and is answered, in order, with
+
+    {\"id\": 7, \"min\": ..., \"residual\": ..., \"extrapolated\": ...}
+
+or with {\"id\": 7, \"error\": \"...\"} if it can not be compared. Blank lines are
+skipped and the results are flushed after every line. Ends at the end of stdin.
+
+Over HTTP the body of a request is the same JSON object, without an id, and
+invalid requests are answered with status 400. GET http://ADDR/metrics returns
+Prometheus metrics: the number of requests by status and histograms of their
+latency and of the extrapolated RBO and residual of the results.
+";
+
+#[derive(Debug)]
+struct ServeArgs {
+    p: f64,
+    http: Option<String>,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<ServeArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = ServeArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        http: pargs.opt_value_from_str("--http")?,
+    };
+    Ok(args)
+}
+
+// the items of the list `name` of a request, numbers written out as text
+fn list(request: &Value, name: &str) -> anyhow::Result<Vec<String>> {
+    let Some(Value::Array(items)) = request.get(name) else {
+        anyhow::bail!("`{name}` must be an array of items");
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            Value::String(item) => Ok(item.clone()),
+            Value::Number(item) => Ok(item.to_string()),
+            _ => anyhow::bail!("the items of `{name}` must be strings or numbers"),
+        })
+        .collect()
+}
+
+fn compare(request: &Value, default_p: f64) -> anyhow::Result<rbo::Rbo> {
+    anyhow::ensure!(request.is_object(), "a request must be a JSON object");
+    let p = match request.get("p") {
+        None | Some(Value::Null) => default_p,
+        Some(p) => p
+            .as_f64()
+            .ok_or_else(|| anyhow::anyhow!("`p` must be a number"))?,
+    };
+    let (first, second) = (list(request, "first")?, list(request, "second")?);
+    Ok(rbo::rbo(&first, &second, p)?)
+}
+
+fn result(rbo: &rbo::Rbo) -> Value {
+    json!({
+        "min": rbo.min,
+        "residual": rbo.residual,
+        "extrapolated": rbo.extrapolated,
+    })
+}
+
+// the result of the request on `line`, with the request's id if it has one
+fn respond(line: &str, default_p: f64) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => return json!({ "error": format!("invalid JSON: {err}") }),
+    };
+    let mut response = match compare(&request, default_p) {
+        Ok(rbo) => result(&rbo),
+        Err(err) => json!({ "error": err.to_string() }),
+    };
+    if let Some(id) = request.get("id") {
+        response["id"] = id.clone();
+    }
+    response
+}
+
+// upper bounds of the buckets of the latency, in seconds, and of the
+// extrapolated RBO and residual histograms
+#[cfg(feature = "http")]
+const LATENCY_BUCKETS: &[f64] = &[1e-5, 1e-4, 1e-3, 0.01, 0.1, 1.0, 10.0];
+#[cfg(feature = "http")]
+const SCORE_BUCKETS: &[f64] = &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+#[cfg(feature = "http")]
+const RESIDUAL_BUCKETS: &[f64] = &[1e-4, 1e-3, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+// a Prometheus histogram, counting the observations up to every bucket bound
+#[cfg(feature = "http")]
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[cfg(feature = "http")]
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    // NaN, the extrapolated RBO of an empty list, is not observed
+    fn observe(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        for (bound, count) in self.bounds.iter().zip(&mut self.counts) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        use std::fmt::Write;
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} histogram");
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {count}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(out, "{name}_sum {}\n{name}_count {}", self.sum, self.count);
+    }
+}
+
+// what `GET /metrics` reports about the requests answered so far
+#[cfg(feature = "http")]
+struct Metrics {
+    ok: u64,
+    failed: u64,
+    latency: Histogram,
+    extrapolated: Histogram,
+    residual: Histogram,
+}
+
+#[cfg(feature = "http")]
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            ok: 0,
+            failed: 0,
+            latency: Histogram::new(LATENCY_BUCKETS),
+            extrapolated: Histogram::new(SCORE_BUCKETS),
+            residual: Histogram::new(RESIDUAL_BUCKETS),
+        }
+    }
+
+    fn record(&mut self, rbo: Option<&rbo::Rbo>, elapsed: std::time::Duration) {
+        self.latency.observe(elapsed.as_secs_f64());
+        match rbo {
+            Some(rbo) => {
+                self.ok += 1;
+                self.extrapolated.observe(rbo.extrapolated);
+                self.residual.observe(rbo.residual);
+            }
+            None => self.failed += 1,
+        }
+    }
+
+    // the Prometheus text exposition format
+    fn render(&self) -> String {
+        let mut out = format!(
+            "# HELP rbo_requests_total Comparison requests answered, by status\n\
+             # TYPE rbo_requests_total counter\n\
+             rbo_requests_total{{status=\"ok\"}} {}\n\
+             rbo_requests_total{{status=\"error\"}} {}\n",
+            self.ok, self.failed
+        );
+        self.latency.render(
+            &mut out,
+            "rbo_request_duration_seconds",
+            "Time taken to answer a comparison request",
+        );
+        self.extrapolated.render(
+            &mut out,
+            "rbo_extrapolated",
+            "Extrapolated RBO of the comparisons answered",
+        );
+        self.residual.render(
+            &mut out,
+            "rbo_residual",
+            "Residual of the comparisons answered",
+        );
+        out
+    }
+}
+
+#[cfg(feature = "http")]
+fn serve_http(address: &str, default_p: f64) -> anyhow::Result<()> {
+    use axum::extract::rejection::JsonRejection;
+    use axum::http::{header, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::Json;
+    use std::sync::{Arc, Mutex};
+
+    let metrics = Arc::new(Mutex::new(Metrics::new()));
+    let recorded = Arc::clone(&metrics);
+    let handler = move |request: Result<Json<Value>, JsonRejection>| async move {
+        let start = std::time::Instant::now();
+        let compared = request.map(|Json(request)| compare(&request, default_p));
+        let rbo = match &compared {
+            Ok(Ok(rbo)) => Some(rbo),
+            _ => None,
+        };
+        recorded
+            .lock()
+            .expect("metrics lock poisoned")
+            .record(rbo, start.elapsed());
+        match compared {
+            Ok(Ok(rbo)) => (StatusCode::OK, Json(result(&rbo))).into_response(),
+            Ok(Err(err)) => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": err.to_string() })),
+            )
+                .into_response(),
+            Err(rejection) => rejection.into_response(),
+        }
+    };
+    let report = move || async move {
+        let text = metrics.lock().expect("metrics lock poisoned").render();
+        ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text)
+    };
+    let app = axum::Router::new()
+        .route("/rbo", axum::routing::post(handler))
+        .route("/metrics", axum::routing::get(report));
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        eprintln!("listening on http://{}/rbo", listener.local_addr()?);
+        axum::serve(listener, app).await?;
+        Ok(())
+    })
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(address) = &args.http {
+        #[cfg(feature = "http")]
+        return serve_http(address, args.p);
+        #[cfg(not(feature = "http"))]
+        anyhow::bail!("rbo serve --http {address} needs the `http` feature");
+    }
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(out, "{}", respond(&line, args.p))?;
+        out.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::input;
+use std::io::BufRead;
+
+const HELP: &str = "\
+rbo test
+Test whether the similarity of two ranked lists is above chance: the second
+list is shuffled many times and its extrapolated RBO against the first list
+recomputed, giving the null distribution for lists of these lengths.
+
+USAGE:
+    rbo test [-p] [OPTIONS] <FIRST_RANKED_LIST_FILE> <SECOND_RANKED_LIST_FILE>
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 [default: 0.9]
+    --permutations <N>      Number of shuffles of the second list [default: 1000]
+    --seed <SEED>           Seed of the shuffles, to reproduce a test [default: random]
+    --depth <K>             Only evaluate the first K items of both lists
+    --field <N>             Compare the N-th field of every line (counting from 1)
+    --delimiter <CHAR>      Split lines into fields at CHAR rather than at whitespace
+    --trim                  Strip leading and trailing whitespace from every item
+    --lowercase             Lowercase every item
+    --normalize-unicode <F> Bring every item into Unicode normal form `nfc` or `nfkc`
+
+Prints the observed RBO, the mean RBO of the shuffles, and the empirical p-value,
+the fraction of shuffles at least as similar as the observed lists.
+";
+
+#[derive(Debug)]
+struct TestArgs {
+    p: f64,
+    permutations: usize,
+    seed: Option<u64>,
+    list: input::ListOptions,
+    first_ranked_list_file: std::path::PathBuf,
+    second_ranked_list_file: std::path::PathBuf,
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<TestArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = TestArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        permutations: pargs.opt_value_from_str("--permutations")?.unwrap_or(1000),
+        seed: pargs.opt_value_from_str("--seed")?,
+        list: input::ListOptions::parse(&mut pargs)?,
+        first_ranked_list_file: pargs.free_from_str()?,
+        second_ranked_list_file: pargs.free_from_str()?,
+    };
+    Ok(args)
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    let (first, second) = (&args.first_ranked_list_file, &args.second_ranked_list_file);
+    anyhow::ensure!(
+        first != second || first != std::path::Path::new("-"),
+        "only one ranked list can be read from stdin"
+    );
+    anyhow::ensure!(args.permutations > 0, "--permutations must be at least 1");
+    args.list.validate()?;
+    let read = |path| -> anyhow::Result<Vec<String>> {
+        Ok(input::open_ranked_list(path, args.list, None)?
+            .lines()
+            .collect::<Result<_, _>>()?)
+    };
+    let (first, second) = (read(first)?, read(second)?);
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let test = rbo::stats::permutation_test(&first, &second, args.p, args.permutations, seed)?;
+    println!("observed\t{:.4}", test.observed);
+    println!("null_mean\t{:.4}", test.null_mean);
+    println!("p_value\t{:.4}", test.p_value);
+    println!("permutations\t{}", test.permutations);
+    println!("seed\t{}", seed);
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use rbo::io::trec::{load_run, rbo_runs, RunOrder};
+use rbo::stats::RboSummary;
+use std::collections::BTreeMap;
+
+const HELP: &str = "\
+rbo trec
+Compare two TREC run files query by query and report the RBO of every query
+and its mean and median over all queries, in the format of trec_eval.
+
+USAGE:
+    rbo trec [-p] [OPTIONS] <FIRST_RUN> <SECOND_RUN>
+
+OPTIONS:
+    -p <PERSISTENCE>        Persistence value p where 0 <= p < 1.0 [default: 0.9]
+    --order <ORDER>         Rank the documents of a query by `score`, as trec_eval
+                            does, or by the `rank` column [default: score]
+    --depth <K>             Only evaluate the first K documents of every query
+
+Only queries retrieved by both runs are compared; the others are listed on stderr.
+";
+
+#[derive(Debug)]
+struct TrecArgs {
+    p: f64,
+    order: RunOrder,
+    depth: Option<usize>,
+    first_run: std::path::PathBuf,
+    second_run: std::path::PathBuf,
+}
+
+fn parse_order(value: &str) -> Result<RunOrder, &'static str> {
+    match value {
+        "score" => Ok(RunOrder::Score),
+        "rank" => Ok(RunOrder::Rank),
+        _ => Err("expected score or rank"),
+    }
+}
+
+fn parse_args(mut pargs: pico_args::Arguments) -> Result<TrecArgs, pico_args::Error> {
+    if pargs.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        std::process::exit(0);
+    }
+    let args = TrecArgs {
+        p: pargs.opt_value_from_str("-p")?.unwrap_or(0.9),
+        order: pargs
+            .opt_value_from_fn("--order", parse_order)?
+            .unwrap_or_default(),
+        depth: pargs.opt_value_from_str("--depth")?,
+        first_run: pargs.free_from_str()?,
+        second_run: pargs.free_from_str()?,
+    };
+    Ok(args)
+}
+
+// the rankings of `run` for the queries retrieved by `other` too, truncated
+// to `depth`; the other queries are listed on stderr
+fn shared_queries(
+    run: BTreeMap<String, Vec<String>>,
+    other: &BTreeMap<String, Vec<String>>,
+    name: &std::path::Path,
+    depth: Option<usize>,
+) -> BTreeMap<String, Vec<String>> {
+    run.into_iter()
+        .filter(|(qid, _)| {
+            let shared = other.contains_key(qid);
+            if !shared {
+                eprintln!("query {} is only retrieved by {}", qid, name.display());
+            }
+            shared
+        })
+        .map(|(qid, mut ranking)| {
+            ranking.truncate(depth.unwrap_or(usize::MAX));
+            (qid, ranking)
+        })
+        .collect()
+}
+
+pub(crate) fn run(pargs: pico_args::Arguments) -> anyhow::Result<()> {
+    let args = match parse_args(pargs) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    };
+    anyhow::ensure!(args.depth != Some(0), "--depth must be at least 1");
+    let first = load_run(&args.first_run, args.order)?;
+    let second = load_run(&args.second_run, args.order)?;
+    let first_shared = shared_queries(first, &second, &args.first_run, args.depth);
+    let second_shared = shared_queries(second, &first_shared, &args.second_run, args.depth);
+    let scores = rbo_runs(&first_shared, &second_shared, args.p)?;
+
+    for (qid, rbo_res) in &scores {
+        println!("{:<22}\t{}\t{:.4}", "rbo_min", qid, rbo_res.min);
+        println!("{:<22}\t{}\t{:.4}", "rbo_res", qid, rbo_res.residual);
+        println!("{:<22}\t{}\t{:.4}", "rbo_ext", qid, rbo_res.extrapolated);
+    }
+    let summary: RboSummary = scores.values().collect();
+    println!("{:<22}\tall\t{}", "num_q", summary.count);
+    if summary.count > 0 {
+        for (name, values) in [
+            ("rbo_min", summary.min),
+            ("rbo_res", summary.residual),
+            ("rbo_ext", summary.extrapolated),
+        ] {
+            println!("{:<22}\tall\t{:.4}", name, values.mean);
+            println!(
+                "{:<22}\tall\t{:.4}",
+                format!("{name}_median"),
+                values.median
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,402 @@
+use crate::options::{Domain, Duplicates, RboOptions};
+use crate::tail::Weighting;
+use crate::ties::TieVariant;
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+/// Which values of an [`Rbo`] a [`RboBuilder`] computes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outputs {
+    /// Compute the lower bound [`Rbo::min`]
+    pub min: bool,
+    /// Compute [`Rbo::residual`]
+    pub residual: bool,
+    /// Compute [`Rbo::extrapolated`]
+    pub extrapolated: bool,
+}
+
+impl Outputs {
+    /// Every value
+    pub const ALL: Outputs = Outputs {
+        min: true,
+        residual: true,
+        extrapolated: true,
+    };
+    /// Only the lower bound
+    pub const MIN: Outputs = Outputs {
+        min: true,
+        residual: false,
+        extrapolated: false,
+    };
+    /// Only the residual
+    pub const RESIDUAL: Outputs = Outputs {
+        min: false,
+        residual: true,
+        extrapolated: false,
+    };
+    /// Only the extrapolated value
+    pub const EXTRAPOLATED: Outputs = Outputs {
+        min: false,
+        residual: false,
+        extrapolated: true,
+    };
+}
+
+impl Default for Outputs {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+///
+/// Configuration of an RBO computation. Start from [`RboBuilder::new`] with the
+/// persistence, adjust what differs from the defaults and call
+/// [`compute`](RboBuilder::compute) for every pair of rankings.
+///
+/// ```
+/// use rbo::{Duplicates, Outputs, RboBuilder};
+///
+/// let builder = RboBuilder::new(0.9)
+///     .max_depth(100)
+///     .duplicates(Duplicates::DedupKeepFirst)
+///     .outputs(Outputs::EXTRAPOLATED);
+/// let first = vec!["a", "b", "c", "b"];
+/// let second = vec!["b", "a", "d"];
+/// let rbo = builder.compute(&first, &second).expect("valid rbo");
+/// assert!(rbo.extrapolated > 0.0);
+/// assert!(rbo.min.is_nan());
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboBuilder {
+    persistence: f64,
+    max_depth: Option<usize>,
+    options: RboOptions,
+    ties: TieVariant,
+    weighting: Weighting,
+    outputs: Outputs,
+    #[cfg_attr(feature = "serde", serde(default))]
+    validate: bool,
+}
+
+impl RboBuilder {
+    /// A computation with persistence `p`, evaluating the complete lists over
+    /// an indefinite domain, rejecting duplicates and computing every value.
+    /// `p` is validated by [`compute`](RboBuilder::compute).
+    pub fn new(p: f64) -> Self {
+        Self {
+            persistence: p,
+            max_depth: None,
+            options: RboOptions::default(),
+            ties: TieVariant::W,
+            weighting: Weighting::Head,
+            outputs: Outputs::ALL,
+            validate: false,
+        }
+    }
+
+    /// Only evaluate the lists down to rank `depth`, see [`rbo_at_depth`](crate::rbo_at_depth)
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// How duplicate items within a list are handled
+    pub fn duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.options.duplicates = duplicates;
+        self
+    }
+
+    /// The domain the ranked items are drawn from
+    pub fn domain(mut self, domain: Domain) -> Self {
+        self.options.domain = domain;
+        self
+    }
+
+    /// How tied items are handled by [`compute_ties`](RboBuilder::compute_ties)
+    pub fn ties(mut self, variant: TieVariant) -> Self {
+        self.ties = variant;
+        self
+    }
+
+    /// Which end of the lists weighs most. With [`Weighting::Tail`] the lists
+    /// are read from the bottom up as in [`rbo_tail`](crate::rbo_tail), so the
+    /// maximum depth and the ranks of duplicates count from the bottom.
+    pub fn weighting(mut self, weighting: Weighting) -> Self {
+        self.weighting = weighting;
+        self
+    }
+
+    /// Which values to compute. Values that are not requested are `NaN`.
+    pub fn outputs(mut self, outputs: Outputs) -> Self {
+        self.outputs = outputs;
+        self
+    }
+
+    /// Check every result against the invariants of RBO before returning it:
+    /// 0 <= min <= min + residual <= 1, an extrapolated value within [0, 1]
+    /// and, for
+    /// [`compute`](RboBuilder::compute), a lower bound that never decreases
+    /// and an upper bound that never increases with the evaluation depth. A
+    /// result breaking one is refused with [`RboError::InvariantViolated`].
+    /// Checking the depths costs a second pass over the lists, so this is
+    /// meant for catching numeric regressions rather than for every call.
+    ///
+    /// ```
+    /// let rbo = rbo::RboBuilder::new(0.9)
+    ///     .validate(true)
+    ///     .compute(&["a", "b", "c"], &["b", "a", "d"])
+    ///     .expect("valid rbo");
+    /// assert!(rbo.min <= rbo.extrapolated);
+    /// ```
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Compare items by `normalize(item)` instead of the items themselves,
+    /// e.g. lowercased or canonicalized URLs. Duplicates are detected after
+    /// normalization, so items that normalize to the same key collide. Set
+    /// the other options first, the returned [`Normalized`] only computes.
+    ///
+    /// ```
+    /// use rbo::{Duplicates, RboBuilder};
+    ///
+    /// let first = vec!["A", "b", "a"];
+    /// let second = vec!["a", "B"];
+    /// let rbo = RboBuilder::new(0.9)
+    ///     .duplicates(Duplicates::DedupKeepFirst)
+    ///     .normalize(|item: &&str| item.to_lowercase())
+    ///     .compute(&first, &second)
+    ///     .expect("valid rbo");
+    /// assert_eq!(rbo, rbo::rbo(&["a", "b"], &["a", "b"], 0.9).expect("valid rbo"));
+    /// ```
+    pub fn normalize<N>(self, normalize: N) -> Normalized<N> {
+        Normalized {
+            builder: self,
+            normalize,
+        }
+    }
+
+    /// The persistence of the computation
+    pub fn persistence(&self) -> f64 {
+        self.persistence
+    }
+
+    /// The domain and duplicate handling of the computation
+    pub fn options(&self) -> RboOptions {
+        self.options
+    }
+
+    fn mask(&self, rbo: Rbo) -> Rbo {
+        let keep = |wanted: bool, value: f64| if wanted { value } else { f64::NAN };
+        Rbo {
+            min: keep(self.outputs.min, rbo.min),
+            residual: keep(self.outputs.residual, rbo.residual),
+            extrapolated: keep(self.outputs.extrapolated, rbo.extrapolated),
+        }
+    }
+
+    ///
+    /// RBO between `first` and `second` as configured
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if lists contain duplicate items and duplicates are rejected
+    /// - Will return `Err` if the lists do not fit into a conjoint domain
+    /// - Will return `Err` if results are validated and break an invariant
+    ///
+    pub fn compute<Item>(&self, first: &[Item], second: &[Item]) -> Result<Rbo, RboError>
+    where
+        Item: Eq + Hash,
+    {
+        match self.weighting {
+            Weighting::Head => self.compute_head(first, second),
+            Weighting::Tail => {
+                let first: Vec<&Item> = first.iter().rev().collect();
+                let second: Vec<&Item> = second.iter().rev().collect();
+                self.compute_head(&first, &second)
+            }
+        }
+    }
+
+    // `compute` with the weight on the head of the lists
+    fn compute_head<Item>(&self, first: &[Item], second: &[Item]) -> Result<Rbo, RboError>
+    where
+        Item: Eq + Hash,
+    {
+        let depth = self.max_depth.unwrap_or(usize::MAX);
+        let first = &first[..depth.min(first.len())];
+        let second = &second[..depth.min(second.len())];
+        let p = self.persistence;
+        if self.options == RboOptions::default() && !self.validate {
+            // a single value has a cheaper dedicated computation
+            let single = match self.outputs {
+                Outputs::EXTRAPOLATED => Some(crate::rbo_ext(first, second, p)),
+                Outputs::MIN => Some(crate::rbo_min(first, second, p)),
+                Outputs::RESIDUAL => Some(crate::rbo_res(first, second, p)),
+                _ => None,
+            };
+            if let Some(value) = single {
+                let value = value?;
+                return Ok(self.mask(Rbo {
+                    min: value,
+                    residual: value,
+                    extrapolated: value,
+                }));
+            }
+        }
+        let rbo = crate::rbo_with_options(first, second, p, self.options)?;
+        if self.validate {
+            crate::invariants::check_bounds(rbo)?;
+            let duplicates = self.options.duplicates;
+            let curve = crate::options::curve_with_options(first, second, p, duplicates)?;
+            crate::invariants::check_curve(&curve)?;
+        }
+        Ok(self.mask(rbo))
+    }
+
+    ///
+    /// RBO between two rankings with ties, given as groups of tied items in
+    /// rank order, using the configured [`TieVariant`]. Tie groups starting
+    /// below the maximum depth are dropped. The domain and duplicate handling
+    /// do not apply: the domain is indefinite and duplicates are rejected.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if lists contain duplicate items
+    /// - Will return `Err` if results are validated and break an invariant
+    ///
+    pub fn compute_ties<Item>(
+        &self,
+        first: &[Vec<Item>],
+        second: &[Vec<Item>],
+    ) -> Result<Rbo, RboError>
+    where
+        Item: Eq + Hash,
+    {
+        match self.weighting {
+            Weighting::Head => self.compute_ties_head(first, second),
+            Weighting::Tail => {
+                self.compute_ties_head(&reversed_groups(first), &reversed_groups(second))
+            }
+        }
+    }
+
+    // `compute_ties` with the weight on the head of the lists
+    fn compute_ties_head<Item>(
+        &self,
+        first: &[Vec<Item>],
+        second: &[Vec<Item>],
+    ) -> Result<Rbo, RboError>
+    where
+        Item: Eq + Hash,
+    {
+        let depth = self.max_depth.unwrap_or(usize::MAX);
+        let first = &first[..groups_within(first, depth)];
+        let second = &second[..groups_within(second, depth)];
+        let rbo = crate::rbo_ties(first, second, self.persistence, self.ties)?;
+        if self.validate {
+            crate::invariants::check_bounds(rbo)?;
+        }
+        Ok(self.mask(rbo))
+    }
+}
+
+///
+/// An [`RboBuilder`] that compares normalized items, see
+/// [`RboBuilder::normalize`]
+///
+#[derive(Clone, Copy)]
+pub struct Normalized<N> {
+    builder: RboBuilder,
+    normalize: N,
+}
+
+impl<N> std::fmt::Debug for Normalized<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Normalized")
+            .field("builder", &self.builder)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<N> Normalized<N> {
+    /// The configuration the normalized items are compared with
+    pub fn builder(&self) -> &RboBuilder {
+        &self.builder
+    }
+
+    ///
+    /// RBO between the normalized `first` and `second` as configured
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if items of a list normalize to the same key and duplicates are rejected
+    /// - Will return `Err` if the lists do not fit into a conjoint domain
+    ///
+    pub fn compute<Item, K>(&self, first: &[Item], second: &[Item]) -> Result<Rbo, RboError>
+    where
+        N: Fn(&Item) -> K,
+        K: Eq + Hash,
+    {
+        let first: Vec<K> = first.iter().map(&self.normalize).collect();
+        let second: Vec<K> = second.iter().map(&self.normalize).collect();
+        self.builder.compute(&first, &second)
+    }
+
+    ///
+    /// RBO between two rankings with ties of normalized items, see
+    /// [`RboBuilder::compute_ties`]
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if items of the lists normalize to the same key
+    ///
+    pub fn compute_ties<Item, K>(
+        &self,
+        first: &[Vec<Item>],
+        second: &[Vec<Item>],
+    ) -> Result<Rbo, RboError>
+    where
+        N: Fn(&Item) -> K,
+        K: Eq + Hash,
+    {
+        let normalize = |groups: &[Vec<Item>]| -> Vec<Vec<K>> {
+            groups
+                .iter()
+                .map(|group| group.iter().map(&self.normalize).collect())
+                .collect()
+        };
+        self.builder
+            .compute_ties(&normalize(first), &normalize(second))
+    }
+}
+
+// the tie groups of `groups` from the bottom up
+fn reversed_groups<Item>(groups: &[Vec<Item>]) -> Vec<Vec<&Item>> {
+    groups
+        .iter()
+        .rev()
+        .map(|group| group.iter().collect())
+        .collect()
+}
+
+// the number of tie groups starting at or above rank `depth`
+fn groups_within<Item>(groups: &[Vec<Item>], depth: usize) -> usize {
+    let mut start = 0;
+    groups
+        .iter()
+        .take_while(|group| {
+            let within = start < depth;
+            start += group.len();
+            within
+        })
+        .count()
+}
@@ -0,0 +1,86 @@
+use crate::{cumulative_weight, RboError};
+
+///
+/// The fraction of the total RBO evaluation weight carried by the top
+/// `depth` ranks for persistence `p`, the same as [`cumulative_weight`].
+/// For example the top 10 ranks carry about 86% of the weight at p = 0.9.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+///
+pub fn top_weight(p: f64, depth: usize) -> Result<f64, RboError> {
+    cumulative_weight(p, depth)
+}
+
+///
+/// The persistence `p` that places the fraction `weight` of the total
+/// evaluation weight in the top `depth` ranks, the inverse of
+/// [`top_weight`]. Found by bisection as the weight of a fixed prefix
+/// decreases monotonically with `p`.
+///
+/// # Errors
+///
+/// - Will return `Err` if `depth` is zero or `weight` is not 0 < weight <= 1
+///
+pub fn persistence_for_weight(depth: usize, weight: f64) -> Result<f64, RboError> {
+    if depth == 0 || !(weight > 0.0 && weight <= 1.0) {
+        return Err(RboError::InvalidTarget);
+    }
+    let (mut low, mut high) = (0.0f64, 1.0f64);
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if top_weight(mid, depth)? > weight {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+///
+/// The smallest evaluation depth at which the residual of two rankings is
+/// at most `max_residual`, whatever the rankings look like. The residual is
+/// largest when the evaluated prefixes share no items, so this is the depth
+/// at which the residual of two disjoint prefixes drops below the target.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if `max_residual` is not positive
+/// - Will return `Err` if the depth would exceed the depth limit, for `p`
+///   close to 1 or a `max_residual` below the rounding error of the residual
+///
+pub fn depth_for_residual(p: f64, max_residual: f64) -> Result<usize, RboError> {
+    crate::state::validate_persistence(p)?;
+    if max_residual.is_nan() || max_residual <= 0.0 {
+        return Err(RboError::InvalidTarget);
+    }
+    // the residual at depth d is at least p^(2d), the weight of the depths
+    // beyond which the disjoint prefixes could agree fully
+    let lowest = (max_residual.ln() / (2.0 * p.ln())).ceil();
+    if lowest > crate::state::MAX_SERIES_DEPTH as f64 {
+        return Err(RboError::DepthLimitExceeded(lowest as usize));
+    }
+    let fits =
+        |depth: usize| crate::state::residual(depth as f64, depth as f64, 0.0, p) <= max_residual;
+    // the residual decreases with depth: find an upper bound, then bisect
+    let mut high = 1;
+    while !fits(high) {
+        if high > crate::state::MAX_SERIES_DEPTH {
+            return Err(RboError::DepthLimitExceeded(high));
+        }
+        high *= 2;
+    }
+    let mut low = 0;
+    while low < high {
+        let mid = (low + high) / 2;
+        if fits(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Ok(low)
+}
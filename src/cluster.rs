@@ -0,0 +1,193 @@
+//! Grouping of similar rankings by their pairwise RBO distance.
+
+use crate::matrix::RboMatrix;
+
+/// One merge of an agglomerative clustering. Rankings are the clusters
+/// `0..n`, the cluster formed by the `i`-th merge is `n + i`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Merge {
+    /// The smaller id of the two merged clusters
+    pub first: usize,
+    /// The larger id of the two merged clusters
+    pub second: usize,
+    /// The average RBO distance between the rankings of the two clusters
+    pub distance: f64,
+    /// The number of rankings in the merged cluster
+    pub size: usize,
+}
+
+/// The complete merge history of an agglomerative clustering, in order of
+/// increasing distance
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dendrogram {
+    /// The number of clustered rankings
+    pub leaves: usize,
+    /// The `leaves - 1` merges joining all rankings into one cluster
+    pub merges: Vec<Merge>,
+}
+
+impl Dendrogram {
+    ///
+    /// The flat clustering obtained by applying every merge at a distance of
+    /// at most `max_distance`. Returns the cluster label of every ranking,
+    /// numbered from 0 in order of each cluster's first ranking.
+    ///
+    #[must_use]
+    pub fn cut(&self, max_distance: f64) -> Vec<usize> {
+        // the cluster every leaf and merged cluster was merged into, if any
+        let mut parent: Vec<usize> = (0..self.leaves + self.merges.len()).collect();
+        for (i, merge) in self.merges.iter().enumerate() {
+            if merge.distance <= max_distance {
+                let merged = self.leaves + i;
+                parent[merge.first] = merged;
+                parent[merge.second] = merged;
+            }
+        }
+        let mut labels = vec![usize::MAX; self.leaves];
+        let mut label_of_root = std::collections::HashMap::new();
+        for (leaf, label) in labels.iter_mut().enumerate() {
+            let next = label_of_root.len();
+            *label = *label_of_root.entry(root(&parent, leaf)).or_insert(next);
+        }
+        labels
+    }
+}
+
+// the outermost cluster containing `id`
+fn root(parent: &[usize], mut id: usize) -> usize {
+    while parent[id] != id {
+        id = parent[id];
+    }
+    id
+}
+
+///
+/// Average-linkage (UPGMA) hierarchical clustering of the rankings of
+/// `matrix` by their RBO distance `1 - extrapolated`. The two closest
+/// clusters are merged until one is left, ties broken by the smaller cluster
+/// ids. Takes O(n^3) time and O(n^2) memory for n rankings.
+///
+/// ```
+/// let lists = vec![
+///     vec!["a", "b", "c", "d"],
+///     vec!["x", "y", "z"],
+///     vec!["a", "b", "d", "c"],
+///     vec!["y", "x", "z"],
+/// ];
+/// let matrix = rbo::matrix::rbo_matrix(&lists, 0.9).expect("valid rbo");
+/// let dendrogram = rbo::cluster::average_linkage(&matrix);
+/// assert_eq!(dendrogram.cut(0.5), vec![0, 1, 0, 1]);
+/// ```
+///
+#[must_use]
+pub fn average_linkage(matrix: &RboMatrix) -> Dendrogram {
+    let leaves = matrix.len();
+    let mut distances = matrix.distances();
+    // the cluster id and size of every active row of `distances`
+    let mut active: Vec<Option<(usize, usize)>> = (0..leaves).map(|i| Some((i, 1))).collect();
+    let mut merges = Vec::with_capacity(leaves.saturating_sub(1));
+    for step in 0..leaves.saturating_sub(1) {
+        // the distance, cluster ids and rows of the closest pair of clusters
+        let mut closest: Option<(f64, (usize, usize), usize, usize)> = None;
+        for i in 0..leaves {
+            let Some((id_i, _)) = active[i] else { continue };
+            for j in i + 1..leaves {
+                let Some((id_j, _)) = active[j] else { continue };
+                let ids = (id_i.min(id_j), id_i.max(id_j));
+                let distance = distances[i][j];
+                if closest.is_none_or(|(best, best_ids, _, _)| {
+                    distance < best || (distance == best && ids < best_ids)
+                }) {
+                    closest = Some((distance, ids, i, j));
+                }
+            }
+        }
+        let (distance, _, i, j) = closest.expect("at least two active clusters");
+        let ((id_i, size_i), (id_j, size_j)) = (
+            active[i].expect("active cluster"),
+            active[j].expect("active cluster"),
+        );
+        // the merged cluster takes row i, row j is retired
+        for k in 0..leaves {
+            if k != i && k != j && active[k].is_some() {
+                let merged = (size_i as f64 * distances[i][k] + size_j as f64 * distances[j][k])
+                    / (size_i + size_j) as f64;
+                distances[i][k] = merged;
+                distances[k][i] = merged;
+            }
+        }
+        active[i] = Some((leaves + step, size_i + size_j));
+        active[j] = None;
+        merges.push(Merge {
+            first: id_i.min(id_j),
+            second: id_i.max(id_j),
+            distance,
+            size: size_i + size_j,
+        });
+    }
+    Dendrogram { leaves, merges }
+}
+
+///
+/// The medoid of the rankings of `matrix`: the ranking with the smallest
+/// total RBO distance to all others, i.e. the most representative one. Ties
+/// are broken by the smaller index, `None` for an empty matrix.
+///
+#[must_use]
+pub fn medoid(matrix: &RboMatrix) -> Option<usize> {
+    (0..matrix.len())
+        .map(|i| {
+            let total: f64 = (0..matrix.len())
+                .map(|j| 1.0 - matrix.get(i, j).extrapolated)
+                .sum();
+            (i, total)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    fn corpus() -> crate::matrix::RboMatrix {
+        let lists: Vec<Vec<char>> = ["abcdef", "abcdfe", "uvwxyz", "uvwxzy", "abdcef", "qrst"]
+            .iter()
+            .map(|l| l.chars().collect())
+            .collect();
+        crate::matrix::rbo_matrix(&lists, 0.9).expect("valid rbo")
+    }
+
+    #[test]
+    fn average_linkage_groups_similar_rankings() {
+        let matrix = corpus();
+        let dendrogram = super::average_linkage(&matrix);
+        assert_eq!(dendrogram.leaves, 6);
+        assert_eq!(dendrogram.merges.len(), 5);
+        assert!(dendrogram
+            .merges
+            .windows(2)
+            .all(|pair| pair[0].distance <= pair[1].distance));
+        assert_eq!(dendrogram.merges.last().map(|merge| merge.size), Some(6));
+        assert_eq!(dendrogram.cut(0.5), vec![0, 0, 1, 1, 0, 2]);
+        assert_eq!(dendrogram.cut(-1.0), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(dendrogram.cut(1.0), vec![0; 6]);
+
+        // the first merge joins the closest pair at their RBO distance
+        let first = dendrogram.merges[0];
+        approx::assert_abs_diff_eq!(
+            first.distance,
+            1.0 - matrix.get(first.first, first.second).extrapolated
+        );
+    }
+
+    #[test]
+    fn medoid_is_most_central() {
+        let matrix = corpus();
+        assert_eq!(super::medoid(&matrix), Some(0));
+        let empty: Vec<Vec<char>> = Vec::new();
+        let matrix = crate::matrix::rbo_matrix(&empty, 0.9).expect("valid rbo");
+        assert_eq!(super::medoid(&matrix), None);
+        assert!(super::average_linkage(&matrix).merges.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::state::{next_power, residual, validate_persistence, zero_persistence};
+use crate::{check_duplicates, RboError};
+use std::collections::HashSet;
+use std::hash::Hash;
+
+// check `p` and both lists, returning the lengths of the shorter and the longer list
+fn check<Item: Eq + Hash>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+) -> Result<(usize, usize), RboError> {
+    validate_persistence(p)?;
+    check_duplicates(first, 0)?;
+    check_duplicates(second, 1)?;
+    let s = first.len().min(second.len());
+    let l = first.len().max(second.len());
+    Ok((s, l))
+}
+
+// walk both lists down to `depth`, calling `f(d, X_d)` for every depth d
+// without keeping the overlap of earlier depths around
+fn for_each_overlap<'a, Item, F>(first: &'a [Item], second: &'a [Item], depth: usize, mut f: F)
+where
+    Item: Eq + Hash,
+    F: FnMut(usize, f64),
+{
+    let mut seen: HashSet<&'a Item> = HashSet::with_capacity(4096);
+    let mut overlap = 0.0;
+    for d in 0..depth {
+        for item in [first.get(d), second.get(d)].into_iter().flatten() {
+            if !seen.insert(item) {
+                overlap += 1.0;
+            }
+        }
+        f(d + 1, overlap);
+    }
+}
+
+///
+/// Only the extrapolated RBO (equation 32), the same value as
+/// `rbo(first, second, p)?.extrapolated`. The overlap at each depth is folded
+/// into the sum as the lists are walked instead of being stored, and neither
+/// the minimum nor the residual is computed.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_ext<Item>(first: &[Item], second: &[Item], p: f64) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let (s, l) = check(first, second, p)?;
+    let (mut seen_sum, mut tail_sum) = (0.0, 0.0);
+    let (mut x_1, mut x_s, mut x_l) = (0.0, 0.0, 0.0);
+    let mut p_d = 1.0;
+    for_each_overlap(first, second, l, |d, x_d| {
+        p_d = next_power(p, d, p_d);
+        seen_sum += x_d * p_d / d as f64;
+        if d == 1 {
+            x_1 = x_d;
+        }
+        if d == s {
+            x_s = x_d;
+        }
+        if d > s {
+            tail_sum += (x_s * (d - s) as f64) / (s * d) as f64 * p_d;
+        }
+        x_l = x_d;
+    });
+    if p == 0.0 {
+        return Ok(zero_persistence(x_1, s).extrapolated);
+    }
+    let last = ((x_l - x_s) / l as f64 + (x_s / s as f64)) * p_d;
+    Ok((1.0 - p) / p * (seen_sum + tail_sum) + last)
+}
+
+///
+/// Only the lower bound on RBO (equation 11), the same value as
+/// `rbo(first, second, p)?.min`. Only the first `min(first.len(), second.len())`
+/// ranks are walked, as the tail of the longer list does not affect the bound.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_min<Item>(first: &[Item], second: &[Item], p: f64) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let (k, _) = check(first, second, p)?;
+    // sum_{d<k} X_d p^d / d and sum_{d<k} p^d / d
+    let (mut weighted, mut harmonic) = (0.0, 0.0);
+    let (mut x_1, mut x_k) = (0.0, 0.0);
+    let mut p_d = 1.0;
+    for_each_overlap(first, second, k, |d, x_d| {
+        p_d = next_power(p, d, p_d);
+        if d == 1 {
+            x_1 = x_d;
+        }
+        if d < k {
+            weighted += x_d * p_d / d as f64;
+            harmonic += p_d / d as f64;
+        } else {
+            x_k = x_d;
+        }
+    });
+    if p == 0.0 {
+        return Ok(zero_persistence(x_1, k).min);
+    }
+    let other = weighted - x_k * harmonic;
+    Ok((1.0 - p) / p * (other - (x_k * (1.0 - p).ln())))
+}
+
+///
+/// Only the residual (equation 30), the same value as
+/// `rbo(first, second, p)?.residual`. The residual depends on nothing but the
+/// list lengths and the size of their overlap, so no per-depth walk is done.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_res<Item>(first: &[Item], second: &[Item], p: f64) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let (s, l) = check(first, second, p)?;
+    let (shorter, longer) = if first.len() <= second.len() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let shorter: HashSet<&Item> = shorter.iter().collect();
+    let x_l = longer.iter().filter(|item| shorter.contains(item)).count();
+    Ok(residual(s as f64, l as f64, x_l as f64, p))
+}
+
+///
+/// The RBO distance `1 - rbo_ext(first, second, p)`, 0 for identical and 1
+/// for disjoint rankings. As RBO is a similarity in [0, 1], this is a
+/// dissimilarity suited to clustering or nearest neighbour search.
+///
+/// ```
+/// let first = vec!["a", "b", "c"];
+/// let distance = rbo::rbo_distance(&first, &first, 0.9).expect("valid rbo");
+/// assert!(distance.abs() < 1e-12);
+/// assert_eq!(rbo::rbo_distance(&first, &["d", "e"], 0.9).expect("valid rbo"), 1.0);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_distance<Item>(first: &[Item], second: &[Item], p: f64) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    Ok(1.0 - rbo_ext(first, second, p)?)
+}
@@ -0,0 +1,60 @@
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+/// The result of comparing one ranking against several weighted references
+#[derive(Debug)]
+pub struct Consensus {
+    /// Weighted combination of the per-reference results. As all three
+    /// quantities are linear in the weights, `combined.min + combined.residual`
+    /// is still an upper bound on the weighted agreement.
+    pub combined: Rbo,
+    /// RBO against each reference, in the order the references were given
+    pub components: Vec<Rbo>,
+    /// The weights used, normalized to sum to one
+    pub weights: Vec<f64>,
+}
+
+///
+/// Compare `ranking` against several reference rankings, each with a
+/// user-supplied weight, and combine the individual RBO results into a single
+/// weighted consensus score. Weights are normalized so they do not have to
+/// sum to one.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if any list contains duplicate items
+/// - Will return `Err` if a weight is negative or not finite, or if no reference has a positive weight
+///
+pub fn rbo_consensus<Item>(
+    ranking: &[Item],
+    references: &[(&[Item], f64)],
+    p: f64,
+) -> Result<Consensus, RboError>
+where
+    Item: Eq + Hash,
+{
+    let total: f64 = references.iter().map(|(_, w)| w).sum();
+    if references.iter().any(|(_, w)| !w.is_finite() || *w < 0.0) || total <= 0.0 {
+        return Err(RboError::InvalidWeights);
+    }
+    let weights: Vec<f64> = references.iter().map(|(_, w)| w / total).collect();
+    let components = references
+        .iter()
+        .map(|(reference, _)| crate::rbo(ranking, reference, p))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let weighted = |f: fn(&Rbo) -> f64| -> f64 {
+        components.iter().zip(&weights).map(|(r, w)| f(r) * w).sum()
+    };
+    let combined = Rbo {
+        min: weighted(|r| r.min),
+        residual: weighted(|r| r.residual),
+        extrapolated: weighted(|r| r.extrapolated),
+    };
+    Ok(Consensus {
+        combined,
+        components,
+        weights,
+    })
+}
@@ -0,0 +1,130 @@
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+/// The RBO result after evaluating both lists down to `depth`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboPoint {
+    /// The evaluation depth, counting from 1
+    pub depth: usize,
+    /// The overlap X_d of the top `depth` items of both lists
+    pub overlap: usize,
+    /// What [`rbo_at_depth`](crate::rbo_at_depth) returns for this depth
+    pub rbo: Rbo,
+}
+
+// prefix sums of p^d and p^d / d, so the sums in equations 11, 30 and 32 over
+// any depth range are O(1)
+struct PowerSums {
+    p: f64,
+    // powers[d] = sum_{i=1..=d} p^i
+    powers: Vec<f64>,
+    // harmonic[d] = sum_{i=1..=d} p^i / i
+    harmonic: Vec<f64>,
+}
+
+impl PowerSums {
+    fn new(p: f64, max_depth: usize) -> Self {
+        let mut powers = Vec::with_capacity(max_depth + 1);
+        let mut harmonic = Vec::with_capacity(max_depth + 1);
+        powers.push(0.0);
+        harmonic.push(0.0);
+        let mut p_d = 1.0;
+        for d in 1..=max_depth {
+            p_d = crate::state::next_power(p, d, p_d);
+            powers.push(powers[d - 1] + p_d);
+            harmonic.push(harmonic[d - 1] + p_d / d as f64);
+        }
+        Self {
+            p,
+            powers,
+            harmonic,
+        }
+    }
+
+    // sum_{i=from+1..=to} p^i / i
+    fn harmonic(&self, from: usize, to: usize) -> f64 {
+        self.harmonic[to.max(from)] - self.harmonic[from]
+    }
+
+    // sum_{i=from+1..=to} p^i
+    fn powers(&self, from: usize, to: usize) -> f64 {
+        self.powers[to.max(from)] - self.powers[from]
+    }
+
+    // equation 30 with all sums taken from the prefix arrays
+    fn residual(&self, s: usize, l: usize, x_l: f64) -> f64 {
+        let p = self.p;
+        let f = (s + l) as f64 - x_l;
+        let uf = f as usize;
+        let ln_1p = (1.0 / (1.0 - p)).ln();
+        p.powi(s as i32) + p.powi(l as i32)
+            - p.powf(f)
+            - ((1.0 - p) / p)
+                * (s as f64 * self.harmonic(s, uf)
+                    + l as f64 * self.harmonic(l, uf)
+                    + x_l * (ln_1p - self.harmonic(0, uf)))
+    }
+}
+
+///
+/// The RBO result at every depth: the d-th point is the result of evaluating
+/// only the top d items of both lists, as returned by
+/// [`rbo_at_depth`](crate::rbo_at_depth). Useful to see how min, residual and
+/// extrapolated converge with depth. All points are computed in a single pass
+/// using running sums, so this costs little more than [`rbo`](crate::rbo).
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_curve<Item>(first: &[Item], second: &[Item], p: f64) -> Result<Vec<RboPoint>, RboError>
+where
+    Item: Eq + Hash,
+{
+    let state = crate::evaluate(first, second, p)?;
+    let overlap = state.overlap();
+    let short = first.len().min(second.len());
+    let long = overlap.len() - 1;
+    let sums = PowerSums::new(p, 2 * long);
+    let scale = (1.0 - p) / p;
+
+    // running sum_{i=1..=l} X_i p^i / i, and the same sum below the shorter list
+    let mut weighted_overlap = 0.0;
+    let mut below_short = 0.0;
+    let mut p_l = 1.0;
+    let mut points = Vec::with_capacity(long);
+    for l in 1..=long {
+        p_l = crate::state::next_power(p, l, p_l);
+        if l <= short {
+            below_short = weighted_overlap;
+        }
+        let x_l = overlap[l];
+        weighted_overlap += x_l * p_l / l as f64;
+        let s = l.min(short);
+        let x_s = overlap[s];
+        // equation 11, which only looks at the top s
+        let min = scale
+            * (below_short - x_s * sums.harmonic(0, s.saturating_sub(1)) - x_s * (1.0 - p).ln());
+        // equation 32
+        let second = x_s / s as f64 * sums.powers(s, l) - x_s * sums.harmonic(s, l);
+        let third = ((x_l - x_s) / l as f64 + x_s / s as f64) * p_l;
+        let extrapolated = scale * (weighted_overlap + second) + third;
+        let rbo = if p == 0.0 {
+            crate::state::zero_persistence(overlap[1], s)
+        } else {
+            Rbo {
+                min,
+                residual: sums.residual(s, l, x_l),
+                extrapolated,
+            }
+        };
+        points.push(RboPoint {
+            depth: l,
+            overlap: x_l as usize,
+            rbo,
+        });
+    }
+    Ok(points)
+}
@@ -0,0 +1,61 @@
+use crate::state::RboState;
+use crate::{check_duplicates, Rbo, RboError};
+use std::hash::Hash;
+
+/// Which earlier snapshot each snapshot is compared with by [`rbo_drift`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DriftBaseline {
+    /// The snapshot right before it, for the volatility between snapshots
+    #[default]
+    Previous,
+    /// The first snapshot, for the cumulative drift away from the start
+    First,
+}
+
+///
+/// The drift of a ranking over a sequence of `snapshots`, e.g. daily search
+/// results for the same query. Entry `i` of the result is the RBO between
+/// snapshot `i + 1` and its `baseline`, so the result has one entry less
+/// than `snapshots`.
+///
+/// ```
+/// use rbo::DriftBaseline;
+///
+/// let snapshots = vec![vec!["a", "b", "c"], vec!["a", "c", "b"], vec!["c", "a", "b"]];
+/// let drift = rbo::rbo_drift(&snapshots, 0.9, DriftBaseline::Previous).expect("valid rbo");
+/// assert_eq!(drift.len(), 2);
+/// assert_eq!(drift[1], rbo::rbo(&snapshots[1], &snapshots[2], 0.9).expect("valid rbo"));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if a snapshot contains duplicate items. The reported
+///   list is the index of the snapshot.
+///
+pub fn rbo_drift<Item, L>(
+    snapshots: &[L],
+    p: f64,
+    baseline: DriftBaseline,
+) -> Result<Vec<Rbo>, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    crate::state::validate_persistence(p)?;
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        check_duplicates(snapshot.as_ref(), i)?;
+    }
+    (1..snapshots.len())
+        .map(|i| {
+            let base = match baseline {
+                DriftBaseline::Previous => i - 1,
+                DriftBaseline::First => 0,
+            };
+            let state = RboState::constant_memory(p)?;
+            let (first, second) = (snapshots[base].as_ref(), snapshots[i].as_ref());
+            crate::walk(state, first, second).into_result()
+        })
+        .collect()
+}
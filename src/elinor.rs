@@ -0,0 +1,88 @@
+//! Adapter for the [`elinor`](https://docs.rs/elinor) IR evaluation crate.
+//!
+//! elinor computes per-query metrics as maps from query id to score, which
+//! its statistical tests consume directly. The functions here compute RBO
+//! between two relevance stores (e.g. the runs of two systems) in the same
+//! shape, so RBO can sit next to nDCG or AP in an elinor based harness.
+//!
+//! ```
+//! use elinor::PredRelStoreBuilder;
+//!
+//! let mut a = PredRelStoreBuilder::new();
+//! a.add_record("q_1", "d_1", 0.9.into()).unwrap();
+//! a.add_record("q_1", "d_2", 0.5.into()).unwrap();
+//! let mut b = PredRelStoreBuilder::new();
+//! b.add_record("q_1", "d_2", 0.8.into()).unwrap();
+//! b.add_record("q_1", "d_1", 0.7.into()).unwrap();
+//!
+//! let scores = rbo::elinor::compute_rbo_scores(&a.build(), &b.build(), 0.9).expect("valid rbo");
+//! assert!(scores["q_1"] < 1.0);
+//! ```
+
+use crate::{Rbo, RboError};
+use elinor::relevance::RelevanceStore;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::hash::Hash;
+
+///
+/// Compute RBO per query between the rankings stored in `first` and
+/// `second`. Each ranking is the store's list of documents sorted by
+/// descending score, with ties broken by document id as elinor does.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if a query of `first` is missing from `second`
+///
+pub fn compute_rbo<K, T, U>(
+    first: &RelevanceStore<K, T>,
+    second: &RelevanceStore<K, U>,
+    p: f64,
+) -> Result<BTreeMap<K, Rbo>, RboError>
+where
+    K: Clone + Eq + Ord + Hash + Display,
+    T: Ord + Clone,
+    U: Ord + Clone,
+{
+    first
+        .query_ids()
+        .map(|query_id| {
+            let a = ranking(first.get_sorted(query_id).unwrap_or_default());
+            let b = second
+                .get_sorted(query_id)
+                .ok_or_else(|| RboError::MissingQuery(query_id.to_string()))?;
+            let b = ranking(b);
+            Ok((query_id.clone(), crate::rbo(&a, &b, p)?))
+        })
+        .collect()
+}
+
+fn ranking<K: Clone, T>(sorted: &[elinor::Relevance<K, T>]) -> Vec<K> {
+    sorted.iter().map(|r| r.doc_id.clone()).collect()
+}
+
+///
+/// Like [`compute_rbo`] but keeps only the extrapolated score, giving the
+/// query id to score map elinor's statistical tests expect.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if a query of `first` is missing from `second`
+///
+pub fn compute_rbo_scores<K, T, U>(
+    first: &RelevanceStore<K, T>,
+    second: &RelevanceStore<K, U>,
+    p: f64,
+) -> Result<BTreeMap<K, f64>, RboError>
+where
+    K: Clone + Eq + Ord + Hash + Display,
+    T: Ord + Clone,
+    U: Ord + Clone,
+{
+    Ok(compute_rbo(first, second, p)?
+        .into_iter()
+        .map(|(query_id, rbo)| (query_id, rbo.extrapolated))
+        .collect())
+}
@@ -0,0 +1,108 @@
+use crate::state::RboState;
+use crate::{Duplicate, Rbo, RboError};
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+///
+/// A reusable RBO computation for hot loops over many short rankings. The
+/// hash tables used to detect duplicates and matches are kept between calls
+/// to [`compute`](RboEngine::compute), so they are only allocated while
+/// they grow. The engine owns clones of the items it is comparing, which
+/// suits cheaply cloned items such as integer ids.
+///
+/// ```
+/// let mut engine = rbo::RboEngine::new();
+/// for (first, second) in [([1, 2, 3], [2, 1, 3]), ([4, 5, 6], [7, 8, 9])] {
+///     let rbo = engine.compute(&first, &second, 0.9).expect("valid rbo");
+///     assert_eq!(rbo, rbo::rbo(&first, &second, 0.9).expect("valid rbo"));
+/// }
+/// ```
+///
+#[derive(Debug)]
+pub struct RboEngine<Item, S = RandomState> {
+    // the walk over both lists
+    state: RboState<Item, fn(usize, f64, f64), S>,
+    // the first rank of every item of the list checked for duplicates
+    ranks: HashMap<Item, usize, S>,
+}
+
+impl<Item: Eq + Hash + Clone> RboEngine<Item> {
+    /// An engine hashing items with the default SipHash
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<Item: Eq + Hash + Clone> Default for RboEngine<Item> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Item: Eq + Hash + Clone, S: BuildHasher + Clone> RboEngine<Item, S> {
+    /// An engine hashing items with `hasher`, see [`rbo_with_hasher`](crate::rbo_with_hasher)
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            state: RboState::constant_memory_with_hasher(0.0, hasher.clone())
+                .expect("0 is a valid persistence"),
+            ranks: HashMap::with_hasher(hasher),
+        }
+    }
+
+    ///
+    /// RBO between `first` and `second`, the same result as [`rbo`](crate::rbo)
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if lists contain duplicate items
+    ///
+    pub fn compute(&mut self, first: &[Item], second: &[Item], p: f64) -> Result<Rbo, RboError> {
+        self.clear();
+        self.state.reset(p)?;
+        self.check_duplicates(first, 0)?;
+        self.check_duplicates(second, 1)?;
+        for (a, b) in first.iter().zip(second) {
+            self.state.update(a.clone(), Some(b.clone()));
+        }
+        // ensure we process the remainder if unequal lists
+        let remainder = match first.len().cmp(&second.len()) {
+            Ordering::Less => &second[first.len()..],
+            Ordering::Equal => &[],
+            Ordering::Greater => &first[second.len()..],
+        };
+        for item in remainder {
+            self.state.update(item.clone(), None);
+        }
+        let rbo = self.state.current();
+        self.clear();
+        Ok(rbo)
+    }
+
+    /// Drop the items of the last comparison, keeping the allocated memory
+    pub fn clear(&mut self) {
+        self.ranks.clear();
+        // any valid persistence, it is set again by `compute`
+        self.state.reset(0.0).expect("0 is a valid persistence");
+    }
+
+    // refuse `list`, the `list_index`-th list of the input, if it contains duplicates
+    fn check_duplicates(&mut self, list: &[Item], list_index: usize) -> Result<(), RboError> {
+        self.ranks.clear();
+        for (rank, item) in list.iter().enumerate() {
+            if let Some(&first) = self.ranks.get(item) {
+                let indices = (first..list.len()).filter(|&i| list[i] == *item).collect();
+                return Err(RboError::DuplicatesInList(Duplicate {
+                    list: list_index,
+                    indices,
+                    item_debug: None,
+                }));
+            }
+            self.ranks.insert(item.clone(), rank);
+        }
+        self.ranks.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use crate::state::{powers, Compensated};
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+/// How much a single item adds to, or costs, the RBO score
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ItemContribution<'a, Item> {
+    /// The item itself
+    pub item: &'a Item,
+    /// Rank in the first ranking, counting from 1
+    pub first_rank: Option<usize>,
+    /// Rank in the second ranking, counting from 1
+    pub second_rank: Option<usize>,
+    /// The agreement weight the item earns: it is part of the overlap at every
+    /// depth from the lower of its two ranks on. Zero for items in one list only.
+    pub contribution: f64,
+    /// The weight the item forfeits compared to being at its better rank in
+    /// both lists: the displacement cost of a shared item, or all of its
+    /// weight for an item in one list only
+    pub cost: f64,
+}
+
+/// The per-item breakdown of an RBO score
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RboExplanation<'a, Item> {
+    /// The RBO result being explained
+    pub rbo: Rbo,
+    /// Items of the first ranking in rank order, followed by the items only in the second
+    pub items: Vec<ItemContribution<'a, Item>>,
+}
+
+// the weight an item earns by being in the overlap from depth d on,
+// sum_{d' >= d} (1 - p) p^(d' - 1) / d', at index d - 1 for d = 1..=depth.
+// The sums of p^d' / d' below every depth are carried forward in one pass.
+fn weights_from(depth: usize, p: f64) -> Vec<f64> {
+    if p == 0.0 {
+        return (1..=depth)
+            .map(|d| if d == 1 { 1.0 } else { 0.0 })
+            .collect();
+    }
+    let total = (1.0 / (1.0 - p)).ln();
+    let mut below = Compensated::new();
+    let mut weights = Vec::with_capacity(depth);
+    for (d, p_d) in (1..=depth).zip(powers(p)) {
+        weights.push((1.0 - p) / p * (total - below.value()));
+        below.add(p_d / d as f64);
+    }
+    weights
+}
+
+///
+/// Explain an RBO score item by item. A shared item contributes to the
+/// agreement at every depth from the lower of its two ranks on, and for
+/// lists of equal length the contributions of all items sum to the `min` of
+/// the result. The `cost` of an item shows how much weight it loses through
+/// being displaced or missing from one of the rankings, so sorting by cost
+/// reveals the items driving a disagreement.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_explain<'a, Item>(
+    first: &'a [Item],
+    second: &'a [Item],
+    p: f64,
+) -> Result<RboExplanation<'a, Item>, RboError>
+where
+    Item: Eq + Hash,
+{
+    let rbo = crate::rbo(first, second, p)?;
+    let weights = weights_from(first.len().max(second.len()), p);
+    let items = crate::rank_movements(first, second)?
+        .into_iter()
+        .map(|movement| {
+            let ranks = [movement.source_rank, movement.target_rank];
+            let best = ranks.iter().flatten().min().copied().unwrap_or(1);
+            let potential = weights[best - 1];
+            let contribution = match ranks {
+                [Some(a), Some(b)] => weights[a.max(b) - 1],
+                _ => 0.0,
+            };
+            ItemContribution {
+                item: movement.item,
+                first_rank: movement.source_rank,
+                second_rank: movement.target_rank,
+                contribution,
+                cost: potential - contribution,
+            }
+        })
+        .collect();
+    Ok(RboExplanation { rbo, items })
+}
+
+///
+/// Like [`rbo_explain`], but for rankings of rich items compared by the key
+/// `key` extracts from each item, as [`rbo_by_key`](crate::rbo_by_key).
+/// Every contribution refers to the original item, so payloads such as titles,
+/// scores or sources come through unchanged without joining them back by key.
+/// For an item in both rankings that is the item of the first ranking, the
+/// one of the second is at `second_rank`.
+///
+/// ```
+/// struct SearchHit {
+///     docid: u64,
+///     title: &'static str,
+/// }
+///
+/// let first = vec![SearchHit { docid: 1, title: "rbo" }, SearchHit { docid: 2, title: "ndcg" }];
+/// let second = vec![SearchHit { docid: 3, title: "map" }, SearchHit { docid: 1, title: "rbo" }];
+/// let explanation =
+///     rbo::rbo_explain_by_key(&first, &second, 0.9, |hit| hit.docid).expect("valid rbo");
+/// let new = explanation.items.iter().find(|i| i.first_rank.is_none()).expect("new item");
+/// assert_eq!(new.item.title, "map");
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if two items of the same list have the same key
+///
+pub fn rbo_explain_by_key<'a, T, K, F>(
+    first: &'a [T],
+    second: &'a [T],
+    p: f64,
+    mut key: F,
+) -> Result<RboExplanation<'a, T>, RboError>
+where
+    K: Eq + Hash,
+    F: FnMut(&T) -> K,
+{
+    let first_keys: Vec<K> = first.iter().map(&mut key).collect();
+    let second_keys: Vec<K> = second.iter().map(&mut key).collect();
+    let explanation = rbo_explain(&first_keys, &second_keys, p)?;
+    let items = explanation
+        .items
+        .into_iter()
+        .map(|contribution| ItemContribution {
+            item: crate::movement::item_at(
+                first,
+                second,
+                contribution.first_rank,
+                contribution.second_rank,
+            ),
+            first_rank: contribution.first_rank,
+            second_rank: contribution.second_rank,
+            contribution: contribution.contribution,
+            cost: contribution.cost,
+        })
+        .collect();
+    Ok(RboExplanation {
+        rbo: explanation.rbo,
+        items,
+    })
+}
@@ -0,0 +1,203 @@
+//! A C interface, for linking the crate into C and C++ evaluation tools.
+//! The header `include/rbo.h` declares it and is generated with
+//! [cbindgen](https://github.com/mozilla/cbindgen):
+//!
+//! ```text
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! cbindgen --config cbindgen.toml --output include/rbo.h src/ffi.rs
+//! ```
+//!
+//! ```c
+//! #include "rbo.h"
+//!
+//! const char *first[] = {"a", "b", "c"};
+//! const char *second[] = {"b", "a", "d"};
+//! RboResult result;
+//! if (rbo_compute(first, 3, second, 3, 0.9, &result) == RBO_STATUS_OK) {
+//!     printf("%f %f %f\n", result.min, result.residual, result.ext);
+//! }
+//! ```
+
+use crate::{Rbo, RboError};
+use std::ffi::{c_char, CStr};
+
+/// The outcome of a call to the C interface
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RboStatus {
+    /// The result was written
+    Ok = 0,
+    /// The persistence is not 0 <= p < 1
+    InvalidPersistence = 1,
+    /// A list contains an item more than once
+    DuplicatesInList = 2,
+    /// A list with items, one of its items or the result is a null pointer
+    NullPointer = 3,
+    /// Rounding errors made the result meaningless, e.g. for `p` close to 0
+    NumericalInstability = 4,
+}
+
+/// The result of [`rbo`](crate::rbo) for C callers
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RboResult {
+    /// the lower bound on RBO
+    pub min: f64,
+    /// the maximum amount RBO can grow beyond `min`
+    pub residual: f64,
+    /// the extrapolated RBO
+    pub ext: f64,
+}
+
+impl From<Rbo> for RboResult {
+    fn from(rbo: Rbo) -> Self {
+        Self {
+            min: rbo.min,
+            residual: rbo.residual,
+            ext: rbo.extrapolated,
+        }
+    }
+}
+
+// the items of a C array of `len` strings
+//
+// Safety: see `rbo_compute`
+unsafe fn ranking<'a>(list: *const *const c_char, len: usize) -> Option<Vec<&'a CStr>> {
+    if len == 0 {
+        return Some(Vec::new());
+    }
+    if list.is_null() {
+        return None;
+    }
+    // SAFETY: `list` points to `len` pointers, as promised by the caller
+    let items = unsafe { std::slice::from_raw_parts(list, len) };
+    items
+        .iter()
+        // SAFETY: non-null items are nul-terminated strings, as promised by the caller
+        .map(|&item| (!item.is_null()).then(|| unsafe { CStr::from_ptr(item) }))
+        .collect()
+}
+
+///
+/// RBO between the `alen` strings of `a` and the `blen` strings of `b` with
+/// persistence `p`. Items are compared byte by byte, they need not be valid
+/// UTF-8. The result is written to `out` only if `RboStatus::Ok` is returned.
+///
+/// # Safety
+///
+/// - `a` and `b` must point to `alen` and `blen` pointers to nul-terminated
+///   strings, or may be null for empty lists
+/// - `out` must be null or point to memory valid for writing an `RboResult`
+/// - none of them may be modified during the call
+///
+#[no_mangle]
+pub unsafe extern "C" fn rbo_compute(
+    a: *const *const c_char,
+    alen: usize,
+    b: *const *const c_char,
+    blen: usize,
+    p: f64,
+    out: *mut RboResult,
+) -> RboStatus {
+    // SAFETY: forwarded from the caller
+    let (Some(first), Some(second)) = (unsafe { ranking(a, alen) }, unsafe { ranking(b, blen) })
+    else {
+        return RboStatus::NullPointer;
+    };
+    if out.is_null() {
+        return RboStatus::NullPointer;
+    }
+    match crate::rbo(&first, &second, p) {
+        Ok(rbo) => {
+            // SAFETY: `out` is valid for writes, as promised by the caller
+            unsafe { out.write(rbo.into()) };
+            RboStatus::Ok
+        }
+        Err(RboError::DuplicatesInList(_)) => RboStatus::DuplicatesInList,
+        Err(RboError::NumericalInstability) => RboStatus::NumericalInstability,
+        Err(_) => RboStatus::InvalidPersistence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rbo_compute, RboResult, RboStatus};
+    use std::ffi::{c_char, CString};
+
+    // RBO through the C interface
+    fn compute(first: &[&str], second: &[&str], p: f64) -> Result<RboResult, RboStatus> {
+        let strings = |list: &[&str]| -> Vec<CString> {
+            list.iter()
+                .map(|item| CString::new(*item).expect("no nul"))
+                .collect()
+        };
+        let (first, second) = (strings(first), strings(second));
+        let pointers =
+            |list: &[CString]| -> Vec<*const c_char> { list.iter().map(|s| s.as_ptr()).collect() };
+        let (a, b) = (pointers(&first), pointers(&second));
+        let mut out = RboResult {
+            min: 0.0,
+            residual: 0.0,
+            ext: 0.0,
+        };
+        // SAFETY: the pointers outlive the call
+        let status = unsafe { rbo_compute(a.as_ptr(), a.len(), b.as_ptr(), b.len(), p, &mut out) };
+        match status {
+            RboStatus::Ok => Ok(out),
+            status => Err(status),
+        }
+    }
+
+    #[test]
+    fn matches_rbo() {
+        let (first, second) = (["a", "b", "c", "d"], ["b", "a", "e"]);
+        let expected = crate::rbo(&first, &second, 0.9).expect("valid rbo");
+        assert_eq!(compute(&first, &second, 0.9), Ok(expected.into()));
+        assert_eq!(compute(&["x"], &["x"], 0.5).map(|rbo| rbo.ext), Ok(1.0));
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(
+            compute(&["a", "b", "a"], &["b"], 0.9),
+            Err(RboStatus::DuplicatesInList)
+        );
+        assert_eq!(
+            compute(&["a"], &["b"], 1.0),
+            Err(RboStatus::InvalidPersistence)
+        );
+        assert_eq!(
+            compute(&["a", "b"], &["b", "a"], 1e-12),
+            Err(RboStatus::NumericalInstability)
+        );
+        let item = CString::new("a").expect("no nul");
+        let items = [item.as_ptr(), std::ptr::null()];
+        let mut out = RboResult {
+            min: 0.0,
+            residual: 0.0,
+            ext: 0.0,
+        };
+        // SAFETY: null items and lists are rejected before they are read
+        unsafe {
+            assert_eq!(
+                rbo_compute(items.as_ptr(), 2, items.as_ptr(), 1, 0.9, &mut out),
+                RboStatus::NullPointer
+            );
+            assert_eq!(
+                rbo_compute(std::ptr::null(), 1, items.as_ptr(), 1, 0.9, &mut out),
+                RboStatus::NullPointer
+            );
+            assert_eq!(
+                rbo_compute(
+                    items.as_ptr(),
+                    1,
+                    items.as_ptr(),
+                    1,
+                    0.9,
+                    std::ptr::null_mut()
+                ),
+                RboStatus::NullPointer
+            );
+        }
+    }
+}
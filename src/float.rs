@@ -0,0 +1,86 @@
+use crate::state::{next_power, residual};
+use crate::{check_duplicates, Rbo, RboError};
+use num_traits::Float;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+///
+/// RBO computed entirely in the float type of `p`, e.g. `f32` for memory
+/// constrained batch jobs. Equations 11, 30 and 32 are evaluated from running
+/// sums exactly as by [`rbo`](crate::rbo), which is the `f64` version.
+///
+/// ```
+/// let first = vec!["a", "b", "c", "d"];
+/// let second = vec!["b", "a", "d", "e"];
+/// let single = rbo::rbo_float(&first, &second, 0.9_f32).expect("valid rbo");
+/// let double = rbo::rbo(&first, &second, 0.9).expect("valid rbo");
+/// assert!((f64::from(single.extrapolated) - double.extrapolated).abs() < 1e-6);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_float<F, Item>(first: &[Item], second: &[Item], p: F) -> Result<Rbo<F>, RboError>
+where
+    F: Float + Send + Sync,
+    Item: Eq + Hash,
+{
+    if !(p >= F::zero() && p < F::one()) {
+        return Err(RboError::InvalidPersistance);
+    }
+    check_duplicates(first, 0)?;
+    check_duplicates(second, 1)?;
+
+    let (s, l) = (first.len().min(second.len()), first.len().max(second.len()));
+    let depth = |d: usize| F::from(d).expect("depth fits the float type");
+    let mut seen: HashSet<&Item> = HashSet::with_capacity(4096);
+    let mut overlap = F::zero();
+    let (mut seen_sum, mut tail_sum) = (F::zero(), F::zero());
+    let (mut min_weighted, mut min_harmonic) = (F::zero(), F::zero());
+    let (mut x_1, mut x_s) = (F::zero(), F::zero());
+    let mut p_d = F::one();
+    for d in 1..=l {
+        for item in [first.get(d - 1), second.get(d - 1)].into_iter().flatten() {
+            if !seen.insert(item) {
+                overlap = overlap + F::one();
+            }
+        }
+        p_d = next_power(p, d, p_d);
+        let weighted = overlap * p_d / depth(d);
+        seen_sum = seen_sum + weighted;
+        if d == 1 {
+            x_1 = overlap;
+        }
+        if d <= s {
+            x_s = overlap;
+            min_weighted = min_weighted + weighted;
+            min_harmonic = min_harmonic + p_d / depth(d);
+        } else {
+            tail_sum = tail_sum + x_s * depth(d - s) / depth(s * d) * p_d;
+        }
+    }
+
+    let one = F::one();
+    if p == F::zero() {
+        // see `zero_persistence`, all the weight is on depth 1
+        let (min, extrapolated) = if s == 0 {
+            (F::zero(), F::nan())
+        } else {
+            (x_1, x_1)
+        };
+        return Ok(Rbo {
+            min,
+            residual: residual(depth(s), depth(l), overlap, p),
+            extrapolated,
+        });
+    }
+    let scale = (one - p) / p;
+    let last = ((overlap - x_s) / depth(l) + x_s / depth(s)) * p_d;
+    Ok(Rbo {
+        min: scale * (min_weighted - x_s * min_harmonic - x_s * (one - p).ln()),
+        residual: residual(depth(s), depth(l), overlap, p),
+        extrapolated: scale * (seen_sum + tail_sum) + last,
+    })
+}
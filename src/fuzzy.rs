@@ -0,0 +1,92 @@
+use crate::state::RboState;
+use crate::{Rbo, RboError};
+
+///
+/// RBO with approximate item matching: two items count as the same if
+/// `similarity` scores them at least `threshold`, e.g. a Jaro-Winkler
+/// similarity of 0.95 or more between two renderings of the same title or
+/// URL. With exact equality as similarity this is plain [`rbo`](crate::rbo).
+///
+/// Matching is injective, every item matches at most one item of the other
+/// ranking, and greedy per depth: the items arriving at a depth are matched
+/// to the still unmatched items of the other prefix, the most similar pairs
+/// first and, among equally similar pairs, those of higher rank first. Once
+/// made, a match is kept at all deeper depths, so the overlap never shrinks.
+///
+/// `similarity` is called O(n m) times for rankings of lengths n and m.
+/// Pairs it scores NaN never match.
+///
+/// ```
+/// let first = ["rust-lang.org", "docs.rs", "crates.io"];
+/// let second = ["www.rust-lang.org", "crates.io/", "docs.rs"];
+/// let same_site = |a: &&str, b: &&str| {
+///     let strip = |s: &str| s.trim_start_matches("www.").trim_end_matches('/').to_owned();
+///     if strip(a) == strip(b) { 1.0 } else { 0.0 }
+/// };
+/// let exact = rbo::rbo(&first, &second, 0.9).expect("valid rbo");
+/// let fuzzy = rbo::rbo_fuzzy(&first, &second, 0.9, 1.0, same_site).expect("valid rbo");
+/// assert!(fuzzy.min > exact.min);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if `threshold` is NaN
+/// - Will return `Err` if rounding errors make the result meaningless, e.g.
+///   for `p` close to 0
+///
+pub fn rbo_fuzzy<Item, F>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    threshold: f64,
+    mut similarity: F,
+) -> Result<Rbo, RboError>
+where
+    F: FnMut(&Item, &Item) -> f64,
+{
+    let mut state: RboState<()> = RboState::constant_memory(p)?;
+    if threshold.is_nan() {
+        return Err(RboError::InvalidTarget);
+    }
+    let lists = [first, second];
+    // whether the item at every rank of either list has been matched
+    let mut matched = [vec![false; first.len()], vec![false; second.len()]];
+    for depth in 0..first.len().max(second.len()) {
+        // candidate pairs (similarity, rank in first, rank in second) of the
+        // items arriving at this depth with the unmatched items of the other
+        // prefix, the pair of both new items only once
+        let mut candidates = Vec::new();
+        for (list, other) in [(0, 1), (1, 0)] {
+            if depth >= lists[list].len() {
+                continue;
+            }
+            let prefix = (depth + 1).min(lists[other].len());
+            let seen = if list == 1 { prefix.min(depth) } else { prefix };
+            for rank in (0..seen).filter(|&rank| !matched[other][rank]) {
+                let mut ranks = [0; 2];
+                ranks[list] = depth;
+                ranks[other] = rank;
+                let score = similarity(&first[ranks[0]], &second[ranks[1]]);
+                if score >= threshold {
+                    candidates.push((score, ranks[0], ranks[1]));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then_with(|| a.1.min(a.2).cmp(&b.1.min(b.2)))
+                .then_with(|| (a.1, a.2).cmp(&(b.1, b.2)))
+        });
+        let mut matches = 0.0;
+        for (_, i, j) in candidates {
+            if !matched[0][i] && !matched[1][j] {
+                matched[0][i] = true;
+                matched[1][j] = true;
+                matches += 1.0;
+            }
+        }
+        state.record(matches, depth < first.len().min(second.len()));
+    }
+    state.into_result()
+}
@@ -0,0 +1,59 @@
+use crate::RboError;
+use std::hash::Hash;
+
+/// The RBO weight gained and forfeited at a single depth
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DepthGain {
+    /// The depth, counting from 1
+    pub depth: usize,
+    /// The weight (1 - p) * p^(d-1) the user model assigns to this depth
+    pub weight: f64,
+    /// The agreement A_d = X_d / d at this depth
+    pub agreement: f64,
+    /// The part of the weight earned through agreement, weight * A_d
+    pub gained: f64,
+    /// The part of the weight lost through disagreement, weight * (1 - A_d)
+    pub forfeited: f64,
+}
+
+///
+/// Break the RBO score down by depth: RBO is the weighted sum of the
+/// agreements at every depth, so each depth contributes `weight * A_d` and
+/// forfeits the remainder of its weight. Summing `gained` over the returned
+/// depths gives the score accumulated by the visible prefix; depths with a
+/// large `forfeited` share are where the two rankings diverge.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_weight_gain<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+) -> Result<Vec<DepthGain>, RboError>
+where
+    Item: Eq + Hash,
+{
+    let state = crate::evaluate(first, second, p)?;
+    let mut weight = 1.0 - p;
+    let gains = state
+        .agreement()
+        .into_iter()
+        .enumerate()
+        .map(|(d, agreement)| {
+            let gain = DepthGain {
+                depth: d + 1,
+                weight,
+                agreement,
+                gained: weight * agreement,
+                forfeited: weight * (1.0 - agreement),
+            };
+            weight *= p;
+            gain
+        })
+        .collect();
+    Ok(gains)
+}
@@ -0,0 +1,366 @@
+use crate::state::RboState;
+use crate::{Duplicate, Rbo, RboDetailed, RboError};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+///
+/// RBO over two rankings that arrive one depth at a time, e.g. paired results
+/// of a live crawler. The current min, residual and extrapolated values can
+/// be read at any depth without re-running the computation.
+///
+/// Items are owned and remembered together with their rank in each list, so
+/// duplicates are refused as soon as they arrive. Once one ranking has ended,
+/// items are pushed to the other one only.
+///
+/// With the `serde` feature the state can be serialized and resumed later,
+/// e.g. to keep comparing continuously updated rankings across restarts.
+///
+/// ```
+/// use rbo::RboIncremental;
+///
+/// let mut state = RboIncremental::new(0.9).expect("valid p");
+/// state.push("a", Some("b")).expect("no duplicates");
+/// state.push("b", Some("a")).expect("no duplicates");
+/// let shallow = state.current();
+/// state.push("c", Some("c")).expect("no duplicates");
+/// assert_eq!(state.depth(), 3);
+/// assert!(state.current().min > shallow.min);
+/// ```
+///
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Item: serde::Serialize",
+        deserialize = "Item: serde::Deserialize<'de> + Eq + Hash"
+    ))
+)]
+pub struct RboIncremental<Item, O = fn(usize, f64, f64)> {
+    // the rank of every item seen so far in the first and in the second list
+    #[cfg_attr(feature = "serde", serde(with = "entries"))]
+    ranks: HashMap<Item, [Option<usize>; 2]>,
+    // the number of items pushed to each list
+    lengths: [usize; 2],
+    // whether each list has ended
+    ended: [bool; 2],
+    // the overlap bookkeeping, items are matched through `ranks` instead
+    state: RboState<(), O>,
+}
+
+impl<Item: Eq + Hash> RboIncremental<Item> {
+    /// An empty state with persistence `p`
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    ///
+    pub fn new(p: f64) -> Result<Self, RboError> {
+        Self::with_capacity(p, 0)
+    }
+
+    // `new`, with room for `capacity` distinct items
+    pub(crate) fn with_capacity(p: f64, capacity: usize) -> Result<Self, RboError> {
+        Ok(Self {
+            ranks: HashMap::with_capacity(capacity),
+            lengths: [0, 0],
+            ended: [false, false],
+            state: RboState::constant_memory(p)?,
+        })
+    }
+
+    // `new`, keeping the overlap at every depth so the result can be computed
+    // for any persistence with `detailed_with_persistence`
+    pub(crate) fn keeping_overlap() -> Self {
+        Self {
+            ranks: HashMap::new(),
+            lengths: [0, 0],
+            ended: [false, false],
+            // the persistence used for the walk is irrelevant
+            state: RboState::with_persistence(0.5).expect("valid p"),
+        }
+    }
+
+    /// Call `observer` with the depth, the overlap X_d and the extrapolated
+    /// RBO so far after every push. An observer is not part of a serialized
+    /// state and has to be registered again after resuming.
+    pub fn with_observer<O>(self, observer: O) -> RboIncremental<Item, O>
+    where
+        O: FnMut(usize, f64, f64),
+    {
+        RboIncremental {
+            ranks: self.ranks,
+            lengths: self.lengths,
+            ended: self.ended,
+            state: self.state.with_observer(observer),
+        }
+    }
+}
+
+impl<Item: Eq + Hash, O: FnMut(usize, f64, f64)> RboIncremental<Item, O> {
+    /// Push the items at the next depth of both rankings. If `second` is
+    /// `None` the second ranking has ended and only `first` continues.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if an item is already in its ranking
+    /// - Will return `Err` if an item is pushed to a ranking that has ended
+    ///
+    pub fn push(&mut self, first: Item, second: Option<Item>) -> Result<(), RboError> {
+        self.push_items(Some(first), second)
+    }
+
+    /// Push the next item of the second ranking after the first one has ended
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the item is already in the second ranking
+    /// - Will return `Err` if the second ranking has ended
+    ///
+    pub fn push_second(&mut self, second: Item) -> Result<(), RboError> {
+        self.push_items(None, Some(second))
+    }
+
+    /// The number of depths pushed so far, the length of the longer ranking
+    pub fn depth(&self) -> usize {
+        self.lengths[0].max(self.lengths[1])
+    }
+
+    /// The RBO of the rankings pushed so far
+    pub fn current(&self) -> Rbo {
+        self.state.current()
+    }
+
+    /// The final RBO once both rankings are complete
+    pub fn into_result(self) -> Rbo {
+        self.state.current()
+    }
+
+    // `into_result`, refused if rounding errors made it meaningless
+    pub(crate) fn into_checked_result(self) -> Result<Rbo, RboError> {
+        self.state.into_result()
+    }
+
+    // the final RBO together with the lengths and overlap of the rankings
+    pub(crate) fn into_detailed(self) -> Result<RboDetailed, RboError> {
+        Ok(RboDetailed {
+            p: self.state.persistence(),
+            first_depth: self.lengths[0],
+            second_depth: self.lengths[1],
+            overlap: self.state.current_overlap() as usize,
+            rbo: self.state.into_result()?,
+        })
+    }
+
+    // the result for persistence `p`, which must be valid, of a state created
+    // with `keeping_overlap`
+    pub(crate) fn detailed_with_persistence(&self, p: f64) -> Result<RboDetailed, RboError> {
+        Ok(RboDetailed {
+            p,
+            first_depth: self.lengths[0],
+            second_depth: self.lengths[1],
+            overlap: self.state.current_overlap() as usize,
+            rbo: self.state.result_with_persistence(p)?,
+        })
+    }
+
+    // refuse `item` as the next item of the `list`-th ranking
+    fn check(&self, list: usize, item: &Item) -> Result<(), RboError> {
+        if self.ended[list] {
+            return Err(RboError::ListEnded(list));
+        }
+        match self.ranks.get(item).and_then(|ranks| ranks[list]) {
+            Some(previous) => Err(RboError::DuplicatesInList(Duplicate {
+                list,
+                indices: vec![previous, self.lengths[list]],
+                item_debug: None,
+            })),
+            None => Ok(()),
+        }
+    }
+
+    // add `item` to the `list`-th ranking, telling whether it is in the other one
+    fn add(&mut self, list: usize, item: Item) -> bool {
+        let ranks = self.ranks.entry(item).or_default();
+        ranks[list] = Some(self.lengths[list]);
+        self.lengths[list] += 1;
+        ranks[1 - list].is_some()
+    }
+
+    // push the items at the next depth, either of which may have ended.
+    // Nothing is changed if an item is refused.
+    pub(crate) fn push_items(
+        &mut self,
+        first: Option<Item>,
+        second: Option<Item>,
+    ) -> Result<(), RboError> {
+        if first.is_none() && second.is_none() {
+            return Ok(());
+        }
+        for (list, item) in [&first, &second].into_iter().enumerate() {
+            if let Some(item) = item {
+                self.check(list, item)?;
+            }
+        }
+        let paired = first.is_some() && second.is_some();
+        let mut matches = 0.0;
+        for (list, item) in [first, second].into_iter().enumerate() {
+            match item {
+                Some(item) => {
+                    if self.add(list, item) {
+                        matches += 1.0;
+                    }
+                }
+                None => self.ended[list] = true,
+            }
+        }
+        self.state.record(matches, paired);
+        Ok(())
+    }
+}
+
+// `ranks` as a sequence of (item, ranks) pairs, as items are not necessarily
+// valid map keys of the serialization format
+#[cfg(feature = "serde")]
+mod entries {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    pub(super) fn serialize<S, Item>(
+        ranks: &HashMap<Item, [Option<usize>; 2]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        Item: Serialize,
+    {
+        serializer.collect_seq(ranks.iter())
+    }
+
+    pub(super) fn deserialize<'de, D, Item>(
+        deserializer: D,
+    ) -> Result<HashMap<Item, [Option<usize>; 2]>, D::Error>
+    where
+        D: Deserializer<'de>,
+        Item: Deserialize<'de> + Eq + Hash,
+    {
+        let entries: Vec<(Item, [Option<usize>; 2])> = Vec::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+///
+/// RBO between two rankings produced by iterators, e.g. database cursors or
+/// lazily generated results, without collecting them first. Duplicates are
+/// detected while iterating; the error reports the rank of the first
+/// occurrence and of the repetition that was found.
+///
+/// ```
+/// let first = (0..100).map(|i| i * 2);
+/// let second = (0..100).map(|i| i * 3);
+/// let rbo = rbo::rbo_iter(first, second, 0.9).expect("valid rbo");
+/// assert!(rbo.extrapolated > 0.0 && rbo.extrapolated < 1.0);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if either iterator yields the same item twice
+///
+pub fn rbo_iter<I, J, Item>(first: I, second: J, p: f64) -> Result<Rbo, RboError>
+where
+    I: IntoIterator<Item = Item>,
+    J: IntoIterator<Item = Item>,
+    Item: Eq + Hash,
+{
+    consume(RboIncremental::new(p)?, first, second)
+}
+
+// push both rankings to `state` until they have ended
+pub(crate) fn consume<I, J, Item>(
+    mut state: RboIncremental<Item>,
+    first: I,
+    second: J,
+) -> Result<Rbo, RboError>
+where
+    I: IntoIterator<Item = Item>,
+    J: IntoIterator<Item = Item>,
+    Item: Eq + Hash,
+{
+    let mut first = first.into_iter().fuse();
+    let mut second = second.into_iter().fuse();
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => break,
+            (a, b) => state.push_items(a, b)?,
+        }
+    }
+    state.into_checked_result()
+}
+
+/// The result of [`rbo_until_residual`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboUntil {
+    /// RBO of the rankings down to `depth`
+    pub rbo: Rbo,
+    /// The number of depths consumed from the longer ranking
+    pub depth: usize,
+    /// Whether the residual dropped below the threshold before both rankings ended
+    pub converged: bool,
+}
+
+///
+/// Like [`rbo_iter`], but stops pulling items from the iterators as soon as
+/// the residual drops below `max_residual`, so deep ranks that can no longer
+/// change the result noticeably are never fetched.
+///
+/// ```
+/// let first = (0..).map(|i| i * 2);
+/// let second = (0..).map(|i| i * 3);
+/// let result = rbo::rbo_until_residual(first, second, 0.5, 0.001).expect("valid rbo");
+/// assert!(result.converged);
+/// assert!(result.rbo.residual < 0.001);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if either iterator yields the same item twice
+///
+pub fn rbo_until_residual<I, J, Item>(
+    first: I,
+    second: J,
+    p: f64,
+    max_residual: f64,
+) -> Result<RboUntil, RboError>
+where
+    I: IntoIterator<Item = Item>,
+    J: IntoIterator<Item = Item>,
+    Item: Eq + Hash,
+{
+    let mut state = RboIncremental::new(p)?;
+    let mut first = first.into_iter().fuse();
+    let mut second = second.into_iter().fuse();
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => {
+                return Ok(RboUntil {
+                    rbo: state.current(),
+                    depth: state.depth(),
+                    converged: false,
+                })
+            }
+            (a, b) => state.push_items(a, b)?,
+        }
+        let rbo = state.current();
+        if rbo.residual < max_residual {
+            return Ok(RboUntil {
+                rbo,
+                depth: state.depth(),
+                converged: true,
+            });
+        }
+    }
+}
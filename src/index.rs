@@ -0,0 +1,234 @@
+//! A persistent similarity index over a corpus of rankings.
+//!
+//! [`RankingIndex`] ingests rankings of string items once, interning every
+//! item into a dense integer id, and answers repeated nearest-ranking queries
+//! against the corpus. New rankings can be added at any time and the whole
+//! index can be saved to and loaded from disk.
+//!
+//! ```
+//! use rbo::index::RankingIndex;
+//!
+//! let mut index = RankingIndex::new();
+//! index.add("monday", &["a", "b", "c", "d"]).expect("no duplicates");
+//! index.add("tuesday", &["d", "c", "b", "a"]).expect("no duplicates");
+//! let nearest = index.most_similar(&["a", "b", "d", "c"], 0.9, 1).expect("valid rbo");
+//! assert_eq!(index.label(nearest[0].0), "monday");
+//! ```
+
+use crate::io::binary::Corpus;
+use crate::{Rbo, RboError};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// An interned, persistable corpus of rankings supporting similarity queries.
+/// Persisted in the [`binary`](crate::io::binary) corpus format.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RankingIndex {
+    corpus: Corpus,
+}
+
+impl RankingIndex {
+    /// Create an empty index
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Build an index over an existing corpus.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if any ranking of the corpus contains duplicate items
+    ///
+    pub fn from_corpus(corpus: Corpus) -> Result<Self, RboError> {
+        for (i, ranking) in corpus.id_rankings().iter().enumerate() {
+            crate::check_duplicates(ranking, i)?;
+        }
+        Ok(Self { corpus })
+    }
+
+    /// The underlying corpus
+    #[must_use]
+    pub fn corpus(&self) -> &Corpus {
+        &self.corpus
+    }
+
+    /// Number of rankings in the index
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.corpus.len()
+    }
+
+    /// `true` if the index holds no rankings
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.corpus.is_empty()
+    }
+
+    /// Label of the ranking at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn label(&self, index: usize) -> &str {
+        self.corpus.label(index)
+    }
+
+    /// The items of the ranking at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn ranking(&self, index: usize) -> impl Iterator<Item = &str> + '_ {
+        self.corpus.ranking(index)
+    }
+
+    ///
+    /// Add a ranking under `label` and return its position in the index.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the ranking contains duplicate items
+    ///
+    pub fn add<S: AsRef<str>>(&mut self, label: &str, ranking: &[S]) -> Result<usize, RboError> {
+        let items: Vec<&str> = ranking.iter().map(AsRef::as_ref).collect();
+        crate::check_duplicates(&items, self.len())?;
+        Ok(self.corpus.push(label, ranking))
+    }
+
+    // map a query onto ids, items unknown to the index get fresh ids that match
+    // nothing. A repeated unknown item gets the same id, so it is refused as a
+    // duplicate like a repeated known one.
+    fn lookup<S: AsRef<str>>(&self, ranking: &[S]) -> Vec<u32> {
+        let mut unknown: HashMap<&str, u32> = HashMap::new();
+        ranking
+            .iter()
+            .map(|item| {
+                let item = item.as_ref();
+                self.corpus.id(item).unwrap_or_else(|| {
+                    let fresh = u32::MAX - 1 - unknown.len() as u32;
+                    *unknown.entry(item).or_insert(fresh)
+                })
+            })
+            .collect()
+    }
+
+    ///
+    /// The `m` rankings in the index most similar to `query`, as
+    /// `(position, rbo)` pairs sorted by decreasing extrapolated RBO. See
+    /// [`most_similar`](crate::most_similar).
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if the query contains duplicate items
+    ///
+    pub fn most_similar<S: AsRef<str>>(
+        &self,
+        query: &[S],
+        p: f64,
+        m: usize,
+    ) -> Result<Vec<(usize, Rbo)>, RboError> {
+        crate::most_similar(&self.lookup(query), self.corpus.id_rankings(), p, m)
+    }
+
+    ///
+    /// RBO between `query` and the ranking at `index`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if the query contains duplicate items
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn rbo_against<S: AsRef<str>>(
+        &self,
+        query: &[S],
+        index: usize,
+        p: f64,
+    ) -> Result<Rbo, RboError> {
+        crate::rbo(&self.lookup(query), self.corpus.ids(index), p)
+    }
+
+    ///
+    /// Write the index to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if writing fails
+    ///
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), RboError> {
+        self.corpus.write_to(writer)
+    }
+
+    ///
+    /// Read an index previously written with [`RankingIndex::write_to`].
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if reading fails or the data is not a valid index
+    ///
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, RboError> {
+        Self::from_corpus(Corpus::read_from(reader)?)
+    }
+
+    ///
+    /// Save the index to the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the file can not be written
+    ///
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RboError> {
+        self.corpus.save(path)
+    }
+
+    ///
+    /// Load an index from the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the file can not be read or is not a valid index
+    ///
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RboError> {
+        Self::from_corpus(Corpus::load(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RankingIndex;
+
+    #[test]
+    fn round_trip_and_query() {
+        let mut index = RankingIndex::new();
+        index.add("a", &["x", "y", "z"]).expect("no duplicates");
+        index.add("b", &["z", "w", "x"]).expect("no duplicates");
+        assert!(index.add("c", &["x", "x"]).is_err());
+
+        let mut buffer = Vec::new();
+        index.write_to(&mut buffer).expect("write to memory");
+        let loaded = RankingIndex::read_from(buffer.as_slice()).expect("valid index");
+        assert_eq!(index, loaded);
+        assert_eq!(loaded.ranking(1).collect::<Vec<_>>(), vec!["z", "w", "x"]);
+
+        let nearest = loaded
+            .most_similar(&["z", "w", "q"], 0.9, 2)
+            .expect("valid rbo");
+        assert_eq!(nearest[0].0, 1);
+        let direct = crate::rbo(&["z", "w", "q"], &["z", "w", "x"], 0.9).expect("valid rbo");
+        assert_eq!(nearest[0].1, direct);
+        // a repeated item of the query is refused, known to the index or not
+        for query in [["q", "q"], ["x", "x"]] {
+            assert!(loaded.most_similar(&query, 0.9, 1).is_err());
+            assert!(loaded.rbo_against(&query, 0, 0.9).is_err());
+        }
+        assert!(loaded.rbo_against(&["q", "r"], 0, 0.9).is_ok());
+        assert!(RankingIndex::read_from(&buffer[..buffer.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::state::ROUNDING_TOLERANCE;
+use crate::{Rbo, RboError, RboPoint};
+
+/// A mathematical invariant of RBO that a computed result breaks, reported by
+/// an [`RboBuilder`](crate::RboBuilder) that validates its results
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvariantViolation {
+    /// The result is not ordered as 0 <= min <= min + residual <= 1, or the
+    /// extrapolated value is not within [min, 1]. On lists of different
+    /// lengths extrapolation may exceed `min + residual` slightly, so only 1
+    /// bounds it from above.
+    Bounds(Rbo),
+    /// The lower bound decreased from one evaluation depth to the next
+    MinDecreasing {
+        /// The deeper of the two depths, counting from 1
+        depth: usize,
+        /// The lower bound at the depth before
+        previous: f64,
+        /// The lower bound at `depth`
+        current: f64,
+    },
+    /// The upper bound `min + residual` increased from one evaluation depth to the next
+    UpperBoundIncreasing {
+        /// The deeper of the two depths, counting from 1
+        depth: usize,
+        /// The upper bound at the depth before
+        previous: f64,
+        /// The upper bound at `depth`
+        current: f64,
+    },
+}
+
+impl std::fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvariantViolation::Bounds(rbo) => write!(
+                f,
+                "min={}, min + residual={} and extrapolated={} are not within [0, 1] in order, \
+                 with extrapolated at least min",
+                rbo.min,
+                rbo.upper_bound(),
+                rbo.extrapolated
+            ),
+            InvariantViolation::MinDecreasing {
+                depth,
+                previous,
+                current,
+            } => write!(
+                f,
+                "min decreased from {previous} to {current} at depth {depth}"
+            ),
+            InvariantViolation::UpperBoundIncreasing {
+                depth,
+                previous,
+                current,
+            } => write!(
+                f,
+                "min + residual increased from {previous} to {current} at depth {depth}"
+            ),
+        }
+    }
+}
+
+// refuse `rbo` unless 0 <= min <= min + residual <= 1 and min <= extrapolated
+// <= 1, up to rounding. Equation 32 does not bound the extrapolated value by
+// min + residual when the lists differ in length, so only 1 bounds it from
+// above. Nothing is extrapolated from an empty list, a NaN extrapolated value
+// is left out.
+pub(crate) fn check_bounds(rbo: Rbo) -> Result<(), RboError> {
+    let ordered = [0.0, rbo.min, rbo.upper_bound(), 1.0]
+        .windows(2)
+        .all(|pair| pair[0] <= pair[1] + ROUNDING_TOLERANCE);
+    let range = rbo.min - ROUNDING_TOLERANCE..=1.0 + ROUNDING_TOLERANCE;
+    let extrapolated = rbo.extrapolated.is_nan() || range.contains(&rbo.extrapolated);
+    if ordered && extrapolated {
+        Ok(())
+    } else {
+        Err(RboError::InvariantViolated(InvariantViolation::Bounds(rbo)))
+    }
+}
+
+// refuse a curve whose lower bound decreases or whose upper bound increases
+// with depth, up to rounding
+pub(crate) fn check_curve(points: &[RboPoint]) -> Result<(), RboError> {
+    for pair in points.windows(2) {
+        let (previous, current) = (pair[0].rbo, pair[1].rbo);
+        let depth = pair[1].depth;
+        if current.min + ROUNDING_TOLERANCE < previous.min {
+            return Err(RboError::InvariantViolated(
+                InvariantViolation::MinDecreasing {
+                    depth,
+                    previous: previous.min,
+                    current: current.min,
+                },
+            ));
+        }
+        if current.upper_bound() > previous.upper_bound() + ROUNDING_TOLERANCE {
+            return Err(RboError::InvariantViolated(
+                InvariantViolation::UpperBoundIncreasing {
+                    depth,
+                    previous: previous.upper_bound(),
+                    current: current.upper_bound(),
+                },
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,297 @@
+//! A compact binary container for corpora of rankings.
+//!
+//! Storing millions of top-k lists as newline separated text is large and slow
+//! to parse. A [`Corpus`] interns every distinct item once into a string table
+//! and stores each ranking as a list of item ids together with a label. On disk
+//! the layout is
+//!
+//! ```text
+//! magic      8 bytes  "RBOBIN\0\x01"
+//! items      varint count, then per item: varint length + UTF-8 bytes
+//! rankings   varint count, then per ranking:
+//!              label: varint length + UTF-8 bytes
+//!              ids:   varint length, then zigzag varint deltas to the previous id
+//! ```
+//!
+//! Ids are handed out in order of first appearance, so consecutive items of a
+//! ranking often have consecutive ids and the deltas fit in a single byte.
+//!
+//! ```
+//! use rbo::io::binary::Corpus;
+//!
+//! let mut corpus = Corpus::new();
+//! corpus.push("q1", &["a", "b", "c"]);
+//! corpus.push("q2", &["c", "a", "d"]);
+//! let mut bytes = Vec::new();
+//! corpus.write_to(&mut bytes).expect("write to memory");
+//! let read = Corpus::read_from(bytes.as_slice()).expect("valid corpus");
+//! assert_eq!(read.ranking(1).collect::<Vec<_>>(), vec!["c", "a", "d"]);
+//! ```
+
+use crate::RboError;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// The magic bytes every binary corpus starts with
+pub const MAGIC: &[u8; 8] = b"RBOBIN\x00\x01";
+
+/// Labelled rankings over an interned string table
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Corpus {
+    // string table, id -> item
+    items: Vec<String>,
+    ids: HashMap<String, u32>,
+    labels: Vec<String>,
+    rankings: Vec<Vec<u32>>,
+}
+
+impl Corpus {
+    /// Create an empty corpus
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of rankings in the corpus
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rankings.len()
+    }
+
+    /// `true` if the corpus holds no rankings
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rankings.is_empty()
+    }
+
+    /// Number of distinct items over all rankings
+    #[must_use]
+    pub fn item_count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Label of the ranking at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn label(&self, index: usize) -> &str {
+        &self.labels[index]
+    }
+
+    /// The items of the ranking at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn ranking(&self, index: usize) -> impl Iterator<Item = &str> + '_ {
+        self.rankings[index]
+            .iter()
+            .map(move |id| self.items[*id as usize].as_str())
+    }
+
+    /// Iterate over all `(label, ranking)` pairs in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Vec<&str>)> + '_ {
+        (0..self.len()).map(move |i| (self.label(i), self.ranking(i).collect()))
+    }
+
+    /// The interned ids of the ranking at `index`
+    pub(crate) fn ids(&self, index: usize) -> &[u32] {
+        &self.rankings[index]
+    }
+
+    /// All rankings as interned ids
+    pub(crate) fn id_rankings(&self) -> &[Vec<u32>] {
+        &self.rankings
+    }
+
+    /// The id of `item`, if it occurs in the corpus
+    pub(crate) fn id(&self, item: &str) -> Option<u32> {
+        self.ids.get(item).copied()
+    }
+
+    fn intern(&mut self, item: &str) -> u32 {
+        if let Some(id) = self.ids.get(item) {
+            return *id;
+        }
+        let id = u32::try_from(self.items.len()).expect("fewer than 2^32 distinct items");
+        self.items.push(item.to_string());
+        self.ids.insert(item.to_string(), id);
+        id
+    }
+
+    /// Append a ranking under `label` and return its position in the corpus.
+    /// The ranking is stored as is, duplicates included.
+    pub fn push<S: AsRef<str>>(&mut self, label: &str, ranking: &[S]) -> usize {
+        let ranking = ranking
+            .iter()
+            .map(|item| self.intern(item.as_ref()))
+            .collect();
+        self.labels.push(label.to_string());
+        self.rankings.push(ranking);
+        self.rankings.len() - 1
+    }
+
+    ///
+    /// Write the corpus to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if writing fails
+    ///
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), RboError> {
+        let mut writer = std::io::BufWriter::new(writer);
+        writer.write_all(MAGIC)?;
+        write_varint(&mut writer, self.items.len() as u64)?;
+        for item in &self.items {
+            write_bytes(&mut writer, item.as_bytes())?;
+        }
+        write_varint(&mut writer, self.rankings.len() as u64)?;
+        for (label, ranking) in self.labels.iter().zip(&self.rankings) {
+            write_bytes(&mut writer, label.as_bytes())?;
+            write_varint(&mut writer, ranking.len() as u64)?;
+            let mut previous = 0i64;
+            for id in ranking {
+                let delta = i64::from(*id) - previous;
+                write_varint(&mut writer, ((delta << 1) ^ (delta >> 63)) as u64)?;
+                previous = i64::from(*id);
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    ///
+    /// Read a corpus previously written with [`Corpus::write_to`].
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if reading fails or the data is not a valid corpus
+    ///
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, RboError> {
+        let mut reader = std::io::BufReader::new(reader);
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a binary ranking corpus"));
+        }
+        let mut corpus = Self::new();
+        for _ in 0..read_varint(&mut reader)? {
+            let item = read_string(&mut reader)?;
+            if corpus.ids.contains_key(&item) {
+                return Err(invalid("duplicate entry in string table"));
+            }
+            corpus.intern(&item);
+        }
+        for _ in 0..read_varint(&mut reader)? {
+            corpus.labels.push(read_string(&mut reader)?);
+            let mut previous = 0i64;
+            let ranking = (0..read_varint(&mut reader)?)
+                .map(|_| {
+                    let zigzag = read_varint(&mut reader)?;
+                    let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                    previous = previous.wrapping_add(delta);
+                    u32::try_from(previous)
+                        .ok()
+                        .filter(|id| (*id as usize) < corpus.items.len())
+                        .ok_or_else(|| invalid("item id out of range"))
+                })
+                .collect::<Result<Vec<u32>, RboError>>()?;
+            corpus.rankings.push(ranking);
+        }
+        Ok(corpus)
+    }
+
+    ///
+    /// Save the corpus to the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the file can not be written
+    ///
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RboError> {
+        self.write_to(std::fs::File::create(path)?)
+    }
+
+    ///
+    /// Load a corpus from the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the file can not be read or is not a valid corpus
+    ///
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RboError> {
+        Self::read_from(std::fs::File::open(path)?)
+    }
+}
+
+fn invalid(message: &str) -> RboError {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string()).into()
+}
+
+// LEB128 style variable length integers
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> std::io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, RboError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        reader.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+    write_varint(writer, bytes.len() as u64)?;
+    writer.write_all(bytes)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, RboError> {
+    let len = read_varint(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(invalid("truncated string"));
+    }
+    String::from_utf8(bytes).map_err(|_| invalid("string is not valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Corpus;
+
+    #[test]
+    fn round_trip() {
+        let mut corpus = Corpus::new();
+        corpus.push("q1", &["x", "y", "z"]);
+        corpus.push("q2", &["z", "w", "x", "x"]);
+        corpus.push("empty", &[] as &[&str]);
+        assert_eq!(corpus.item_count(), 4);
+
+        let mut bytes = Vec::new();
+        corpus.write_to(&mut bytes).expect("write to memory");
+        let read = Corpus::read_from(bytes.as_slice()).expect("valid corpus");
+        assert_eq!(corpus, read);
+        assert_eq!(
+            read.ranking(1).collect::<Vec<_>>(),
+            vec!["z", "w", "x", "x"]
+        );
+        assert!(Corpus::read_from(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Corpus::read_from(&b"not a corpus"[..]).is_err());
+    }
+}
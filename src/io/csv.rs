@@ -0,0 +1,257 @@
+//! Rankings stored in CSV or TSV files, e.g. exported spreadsheets.
+//!
+//! Every row holds one ranked item. [`CsvOptions`] select the column of the
+//! item, optionally the columns ordering the items by rank or score, and a
+//! column grouping the rows into separate rankings, e.g. one per query.
+//!
+//! ```
+//! use rbo::io::csv::{read_csv, CsvOptions};
+//!
+//! let csv = "query,doc,score\nq1,d1,0.2\nq1,d2,0.9\nq2,d3,0.5\n";
+//! let options = CsvOptions {
+//!     item: "doc".into(),
+//!     score: Some("score".into()),
+//!     group: Some("query".into()),
+//!     ..CsvOptions::default()
+//! };
+//! let rankings = read_csv(csv.as_bytes(), &options).expect("valid csv");
+//! assert_eq!(rankings["q1"], vec!["d2", "d1"]);
+//! assert_eq!(rankings["q2"], vec!["d3"]);
+//! ```
+
+use crate::RboError;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// A column of a CSV file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    /// The column at a 0-based position
+    Index(usize),
+    /// The column with a header, for files with a header row
+    Name(String),
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+/// Options for [`read_csv`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The column holding the ranked items
+    pub item: Column,
+    /// The column holding the rank of every item, ordering items by increasing rank
+    pub rank: Option<Column>,
+    /// The column holding the score of every item, ordering items by
+    /// decreasing score, or breaking ties of the rank if there is one
+    pub score: Option<Column>,
+    /// The column whose values split the rows into separate rankings
+    pub group: Option<Column>,
+    /// The field delimiter, `b'\t'` for TSV
+    pub delimiter: u8,
+    /// Whether the first row holds the column names
+    pub has_headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            item: Column::Index(0),
+            rank: None,
+            score: None,
+            group: None,
+            delimiter: b',',
+            has_headers: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// The default options for tab separated files
+    #[must_use]
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::default()
+        }
+    }
+}
+
+// one row of the file
+struct Row {
+    item: String,
+    rank: Option<i64>,
+    score: Option<f64>,
+}
+
+///
+/// Read the rankings stored in `reader` as described by `options`. Returns
+/// the ranking of every group, or of the empty group `""` if there is no
+/// group column. Without rank and score columns, items are ranked in the
+/// order of their rows.
+///
+/// # Errors
+///
+/// - Will return `Err` if reading or parsing the CSV fails
+/// - Will return `Err` if a column is missing, or a named column is
+///   selected for a file without headers
+/// - Will return `Err` if a rank or score is not a number
+///
+pub fn read_csv<R: Read>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<BTreeMap<String, Vec<String>>, RboError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers)
+        .flexible(true)
+        .from_reader(reader);
+    let headers = if options.has_headers {
+        Some(reader.headers()?.clone())
+    } else {
+        None
+    };
+    let position = |column: &Column| match column {
+        Column::Index(index) => Ok(*index),
+        Column::Name(name) => headers
+            .as_ref()
+            .and_then(|headers| headers.iter().position(|header| header == name))
+            .ok_or_else(|| invalid(format!("no column named `{name}`"))),
+    };
+    let item = position(&options.item)?;
+    let rank = options.rank.as_ref().map(position).transpose()?;
+    let score = options.score.as_ref().map(position).transpose()?;
+    let group = options.group.as_ref().map(position).transpose()?;
+
+    let mut groups: BTreeMap<String, Vec<Row>> = BTreeMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, csv::Position::line);
+        let field = |index: usize| {
+            record
+                .get(index)
+                .ok_or_else(|| invalid(format!("line {line}: no column {index}")))
+        };
+        let row = Row {
+            item: field(item)?.to_string(),
+            rank: rank
+                .map(|rank| parse(field(rank)?, line, "rank"))
+                .transpose()?,
+            score: score
+                .map(|score| parse(field(score)?, line, "score"))
+                .transpose()?,
+        };
+        let key = group.map(field).transpose()?.unwrap_or_default();
+        groups.entry(key.to_string()).or_default().push(row);
+    }
+    Ok(groups
+        .into_iter()
+        .map(|(key, mut rows)| {
+            // stable, so rows without rank and score keep their order
+            rows.sort_by(|a, b| {
+                a.rank.cmp(&b.rank).then_with(|| match (a.score, b.score) {
+                    (Some(a), Some(b)) => b.total_cmp(&a),
+                    _ => std::cmp::Ordering::Equal,
+                })
+            });
+            (key, rows.into_iter().map(|row| row.item).collect())
+        })
+        .collect())
+}
+
+///
+/// Load the rankings stored in the file at `path`, see [`read_csv`]
+///
+/// # Errors
+///
+/// - Will return `Err` if the file can not be read or does not hold rankings
+///   as described by `options`
+///
+pub fn load_csv(
+    path: impl AsRef<Path>,
+    options: &CsvOptions,
+) -> Result<BTreeMap<String, Vec<String>>, RboError> {
+    read_csv(std::fs::File::open(path)?, options)
+}
+
+// the number in a field of line `line`
+fn parse<T: std::str::FromStr>(field: &str, line: u64, name: &str) -> Result<T, RboError> {
+    field
+        .trim()
+        .parse()
+        .map_err(|_| invalid(format!("line {line}: invalid {name}")))
+}
+
+fn invalid(message: String) -> RboError {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_csv, Column, CsvOptions};
+
+    #[test]
+    fn selects_and_orders_columns() {
+        let tsv = "q1\td1\t3\t0.1\nq1\td2\t1\t0.5\nq2\td3\t1\t0.7\nq1\td4\t1\t0.9\n";
+        let options = CsvOptions {
+            item: 1.into(),
+            rank: Some(2.into()),
+            score: Some(3.into()),
+            group: Some(0.into()),
+            has_headers: false,
+            ..CsvOptions::tsv()
+        };
+        let rankings = read_csv(tsv.as_bytes(), &options).expect("valid tsv");
+        assert_eq!(rankings.len(), 2);
+        assert_eq!(rankings["q1"], vec!["d4", "d2", "d1"]);
+        assert_eq!(rankings["q2"], vec!["d3"]);
+    }
+
+    #[test]
+    fn keeps_row_order_without_ranks() {
+        let csv = "doc\nb\na\n\"c,d\"\n";
+        let options = CsvOptions {
+            item: "doc".into(),
+            ..CsvOptions::default()
+        };
+        let rankings = read_csv(csv.as_bytes(), &options).expect("valid csv");
+        assert_eq!(rankings[""], vec!["b", "a", "c,d"]);
+    }
+
+    #[test]
+    fn rejects_invalid_columns() {
+        let csv = "doc,score\na,1\nb,x\n";
+        let named = |name: &str| CsvOptions {
+            item: Column::Name(name.to_string()),
+            ..CsvOptions::default()
+        };
+        assert!(read_csv(csv.as_bytes(), &named("missing")).is_err());
+        let headerless = CsvOptions {
+            has_headers: false,
+            ..named("doc")
+        };
+        assert!(read_csv(csv.as_bytes(), &headerless).is_err());
+        let scored = CsvOptions {
+            score: Some("score".into()),
+            ..named("doc")
+        };
+        let err = read_csv(csv.as_bytes(), &scored).expect_err("invalid score");
+        assert!(err.to_string().contains("line 3"));
+        assert!(read_csv(csv.as_bytes(), &CsvOptions::default()).is_ok());
+        let out_of_range = CsvOptions {
+            item: 5.into(),
+            ..CsvOptions::default()
+        };
+        assert!(read_csv(csv.as_bytes(), &out_of_range).is_err());
+    }
+}
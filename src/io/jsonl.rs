@@ -0,0 +1,152 @@
+//! Rankings stored as JSON Lines, one JSON value per line.
+//!
+//! A line is either a bare array of ranked items, or an object whose `field`
+//! holds the ranked items. The other fields of an object are kept as the
+//! ranking's metadata, e.g. to pair up the rankings of two files by a query
+//! id with [`JsonlRanking::key`].
+//!
+//! ```
+//! use rbo::io::jsonl::read_jsonl;
+//!
+//! let jsonl = r#"{"query": "q1", "results": ["d1", "d2", 7]}
+//! {"query": "q2", "results": []}"#;
+//! let rankings = read_jsonl(jsonl.as_bytes(), Some("results")).expect("valid jsonl");
+//! assert_eq!(rankings[0].ranking, vec!["d1", "d2", "7"]);
+//! assert_eq!(rankings[1].key("query").as_deref(), Some("q2"));
+//! ```
+
+use crate::RboError;
+use serde_json::{Map, Value};
+use std::io::BufRead;
+use std::path::Path;
+
+/// A ranking read from a line of JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonlRanking {
+    /// The ranked items, numbers written out as text
+    pub ranking: Vec<String>,
+    /// The other fields of the line's object, empty for a bare array
+    pub metadata: Map<String, Value>,
+}
+
+impl JsonlRanking {
+    /// The metadata field `name` as text, strings without their quotes
+    pub fn key(&self, name: &str) -> Option<String> {
+        self.metadata.get(name).map(|value| match value {
+            Value::String(text) => text.clone(),
+            value => value.to_string(),
+        })
+    }
+}
+
+///
+/// Read one ranking per non-blank line of `reader`. Without a `field` every
+/// line must be an array of items. Otherwise every line must be an object,
+/// whose `field` holds the items; a `field` starting with `/` is a JSON
+/// pointer into nested objects, e.g. `/response/docs`. Items must be
+/// strings or numbers.
+///
+/// # Errors
+///
+/// - Will return `Err` if reading fails
+/// - Will return `Err` if a line is not valid JSON of the expected shape
+///
+pub fn read_jsonl<R: BufRead>(
+    reader: R,
+    field: Option<&str>,
+) -> Result<Vec<JsonlRanking>, RboError> {
+    let mut rankings = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line)
+            .map_err(|err| invalid(number, &format!("invalid JSON: {err}")))?;
+        let (items, metadata) = match (field, value) {
+            (None, Value::Array(items)) => (items, Map::new()),
+            (None, _) => return Err(invalid(number, "expected an array of items")),
+            (Some(field), Value::Object(mut object)) => {
+                let items = if field.starts_with('/') {
+                    Value::Object(object.clone()).pointer(field).cloned()
+                } else {
+                    object.remove(field)
+                };
+                match items {
+                    Some(Value::Array(items)) => (items, object),
+                    _ => return Err(invalid(number, &format!("no array of items at `{field}`"))),
+                }
+            }
+            (Some(_), _) => return Err(invalid(number, "expected an object")),
+        };
+        let ranking = items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(item) => Ok(item),
+                Value::Number(item) => Ok(item.to_string()),
+                _ => Err(invalid(number, "ranked items must be strings or numbers")),
+            })
+            .collect::<Result<_, _>>()?;
+        rankings.push(JsonlRanking { ranking, metadata });
+    }
+    Ok(rankings)
+}
+
+///
+/// Load the rankings stored in the file at `path`, see [`read_jsonl`]
+///
+/// # Errors
+///
+/// - Will return `Err` if the file can not be read or is not valid JSON Lines
+///
+pub fn load_jsonl(
+    path: impl AsRef<Path>,
+    field: Option<&str>,
+) -> Result<Vec<JsonlRanking>, RboError> {
+    read_jsonl(std::io::BufReader::new(std::fs::File::open(path)?), field)
+}
+
+fn invalid(line: usize, message: &str) -> RboError {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("line {}: {message}", line + 1),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_jsonl;
+
+    #[test]
+    fn reads_arrays_and_objects() {
+        let rankings = read_jsonl("[\"a\", \"b\"]\n\n[1, 2.5]\n".as_bytes(), None).expect("valid");
+        assert_eq!(rankings.len(), 2);
+        assert_eq!(rankings[0].ranking, vec!["a", "b"]);
+        assert_eq!(rankings[1].ranking, vec!["1", "2.5"]);
+        assert!(rankings[1].metadata.is_empty());
+
+        let jsonl = r#"{"qid": 3, "run": "bm25", "hits": {"docs": ["x", "y"]}}"#;
+        let rankings = read_jsonl(jsonl.as_bytes(), Some("/hits/docs")).expect("valid");
+        assert_eq!(rankings[0].ranking, vec!["x", "y"]);
+        assert_eq!(rankings[0].key("qid").as_deref(), Some("3"));
+        assert_eq!(rankings[0].key("run").as_deref(), Some("bm25"));
+        assert_eq!(rankings[0].key("missing"), None);
+    }
+
+    #[test]
+    fn rejects_unexpected_shapes() {
+        for (jsonl, field) in [
+            ("{\"a\": [1]}", None),
+            ("[\"a\"]", Some("a")),
+            ("{\"a\": \"b\"}", Some("a")),
+            ("{\"a\": [[1]]}", Some("a")),
+            ("{\"a\": [1]}", Some("b")),
+            ("[1, 2", None),
+        ] {
+            assert!(read_jsonl(jsonl.as_bytes(), field).is_err(), "{jsonl}");
+        }
+        let err = read_jsonl("[1]\n{}\n".as_bytes(), None).expect_err("invalid line");
+        assert!(err.to_string().contains("line 2"));
+    }
+}
@@ -0,0 +1,307 @@
+//! Rankings stored as text, one item per line.
+
+use crate::incremental::RboIncremental;
+use crate::{Duplicate, Rbo, RboDetailed, RboError};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+
+// the number of depths between checks of the clock in
+// `rbo_from_readers_with_progress`
+const PROGRESS_CHECK: usize = 1024;
+
+// 128-bit hashes standing in for the lines of both rankings, so the state
+// keeps a fixed-size key per distinct line however long it is. Two lines
+// collide with a probability of about 2^-128, negligible even for billions
+// of lines.
+struct Fingerprinter([RandomState; 2]);
+
+impl Fingerprinter {
+    fn new() -> Self {
+        Fingerprinter([RandomState::new(), RandomState::new()])
+    }
+
+    // the fingerprints of the lines of `reader`
+    fn lines<R: BufRead>(&self, reader: R) -> Lines<'_, R> {
+        Lines {
+            fingerprinter: self,
+            reader: Some(reader),
+            line: Vec::new(),
+        }
+    }
+}
+
+// The fingerprints of the lines of a reader, without `\n` or `\r\n`, read
+// into a single reused buffer. Lines must be valid UTF-8 as for
+// `BufRead::lines`.
+struct Lines<'a, R> {
+    fingerprinter: &'a Fingerprinter,
+    // `None` once the reader has ended or failed
+    reader: Option<R>,
+    // the line read last
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> Iterator for Lines<'_, R> {
+    type Item = std::io::Result<u128>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let reader = self.reader.as_mut()?;
+        self.line.clear();
+        let read = match reader.read_until(b'\n', &mut self.line) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                if self.line.ends_with(b"\n") {
+                    self.line.pop();
+                    if self.line.ends_with(b"\r") {
+                        self.line.pop();
+                    }
+                }
+                std::str::from_utf8(&self.line)
+                    .map(|_| {
+                        let [high, low] = &self.fingerprinter.0;
+                        Some(
+                            u128::from(high.hash_one(&self.line)) << 64
+                                | u128::from(low.hash_one(&self.line)),
+                        )
+                    })
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+            }
+            Err(err) => Err(err),
+        };
+        if !matches!(read, Ok(Some(_))) {
+            self.reader = None;
+        }
+        read.transpose()
+    }
+}
+
+// read the pairs of lines of `first` and `second` into `state` until both
+// end, calling `each` after every depth. Only fingerprints are kept, so the
+// line of a repeated item is added to the error as it is read.
+fn read_pairs<A, B, F>(
+    state: &mut RboIncremental<u128>,
+    first: A,
+    second: B,
+    mut each: F,
+) -> Result<(), RboError>
+where
+    A: BufRead,
+    B: BufRead,
+    F: FnMut(&RboIncremental<u128>),
+{
+    let fingerprinter = Fingerprinter::new();
+    let mut first = fingerprinter.lines(first);
+    let mut second = fingerprinter.lines(second);
+    loop {
+        let pair = (first.next().transpose()?, second.next().transpose()?);
+        if pair == (None, None) {
+            return Ok(());
+        }
+        state.push_items(pair.0, pair.1).map_err(|err| match err {
+            RboError::DuplicatesInList(duplicate) => {
+                let line = [&first.line, &second.line][duplicate.list];
+                let item_debug = Some(format!("{:?}", String::from_utf8_lossy(line)));
+                RboError::DuplicatesInList(Duplicate {
+                    item_debug,
+                    ..duplicate
+                })
+            }
+            err => err,
+        })?;
+        each(state);
+    }
+}
+
+///
+/// RBO between two rankings read line by line from `first` and `second`,
+/// one item per line, e.g. large run files. Lines are read one depth at a
+/// time as in [`rbo_iter`](crate::rbo_iter), so neither ranking is collected
+/// first and duplicates are detected as they are read. Only a 128-bit hash
+/// of every line is kept, so memory grows by a small fixed amount per line
+/// whatever its length. Returns the lengths and final overlap of the
+/// rankings along with the result.
+///
+/// ```
+/// let first = "a\nb\nc\n".as_bytes();
+/// let second = "b\na\nd\n".as_bytes();
+/// let detailed = rbo::io::lines::rbo_from_readers(first, second, 0.9).expect("valid rbo");
+/// assert_eq!((detailed.first_depth, detailed.overlap), (3, 2));
+/// assert_eq!(detailed.rbo, rbo::rbo(&["a", "b", "c"], &["b", "a", "d"], 0.9).expect("valid rbo"));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if either ranking repeats a line
+/// - Will return `Err` if reading either ranking fails
+///
+pub fn rbo_from_readers<A, B>(first: A, second: B, p: f64) -> Result<RboDetailed, RboError>
+where
+    A: BufRead,
+    B: BufRead,
+{
+    let mut state = RboIncremental::new(p)?;
+    read_pairs(&mut state, first, second, |_| {})?;
+    state.into_detailed()
+}
+
+///
+/// [`rbo_from_readers`], calling `progress` with the depth and the RBO of
+/// the lines read so far at most once per `interval`, e.g. to report on the
+/// comparison of files with millions of lines. The clock is checked every
+/// 1024 depths, and the RBO is only computed when `progress` is called.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let lines: String = (0..3000).map(|i| format!("d{i}\n")).collect();
+/// let mut depths = Vec::new();
+/// let detailed = rbo::io::lines::rbo_from_readers_with_progress(
+///     lines.as_bytes(),
+///     lines.as_bytes(),
+///     0.9,
+///     Duration::ZERO,
+///     |depth, _| depths.push(depth),
+/// )
+/// .expect("valid rbo");
+/// assert_eq!(depths, vec![1024, 2048]);
+/// assert_eq!(detailed.overlap, 3000);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if either ranking repeats a line
+/// - Will return `Err` if reading either ranking fails
+///
+pub fn rbo_from_readers_with_progress<A, B, F>(
+    first: A,
+    second: B,
+    p: f64,
+    interval: Duration,
+    mut progress: F,
+) -> Result<RboDetailed, RboError>
+where
+    A: BufRead,
+    B: BufRead,
+    F: FnMut(usize, Rbo),
+{
+    let mut state = RboIncremental::new(p)?;
+    let mut last = Instant::now();
+    read_pairs(&mut state, first, second, |state| {
+        if state.depth() % PROGRESS_CHECK == 0 && last.elapsed() >= interval {
+            progress(state.depth(), state.current());
+            last = Instant::now();
+        }
+    })?;
+    state.into_detailed()
+}
+
+///
+/// RBO for several persistence values at once between two rankings read line
+/// by line as in [`rbo_from_readers`]. Both rankings are read a single time
+/// and only the final sums are computed per `p`, as in
+/// [`rbo_multi_p`](crate::rbo_multi_p). Results are in the order of `ps`.
+///
+/// ```
+/// let first = "a\nb\nc\n".as_bytes();
+/// let second = "b\na\nd\n".as_bytes();
+/// let results = rbo::io::lines::rbo_multi_p_from_readers(first, second, &[0.5, 0.9])
+///     .expect("valid rbo");
+/// assert_eq!(results.len(), 2);
+/// assert_eq!((results[1].p, results[1].second_depth), (0.9, 3));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if any `p` is not 0 <= p < 1
+/// - Will return `Err` if either ranking repeats a line
+/// - Will return `Err` if reading either ranking fails
+///
+pub fn rbo_multi_p_from_readers<A, B>(
+    first: A,
+    second: B,
+    ps: &[f64],
+) -> Result<Vec<RboDetailed>, RboError>
+where
+    A: BufRead,
+    B: BufRead,
+{
+    for p in ps {
+        crate::state::validate_persistence(*p)?;
+    }
+    let mut state = RboIncremental::keeping_overlap();
+    read_pairs(&mut state, first, second, |_| {})?;
+    ps.iter()
+        .map(|p| state.detailed_with_persistence(*p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    #[test]
+    fn matches_collected_lines() {
+        let first = "d1\nd2\nd3\nd4\nd5\n";
+        let second = "d3\r\nd1\r\nd9\r\n";
+        let detailed =
+            super::rbo_from_readers(first.as_bytes(), second.as_bytes(), 0.9).expect("valid rbo");
+        let collect = |text: &str| -> Vec<String> {
+            text.as_bytes()
+                .lines()
+                .collect::<Result<_, _>>()
+                .expect("valid utf-8")
+        };
+        assert_eq!(
+            detailed,
+            crate::rbo_detailed(&collect(first), &collect(second), 0.9).expect("valid rbo")
+        );
+    }
+
+    #[test]
+    fn matches_single_persistence() {
+        let first = "d1\nd2\nd3\nd4\nd5\n";
+        let second = "d3\nd1\nd9\n";
+        let ps = [0.5, 0.9, 0.99];
+        let results = super::rbo_multi_p_from_readers(first.as_bytes(), second.as_bytes(), &ps)
+            .expect("valid rbo");
+        for (p, result) in ps.iter().zip(&results) {
+            let single = super::rbo_from_readers(first.as_bytes(), second.as_bytes(), *p)
+                .expect("valid rbo");
+            assert_eq!(result.p, single.p);
+            assert_eq!(
+                (result.first_depth, result.second_depth, result.overlap),
+                (single.first_depth, single.second_depth, single.overlap)
+            );
+            approx::assert_abs_diff_eq!(result.rbo.min, single.rbo.min, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(result.rbo.residual, single.rbo.residual, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(
+                result.rbo.extrapolated,
+                single.rbo.extrapolated,
+                epsilon = 1e-12
+            );
+        }
+        let invalid =
+            super::rbo_multi_p_from_readers("a\n".as_bytes(), "a\n".as_bytes(), &[0.9, 1.0]);
+        assert!(matches!(invalid, Err(crate::RboError::InvalidPersistance)));
+    }
+
+    #[test]
+    fn errors() {
+        let result = super::rbo_from_readers("a\nb\na\n".as_bytes(), "c\n".as_bytes(), 0.9);
+        match result {
+            Err(crate::RboError::DuplicatesInList(duplicate)) => {
+                assert_eq!(duplicate.list, 0);
+                assert_eq!(duplicate.indices, vec![0, 2]);
+                assert_eq!(duplicate.item_debug.as_deref(), Some("\"a\""));
+            }
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+        let invalid: &[u8] = &[b'a', b'\n', 0xff, b'\n'];
+        let result = super::rbo_from_readers("a\nb\n".as_bytes(), invalid, 0.9);
+        assert!(matches!(result, Err(crate::RboError::Io(_))));
+    }
+}
@@ -0,0 +1,17 @@
+//! Loaders for rankings stored outside of the program.
+//!
+//! Each loader produces plain `Vec<String>` rankings, ready to be passed to
+//! [`rbo`](crate::rbo) and friends. Text files too large to be loaded can be
+//! compared as they are read with [`lines::rbo_from_readers`], and the runs of
+//! two retrieval systems query by query with [`trec::rbo_runs`].
+
+pub mod binary;
+#[cfg(feature = "csv")]
+pub mod csv;
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+pub mod lines;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod source;
+pub mod trec;
@@ -0,0 +1,64 @@
+//! Read rankings directly from Redis sorted sets.
+
+use crate::RboError;
+use ::redis::Commands;
+
+/// The direction in which the members of a sorted set are ranked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreOrder {
+    /// Highest score first (`ZREVRANGE`), the usual layout of top-k caches
+    #[default]
+    Descending,
+    /// Lowest score first (`ZRANGE`)
+    Ascending,
+}
+
+///
+/// Read the members of the sorted set `key` as a ranking, using an existing
+/// connection. At most `depth` members are fetched, or the whole set if
+/// `depth` is `None`.
+///
+/// # Errors
+///
+/// - Will return `Err` if the Redis command fails, e.g. because `key` does not hold a sorted set
+///
+pub fn read_sorted_set<C>(
+    connection: &mut C,
+    key: &str,
+    order: ScoreOrder,
+    depth: Option<usize>,
+) -> Result<Vec<String>, RboError>
+where
+    C: ::redis::ConnectionLike,
+{
+    let stop = match depth {
+        Some(0) => return Ok(Vec::new()),
+        Some(depth) => isize::try_from(depth - 1).unwrap_or(isize::MAX),
+        None => -1,
+    };
+    let members = match order {
+        ScoreOrder::Descending => connection.zrevrange(key, 0, stop)?,
+        ScoreOrder::Ascending => connection.zrange(key, 0, stop)?,
+    };
+    Ok(members)
+}
+
+///
+/// Connect to the server at `url` (e.g. `redis://127.0.0.1:6379/0`) and read
+/// the sorted set `key` as a ranking. See [`read_sorted_set`].
+///
+/// # Errors
+///
+/// - Will return `Err` if `url` is invalid or the server can not be reached
+/// - Will return `Err` if the Redis command fails, e.g. because `key` does not hold a sorted set
+///
+pub fn fetch_sorted_set(
+    url: &str,
+    key: &str,
+    order: ScoreOrder,
+    depth: Option<usize>,
+) -> Result<Vec<String>, RboError> {
+    let client = ::redis::Client::open(url)?;
+    let mut connection = client.get_connection()?;
+    read_sorted_set(&mut connection, key, order, depth)
+}
@@ -0,0 +1,126 @@
+//! Fetch rankings on demand, e.g. from a live search endpoint.
+
+use crate::{Rbo, RboError};
+
+/// A system that produces a ranking for a query
+pub trait RankingSource {
+    /// Human readable name of the source, used in reports
+    fn name(&self) -> &str;
+
+    /// Fetch the top `depth` results for `query`
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the ranking can not be retrieved
+    ///
+    fn fetch(&self, query: &str, depth: usize) -> Result<Vec<String>, RboError>;
+}
+
+/// A [`RankingSource`] backed by a shell command printing one item per line
+///
+/// The command template is run with `sh -c`. Occurrences of `{query}` and
+/// `{depth}` are replaced by the shell-quoted query and the depth; both are
+/// also available as the environment variables `RBO_QUERY` and `RBO_DEPTH`.
+/// HTTP endpoints can be queried through e.g. `curl` and `jq`:
+///
+/// ```text
+/// curl -s "https://search.example.com/api?n=$RBO_DEPTH" --data-urlencode "q=$RBO_QUERY" | jq -r '.hits[].id'
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandSource {
+    name: String,
+    template: String,
+}
+
+impl CommandSource {
+    /// Create a source called `name` running the command `template`
+    pub fn new(name: impl Into<String>, template: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            template: template.into(),
+        }
+    }
+}
+
+// quote `value` so the shell passes it through as a single literal word
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+impl RankingSource for CommandSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self, query: &str, depth: usize) -> Result<Vec<String>, RboError> {
+        let command = self
+            .template
+            .replace("{query}", &shell_quote(query))
+            .replace("{depth}", &depth.to_string());
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .env("RBO_QUERY", query)
+            .env("RBO_DEPTH", depth.to_string())
+            .stderr(std::process::Stdio::inherit())
+            .output()?;
+        if !output.status.success() {
+            return Err(RboError::SourceFailed(format!(
+                "{}: `{}` exited with {}",
+                self.name, command, output.status
+            )));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .take(depth)
+            .map(String::from)
+            .collect())
+    }
+}
+
+///
+/// Run every query against both sources and compute RBO between the two
+/// rankings returned for it. Results are returned in query order.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if a source fails to produce a ranking
+/// - Will return `Err` if a ranking contains duplicate items
+///
+pub fn compare_sources<A, B, Q>(
+    first: &A,
+    second: &B,
+    queries: &[Q],
+    depth: usize,
+    p: f64,
+) -> Result<Vec<Rbo>, RboError>
+where
+    A: RankingSource + ?Sized,
+    B: RankingSource + ?Sized,
+    Q: AsRef<str>,
+{
+    queries
+        .iter()
+        .map(|query| {
+            let a = first.fetch(query.as_ref(), depth)?;
+            let b = second.fetch(query.as_ref(), depth)?;
+            crate::rbo(&a, &b, p)
+        })
+        .collect()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{CommandSource, RankingSource};
+
+    #[test]
+    fn command_source_quotes_query() {
+        let source = CommandSource::new("echo", "printf '%s\\n' {query} b c d | head -n {depth}");
+        let ranking = source.fetch("it's a query", 3).expect("command runs");
+        assert_eq!(ranking, vec!["it's a query", "b", "c"]);
+    }
+}
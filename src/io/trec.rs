@@ -0,0 +1,201 @@
+//! Run files in the format of the TREC evaluation campaigns.
+//!
+//! Every line of a run holds one retrieved document of one query as six
+//! whitespace separated columns:
+//!
+//! ```text
+//! qid Q0 docid rank score tag
+//! ```
+//!
+//! A run is read into the ranking of every query, so two systems can be
+//! compared query by query with [`rbo_runs`].
+//!
+//! ```
+//! use rbo::io::trec::{read_run, rbo_runs, RunOrder};
+//!
+//! let first = "q1 Q0 d1 1 2.5 bm25\nq1 Q0 d2 2 1.5 bm25\nq2 Q0 d7 1 0.3 bm25\n";
+//! let second = "q1 Q0 d2 1 9.0 dense\nq1 Q0 d1 2 8.0 dense\nq2 Q0 d7 1 0.9 dense\n";
+//! let first = read_run(first.as_bytes(), RunOrder::Score).expect("valid run");
+//! let second = read_run(second.as_bytes(), RunOrder::Score).expect("valid run");
+//! assert_eq!(first["q1"], vec!["d1", "d2"]);
+//! let scores = rbo_runs(&first, &second, 0.9).expect("valid rbo");
+//! assert_eq!(scores["q2"].extrapolated, 1.0);
+//! ```
+
+use crate::{Rbo, RboError};
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::path::Path;
+
+/// How the documents retrieved for a query are ordered into its ranking
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RunOrder {
+    /// By decreasing score, ties broken by decreasing document id, ignoring
+    /// the rank column as `trec_eval` does
+    #[default]
+    Score,
+    /// By increasing rank, ties broken by decreasing score
+    Rank,
+}
+
+// one line of a run
+struct Entry {
+    docid: String,
+    rank: i64,
+    score: f64,
+}
+
+///
+/// Read a run from `reader`, returning the ranking of every query. Blank
+/// lines are skipped, and the tag column may be missing.
+///
+/// # Errors
+///
+/// - Will return `Err` if reading fails
+/// - Will return `Err` if a line does not have five or six columns, or its
+///   rank or score is not a number
+///
+pub fn read_run<R: BufRead>(
+    reader: R,
+    order: RunOrder,
+) -> Result<BTreeMap<String, Vec<String>>, RboError> {
+    let mut queries: BTreeMap<String, Vec<Entry>> = BTreeMap::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        let (qid, docid, rank, score) = match columns[..] {
+            [] => continue,
+            [qid, _, docid, rank, score] | [qid, _, docid, rank, score, _] => {
+                (qid, docid, rank, score)
+            }
+            _ => return Err(invalid(number, "expected `qid Q0 docid rank score tag`")),
+        };
+        let entry = Entry {
+            docid: docid.to_string(),
+            rank: rank.parse().map_err(|_| invalid(number, "invalid rank"))?,
+            score: score
+                .parse()
+                .map_err(|_| invalid(number, "invalid score"))?,
+        };
+        queries.entry(qid.to_string()).or_default().push(entry);
+    }
+    Ok(queries
+        .into_iter()
+        .map(|(qid, mut entries)| {
+            match order {
+                RunOrder::Score => entries.sort_by(|a, b| {
+                    b.score
+                        .total_cmp(&a.score)
+                        .then_with(|| b.docid.cmp(&a.docid))
+                }),
+                RunOrder::Rank => {
+                    entries.sort_by(|a, b| a.rank.cmp(&b.rank).then(b.score.total_cmp(&a.score)))
+                }
+            }
+            (qid, entries.into_iter().map(|entry| entry.docid).collect())
+        })
+        .collect())
+}
+
+///
+/// Load the run stored in the file at `path`, see [`read_run`]
+///
+/// # Errors
+///
+/// - Will return `Err` if the file can not be read or is not a valid run
+///
+pub fn load_run(
+    path: impl AsRef<Path>,
+    order: RunOrder,
+) -> Result<BTreeMap<String, Vec<String>>, RboError> {
+    read_run(std::io::BufReader::new(std::fs::File::open(path)?), order)
+}
+
+///
+/// RBO between the rankings of every query of the run `first` and the
+/// ranking of the same query in the run `second`
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if a query of `first` is missing from `second`
+/// - Will return `Err` if a run retrieves a document twice for the same query
+///
+pub fn rbo_runs(
+    first: &BTreeMap<String, Vec<String>>,
+    second: &BTreeMap<String, Vec<String>>,
+    p: f64,
+) -> Result<BTreeMap<String, Rbo>, RboError> {
+    first
+        .iter()
+        .map(|(qid, a)| {
+            let b = second
+                .get(qid)
+                .ok_or_else(|| RboError::MissingQuery(qid.clone()))?;
+            Ok((qid.clone(), crate::rbo(a, b, p)?))
+        })
+        .collect()
+}
+
+fn invalid(line: usize, message: &str) -> RboError {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("line {}: {message}", line + 1),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rbo_runs, read_run, RunOrder};
+
+    const RUN: &str = "\
+q2 Q0 d5 1 3.0 sys
+q1 Q0 d1 3 1.0 sys
+
+q1 Q0 d2 1 2.0 sys
+q1 Q0 d3 2 2.0 sys
+q1 Q0 d4 4 0.5
+";
+
+    #[test]
+    fn orders_rankings() {
+        let run = read_run(RUN.as_bytes(), RunOrder::Score).expect("valid run");
+        assert_eq!(run.keys().collect::<Vec<_>>(), vec!["q1", "q2"]);
+        assert_eq!(run["q1"], vec!["d3", "d2", "d1", "d4"]);
+        let run = read_run(RUN.as_bytes(), RunOrder::Rank).expect("valid run");
+        assert_eq!(run["q1"], vec!["d2", "d3", "d1", "d4"]);
+        assert_eq!(run["q2"], vec!["d5"]);
+    }
+
+    #[test]
+    fn compares_runs() {
+        let first = read_run(RUN.as_bytes(), RunOrder::Rank).expect("valid run");
+        let second = read_run(RUN.as_bytes(), RunOrder::Score).expect("valid run");
+        let scores = rbo_runs(&first, &second, 0.9).expect("valid rbo");
+        let expected = crate::rbo(&first["q1"], &second["q1"], 0.9).expect("valid rbo");
+        assert_eq!(scores["q1"], expected);
+        assert_eq!(scores["q2"].extrapolated, 1.0);
+
+        let partial =
+            read_run("q1 Q0 d1 1 1.0 sys\n".as_bytes(), RunOrder::Score).expect("valid run");
+        assert!(matches!(
+            rbo_runs(&first, &partial, 0.9),
+            Err(crate::RboError::MissingQuery(qid)) if qid == "q2"
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for run in [
+            "q1 Q0 d1 1\n",
+            "q1 Q0 d1 x 1.0 sys\n",
+            "q1 Q0 d1 1 high sys\n",
+        ] {
+            assert!(read_run(run.as_bytes(), RunOrder::Score).is_err());
+        }
+        let err = read_run("q1 Q0 d1 1 1.0\nq1 Q0 d2\n".as_bytes(), RunOrder::Score)
+            .expect_err("invalid run");
+        assert!(err.to_string().contains("line 2"));
+    }
+}
@@ -45,7 +45,104 @@
 //! println!("{}",rbo_val);
 //! ```
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "rayon")]
+mod batch;
+mod builder;
+mod calibration;
+pub mod cluster;
+mod components;
+mod consensus;
+mod curve;
+mod drift;
+#[cfg(feature = "elinor")]
+pub mod elinor;
+mod engine;
+mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod float;
+mod fuzzy;
+mod gain;
+mod ids;
+mod incremental;
+pub mod index;
+mod invariants;
+pub mod io;
+pub mod matrix;
+pub mod metrics;
+mod movement;
+mod multi;
+mod null;
+mod options;
+mod ord;
+mod overlap;
+#[cfg(feature = "arbitrary-precision")]
+pub mod precise;
+#[cfg(feature = "python")]
+pub mod python;
+mod ranked;
+mod ranks;
+mod rbp;
+mod reference;
+#[cfg(feature = "report")]
+pub mod report;
+mod scored;
+mod search;
+mod segments;
+mod sensitivity;
 mod state;
+pub mod stats;
+#[cfg(feature = "futures")]
+mod stream;
+mod table;
+mod tail;
+mod ties;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+mod weighted;
+mod weights;
+
+#[cfg(feature = "rayon")]
+pub use batch::{rbo_batch, rbo_batch_in};
+pub use builder::{Normalized, Outputs, RboBuilder};
+pub use calibration::{depth_for_residual, persistence_for_weight, top_weight};
+pub use components::{rbo_distance, rbo_ext, rbo_min, rbo_res};
+pub use consensus::{rbo_consensus, Consensus};
+pub use curve::{rbo_curve, RboPoint};
+pub use drift::{rbo_drift, DriftBaseline};
+pub use engine::RboEngine;
+pub use explain::{rbo_explain, rbo_explain_by_key, ItemContribution, RboExplanation};
+pub use float::rbo_float;
+pub use fuzzy::rbo_fuzzy;
+pub use gain::{rbo_weight_gain, DepthGain};
+pub use ids::rbo_ids;
+pub use incremental::{rbo_iter, rbo_until_residual, RboIncremental, RboUntil};
+pub use invariants::InvariantViolation;
+pub use movement::{rank_movements, rank_movements_by_key, ItemMovement, MovementKind};
+pub use multi::rbo_multi;
+pub use null::{rbo_normalized, rbo_null};
+pub use options::{rbo_with_options, Domain, Duplicates, RboOptions};
+pub use ord::rbo_ord;
+pub use overlap::{average_overlap, overlap_curve};
+pub use ranked::{rbo_ranked, RankedList};
+pub use ranks::{rbo_from_ranks, RankMap};
+pub use rbp::{rbp, Rbp};
+pub use reference::ReferenceRanking;
+pub use scored::{rbo_scored, ScoreTies};
+pub use search::most_similar;
+#[cfg(feature = "rayon")]
+pub use search::{most_similar_par, most_similar_par_in};
+pub use segments::rbo_segments;
+pub use sensitivity::{rbo_sensitivity, RboSensitivity};
+#[cfg(feature = "futures")]
+pub use stream::rbo_from_streams;
+pub use table::PersistenceTable;
+pub use tail::{rbo_tail, Weighting};
+pub use ties::{rbo_ties, rbo_vs_set, TieVariant};
+pub use weighted::rbo_weighted;
+pub use weights::{cumulative_weight, weight_at_depth};
 
 use thiserror::Error;
 
@@ -56,28 +153,280 @@ pub enum RboError {
     #[error("Persistance parameter p must be 0.0 <= p < 1.0")]
     InvalidPersistance,
     /// Individual ranked lists should not contain duplicates
-    #[error("Individual ranked lists should not contain duplicates")]
-    DuplicatesInList,
+    #[error("Individual ranked lists should not contain duplicates: {0}")]
+    DuplicatesInList(Duplicate),
+    /// Weights must be finite, non-negative and not all zero
+    #[error("Weights must be finite, non-negative and not all zero")]
+    InvalidWeights,
+    /// Not enough data points for the requested statistic
+    #[error("Not enough data points for the requested statistic")]
+    InsufficientData,
+    /// All rows of a score table must have the same length
+    #[error("All rows of a score table must have the same length")]
+    RaggedScores,
+    /// Confidence level must be 0 < level < 1
+    #[error("Confidence level must be 0 < level < 1")]
+    InvalidConfidenceLevel,
+    /// Paired scores must have the same length
+    #[error("Paired scores must have the same length")]
+    UnpairedScores,
+    /// Both rankings must contain exactly the same items
+    #[error("Both rankings must contain exactly the same items")]
+    NotConjoint,
+    /// The rankings contain more distinct items than the conjoint domain holds
+    #[error("The rankings contain more distinct items than the conjoint domain holds")]
+    InvalidDomain,
+    /// Scores must not be NaN
+    #[error("Scores must not be NaN")]
+    InvalidScore,
+    /// Items of a ranking share the same score
+    #[error("Items of a ranking share the same score")]
+    TiedScores,
+    /// Ranks must be distinct and contiguous
+    #[error("Ranks must be distinct and contiguous")]
+    InvalidRanks,
+    /// A requested target value is out of range
+    #[error("A requested target value is out of range")]
+    InvalidTarget,
+    /// A query is present in one set of rankings but not the other
+    #[error("Query {0} is missing from one of the rankings")]
+    MissingQuery(String),
+    /// A checkpoint directory does not match the computation being resumed
+    #[error("Checkpoint {0} does not match the computation being resumed")]
+    CheckpointMismatch(String),
+    /// Reading or writing data failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// An item was pushed to a ranking that has already ended
+    #[error("List {0} has already ended")]
+    ListEnded(usize),
+    /// A ranking source failed to produce a ranking
+    #[error("Ranking source failed: {0}")]
+    SourceFailed(String),
+    /// Rounding errors or non-finite intermediate sums made a result meaningless,
+    /// e.g. for `p` so close to 0 that `(1 - p) / p` amplifies every rounding error
+    #[error("Numerical instability: the result is not finite or outside [0, 1]")]
+    NumericalInstability,
+    /// A result breaks a mathematical invariant of RBO, see [`RboBuilder::validate`]
+    #[error("Invariant violated: {0}")]
+    InvariantViolated(InvariantViolation),
+    /// A series would have to be summed to more depths than the limit allows,
+    /// e.g. for `p` so close to 1 that its terms barely shrink
+    #[error("Summing to depth {0} exceeds the limit of {limit} depths", limit = state::MAX_SERIES_DEPTH)]
+    DepthLimitExceeded(usize),
+    /// An Arrow array is not a column of rankings
+    #[cfg(feature = "arrow")]
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+    /// Reading a CSV file failed
+    #[cfg(feature = "csv")]
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    /// A Redis command failed
+    #[cfg(feature = "redis")]
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+}
+
+/// Where a duplicate item was found in the input of a computation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Duplicate {
+    /// The list holding the duplicate, 0 for the first and 1 for the second
+    /// list of a pair, or the position of the list among many
+    pub list: usize,
+    /// The 0-based rank of every occurrence of the duplicated item, in order
+    pub indices: Vec<usize>,
+    /// The `Debug` rendering of the duplicated item. Functions generic over
+    /// `Eq + Hash` items can not render them and leave this `None`, see
+    /// [`with_item_debug`](Duplicate::with_item_debug). Text and Arrow inputs
+    /// and the bindings fill it in.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub item_debug: Option<String>,
+}
+
+impl Duplicate {
+    /// The duplicated item, given the list it was found in
+    ///
+    /// # Panics
+    ///
+    /// - Will panic if `list` is not the list the duplicate was found in
+    ///
+    pub fn item<'a, Item>(&self, list: &'a [Item]) -> &'a Item {
+        &list[self.indices[0]]
+    }
+
+    /// The duplicate with the `Debug` rendering of its item, given the list
+    /// it was found in, e.g. to report which item a generic function refused
+    ///
+    /// ```
+    /// let first = ["a", "b", "a"];
+    /// let Err(rbo::RboError::DuplicatesInList(duplicate)) = rbo::rbo(&first, &["b"], 0.9) else {
+    ///     panic!("expected a duplicate");
+    /// };
+    /// let duplicate = duplicate.with_item_debug(&first);
+    /// assert_eq!(duplicate.to_string(), "the first list repeats \"a\" at ranks [0, 2]");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Will panic if `list` is not the list the duplicate was found in
+    ///
+    #[must_use]
+    pub fn with_item_debug<Item: std::fmt::Debug>(self, list: &[Item]) -> Self {
+        let item_debug = Some(format!("{:?}", self.item(list)));
+        Self { item_debug, ..self }
+    }
+}
+
+impl std::fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.list {
+            0 => write!(f, "the first list")?,
+            1 => write!(f, "the second list")?,
+            n => write!(f, "list {n}")?,
+        }
+        match &self.item_debug {
+            Some(item) => write!(f, " repeats {item} at ranks {:?}", self.indices),
+            None => write!(f, " repeats an item at ranks {:?}", self.indices),
+        }
+    }
+}
+
+impl RboError {
+    /// The error with the `Debug` rendering of the duplicated item filled
+    /// in, if it is a [`RboError::DuplicatesInList`] of a pair of lists, see
+    /// [`Duplicate::with_item_debug`]. Other errors are returned unchanged.
+    ///
+    /// ```
+    /// let (first, second) = (["a", "b"], ["c", "d", "c"]);
+    /// let err = rbo::rbo(&first, &second, 0.9).unwrap_err();
+    /// let err = err.with_item_debug(&first, &second);
+    /// assert!(err.to_string().ends_with("the second list repeats \"c\" at ranks [0, 2]"));
+    /// ```
+    #[must_use]
+    pub fn with_item_debug<Item: std::fmt::Debug>(self, first: &[Item], second: &[Item]) -> Self {
+        match self {
+            RboError::DuplicatesInList(duplicate) if duplicate.list < 2 => {
+                let list = [first, second][duplicate.list];
+                RboError::DuplicatesInList(duplicate.with_item_debug(list))
+            }
+            err => err,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl RboError {
+    // the variant name, a stable identifier for serialized errors
+    fn kind(&self) -> &'static str {
+        match self {
+            RboError::InvalidPersistance => "InvalidPersistance",
+            RboError::DuplicatesInList(_) => "DuplicatesInList",
+            RboError::InvalidWeights => "InvalidWeights",
+            RboError::InsufficientData => "InsufficientData",
+            RboError::RaggedScores => "RaggedScores",
+            RboError::InvalidConfidenceLevel => "InvalidConfidenceLevel",
+            RboError::UnpairedScores => "UnpairedScores",
+            RboError::NotConjoint => "NotConjoint",
+            RboError::InvalidDomain => "InvalidDomain",
+            RboError::InvalidScore => "InvalidScore",
+            RboError::TiedScores => "TiedScores",
+            RboError::InvalidRanks => "InvalidRanks",
+            RboError::InvalidTarget => "InvalidTarget",
+            RboError::MissingQuery(_) => "MissingQuery",
+            RboError::CheckpointMismatch(_) => "CheckpointMismatch",
+            RboError::Io(_) => "Io",
+            RboError::ListEnded(_) => "ListEnded",
+            RboError::SourceFailed(_) => "SourceFailed",
+            RboError::NumericalInstability => "NumericalInstability",
+            RboError::InvariantViolated(_) => "InvariantViolated",
+            RboError::DepthLimitExceeded(_) => "DepthLimitExceeded",
+            #[cfg(feature = "arrow")]
+            RboError::Arrow(_) => "Arrow",
+            #[cfg(feature = "csv")]
+            RboError::Csv(_) => "Csv",
+            #[cfg(feature = "redis")]
+            RboError::Redis(_) => "Redis",
+        }
+    }
+}
+
+/// Errors serialize as `{"kind": <variant>, "message": <display text>}` so they
+/// can be written next to successful results.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RboError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("RboError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
 }
 
 use state::RboState;
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 
-#[derive(Debug)]
-/// The result of the RBO computation
-pub struct Rbo {
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// The result of the RBO computation, in `f64` unless computed by [`rbo_float`]
+pub struct Rbo<F = f64> {
     /// Lower bound estimate of RBO (RBO_min in paper)
-    pub min: f64,
+    pub min: F,
     /// residual uncertainty attendant upon prefix, rather than full, evaluation
     /// Residual corresponding to min; min + res is an upper bound estimate
-    pub residual: f64,
+    pub residual: F,
     /// point estimate by extrapolation from the visible lists,
     /// assuming that the degree of agreement seen up to depth k is continued indefinitely
-    pub extrapolated: f64,
+    pub extrapolated: F,
+}
+
+impl<F: num_traits::Float> Rbo<F> {
+    /// Upper bound on the full RBO, `min + residual`
+    #[must_use]
+    pub fn upper_bound(&self) -> F {
+        self.min + self.residual
+    }
+
+    /// The middle of the interval between `min` and the upper bound
+    #[must_use]
+    pub fn midpoint(&self) -> F {
+        self.min + self.residual / (F::one() + F::one())
+    }
+}
+
+/// An RBO result together with the parameters it was computed with
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboDetailed {
+    /// The RBO result itself
+    pub rbo: Rbo,
+    /// The persistence used
+    pub p: f64,
+    /// Number of items of the first list that were evaluated
+    pub first_depth: usize,
+    /// Number of items of the second list that were evaluated
+    pub second_depth: usize,
+    /// The overlap of the two lists at the depth of the longer one
+    pub overlap: usize,
 }
 
-impl std::fmt::Display for Rbo {
+/// An RBO result together with the per-depth curves it was computed from
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboVerbose {
+    /// The RBO result itself
+    pub rbo: Rbo,
+    /// The overlap X_d at depths d = 1, 2, ... down to the end of the longer list
+    pub overlap: Vec<f64>,
+    /// The agreement A_d = X_d / d at the same depths
+    pub agreement: Vec<f64>,
+}
+
+impl<F: std::fmt::Display> std::fmt::Display for Rbo<F> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
@@ -87,12 +436,72 @@ impl std::fmt::Display for Rbo {
     }
 }
 
-fn contains_duplicates<Item>(list: &[Item]) -> bool
+// the positions of every occurrence of the first item of `list` that occurs
+// more than once, comparing items by the `Q` they borrow as
+fn find_duplicate<Item, Q, S>(list: &[Item], hasher: S) -> Option<Vec<usize>>
+where
+    Item: Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    S: BuildHasher,
+{
+    let mut first_seen = std::collections::HashMap::with_capacity_and_hasher(list.len(), hasher);
+    for (rank, item) in list.iter().enumerate() {
+        let item: &Q = item.borrow();
+        if let Some(&first) = first_seen.get(item) {
+            let indices = (first..list.len())
+                .filter(|&i| list[i].borrow() == item)
+                .collect();
+            return Some(indices);
+        }
+        first_seen.insert(item, rank);
+    }
+    None
+}
+
+// refuse `list`, the `list_index`-th list of the input, if it contains duplicates
+fn check_duplicates<Item>(list: &[Item], list_index: usize) -> Result<(), RboError>
 where
     Item: Eq + Hash,
 {
-    let hash_set: std::collections::HashSet<_> = list.iter().collect();
-    hash_set.len() != list.len()
+    check_duplicates_with(
+        list,
+        list_index,
+        std::collections::hash_map::RandomState::new(),
+    )
+}
+
+// `check_duplicates`, hashing items with `hasher`
+fn check_duplicates_with<Item, S>(
+    list: &[Item],
+    list_index: usize,
+    hasher: S,
+) -> Result<(), RboError>
+where
+    Item: Eq + Hash,
+    S: BuildHasher,
+{
+    check_duplicates_borrowed::<Item, Item, S>(list, list_index, hasher)
+}
+
+// `check_duplicates_with`, comparing items by the `Q` they borrow as
+fn check_duplicates_borrowed<Item, Q, S>(
+    list: &[Item],
+    list_index: usize,
+    hasher: S,
+) -> Result<(), RboError>
+where
+    Item: Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    S: BuildHasher,
+{
+    match find_duplicate::<Item, Q, S>(list, hasher) {
+        Some(indices) => Err(RboError::DuplicatesInList(Duplicate {
+            list: list_index,
+            indices,
+            item_debug: None,
+        })),
+        None => Ok(()),
+    }
 }
 
 ///
@@ -102,34 +511,412 @@ where
 ///
 /// - Will return `Err` if `p` is not 0 <= p < 1
 /// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if rounding errors make the result meaningless, e.g.
+///   for `p` close to 0
 ///
 pub fn rbo<Item>(first: &[Item], second: &[Item], p: f64) -> Result<Rbo, RboError>
 where
     Item: Eq + Hash,
 {
-    let mut rbo_state = RboState::with_persistence(p)?;
+    // only the final sums are needed, not the overlap at every depth
+    let rbo_state = RboState::constant_memory(p)?;
+    check_duplicates(first, 0)?;
+    check_duplicates(second, 1)?;
+    // finalize
+    walk(rbo_state, first, second).into_result()
+}
 
-    // ensure we have no duplicates in lists first
-    if contains_duplicates(first) || contains_duplicates(second) {
-        return Err(crate::RboError::DuplicatesInList);
+///
+/// Like [`rbo`], but the residual series of very long lists, which is summed
+/// in chunks concurrently, is summed on `pool` instead of the global rayon
+/// thread pool.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if rounding errors make the result meaningless, e.g.
+///   for `p` close to 0
+///
+#[cfg(feature = "rayon")]
+pub fn rbo_in<Item>(
+    pool: &rayon::ThreadPool,
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash + Sync,
+{
+    pool.install(|| rbo(first, second, p))
+}
+
+///
+/// Like [`rbo`], but hashes items with `hasher` instead of the default
+/// SipHash, e.g. a faster non-cryptographic hash for short string items.
+///
+/// ```
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::BuildHasherDefault;
+///
+/// let first = vec!["a", "b", "c"];
+/// let second = vec!["b", "c", "d"];
+/// let hasher = BuildHasherDefault::<DefaultHasher>::default();
+/// let rbo = rbo::rbo_with_hasher(&first, &second, 0.9, hasher).expect("valid rbo");
+/// assert_eq!(rbo, rbo::rbo(&first, &second, 0.9).expect("valid rbo"));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_with_hasher<Item, S>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    hasher: S,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+    S: BuildHasher + Clone,
+{
+    let rbo_state = RboState::constant_memory_with_hasher(p, hasher.clone())?;
+    check_duplicates_with(first, 0, hasher.clone())?;
+    check_duplicates_with(second, 1, hasher)?;
+    walk(rbo_state, first, second).into_result()
+}
+
+///
+/// Like [`rbo`], but the two lists may hold different item types that borrow
+/// as the same `Q`, e.g. a `Vec<String>` against a `Vec<&str>`, without
+/// converting one list into the other's type first.
+///
+/// ```
+/// let first: Vec<String> = vec!["a".into(), "b".into(), "c".into()];
+/// let second = vec!["b", "c", "d"];
+/// let rbo = rbo::rbo_borrowed::<_, _, str>(&first, &second, 0.9).expect("valid rbo");
+/// assert_eq!(rbo, rbo::rbo(&["a", "b", "c"], &second, 0.9).expect("valid rbo"));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_borrowed<A, B, Q>(first: &[A], second: &[B], p: f64) -> Result<Rbo, RboError>
+where
+    A: Borrow<Q>,
+    B: Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+{
+    let rbo_state = RboState::constant_memory(p)?;
+    let hasher = std::collections::hash_map::RandomState::new();
+    check_duplicates_borrowed::<A, Q, _>(first, 0, hasher.clone())?;
+    check_duplicates_borrowed::<B, Q, _>(second, 1, hasher)?;
+    walk_borrowed(rbo_state, first, second).into_result()
+}
+
+///
+/// RBO between two rankings of rich items, compared by the key `key`
+/// extracts from each item. Only the key needs to implement `Eq + Hash`.
+///
+/// ```
+/// struct SearchHit {
+///     docid: u64,
+///     score: f32,
+/// }
+///
+/// let first = vec![SearchHit { docid: 1, score: 0.9 }, SearchHit { docid: 2, score: 0.5 }];
+/// let second = vec![SearchHit { docid: 2, score: 0.8 }, SearchHit { docid: 1, score: 0.7 }];
+/// let by_key = rbo::rbo_by_key(&first, &second, 0.9, |hit| hit.docid).expect("valid rbo");
+/// assert_eq!(by_key, rbo::rbo(&[1, 2], &[2, 1], 0.9).expect("valid rbo"));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if two items of the same list have the same key
+///
+pub fn rbo_by_key<T, K, F>(first: &[T], second: &[T], p: f64, mut key: F) -> Result<Rbo, RboError>
+where
+    K: Eq + Hash,
+    F: FnMut(&T) -> K,
+{
+    let first: Vec<K> = first.iter().map(&mut key).collect();
+    let second: Vec<K> = second.iter().map(&mut key).collect();
+    rbo(&first, &second, p)
+}
+
+///
+/// RBO between two rankings whose items are compared with the matching
+/// function `eq` instead of `Eq + Hash`, e.g. for items with floating point
+/// payloads or handles that can only be compared through another API. Items
+/// are matched by position, so this takes O((n + m)^2) calls to `eq`.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if two items of the same list match each other
+///
+pub fn rbo_by<T, F>(first: &[T], second: &[T], p: f64, mut eq: F) -> Result<Rbo, RboError>
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut duplicate = |list: &[T]| {
+        list.iter().enumerate().find_map(|(i, a)| {
+            let repeats: Vec<usize> = (i + 1..list.len()).filter(|&j| eq(a, &list[j])).collect();
+            (!repeats.is_empty()).then(|| std::iter::once(i).chain(repeats).collect())
+        })
+    };
+    for (list, items) in [first, second].into_iter().enumerate() {
+        if let Some(indices) = duplicate(items) {
+            return Err(RboError::DuplicatesInList(Duplicate {
+                list,
+                indices,
+                item_debug: None,
+            }));
+        }
+    }
+    // items of the first list are identified by their position, items of the
+    // second by the position of their match in the first list, if any
+    let first_ids: Vec<usize> = (0..first.len()).collect();
+    let second_ids: Vec<usize> = second
+        .iter()
+        .enumerate()
+        .map(|(j, b)| {
+            first
+                .iter()
+                .position(|a| eq(a, b))
+                .unwrap_or(first.len() + j)
+        })
+        .collect();
+    rbo(&first_ids, &second_ids, p)
+}
+
+///
+/// Like [`rbo`], but also returns the persistence, the evaluated depths and
+/// the final overlap along with the result.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_detailed<Item>(first: &[Item], second: &[Item], p: f64) -> Result<RboDetailed, RboError>
+where
+    Item: Eq + Hash,
+{
+    let rbo_state = evaluate(first, second, p)?;
+    let overlap = rbo_state.overlap().last().copied().unwrap_or(0.0) as usize;
+    Ok(RboDetailed {
+        rbo: rbo_state.into_result()?,
+        p,
+        first_depth: first.len(),
+        second_depth: second.len(),
+        overlap,
+    })
+}
+
+///
+/// Like [`rbo`], but also returns the overlap X_d and agreement A_d at every
+/// depth, e.g. for the diagnostic plots of the paper.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_verbose<Item>(first: &[Item], second: &[Item], p: f64) -> Result<RboVerbose, RboError>
+where
+    Item: Eq + Hash,
+{
+    let rbo_state = evaluate(first, second, p)?;
+    Ok(RboVerbose {
+        overlap: rbo_state.overlap()[1..].to_vec(),
+        agreement: rbo_state.agreement(),
+        rbo: rbo_state.into_result()?,
+    })
+}
+
+///
+/// Like [`rbo`], but calls `observer` with the depth, the overlap X_d and the
+/// extrapolated RBO of the lists down to that depth after every depth, e.g.
+/// to stream convergence data into telemetry.
+///
+/// ```
+/// let first = vec!["a", "b", "c", "d"];
+/// let second = vec!["b", "a", "d", "c"];
+/// let mut trace = Vec::new();
+/// let rbo = rbo::rbo_observed(&first, &second, 0.9, |depth, overlap, ext| {
+///     trace.push((depth, overlap, ext))
+/// })
+/// .expect("valid rbo");
+/// assert_eq!(trace.len(), 4);
+/// assert_eq!(trace[1].1, 2.0);
+/// assert!((trace[3].2 - rbo.extrapolated).abs() < 1e-12);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_observed<Item, O>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    observer: O,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+    O: FnMut(usize, f64, f64),
+{
+    let rbo_state = RboState::constant_memory(p)?.with_observer(observer);
+    check_duplicates(first, 0)?;
+    check_duplicates(second, 1)?;
+    walk(rbo_state, first, second).into_result()
+}
+
+///
+/// RBO for several persistence values at once. The lists are walked a single
+/// time and only the final sums are computed per `p`, which is much cheaper
+/// than calling [`rbo`] once per value. Results are in the order of `ps`.
+///
+/// # Errors
+///
+/// - Will return `Err` if any `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_multi_p<Item>(first: &[Item], second: &[Item], ps: &[f64]) -> Result<Vec<Rbo>, RboError>
+where
+    Item: Eq + Hash,
+{
+    for p in ps {
+        state::validate_persistence(*p)?;
+    }
+    // the persistence used for the walk is irrelevant
+    let rbo_state = evaluate(first, second, 0.5)?;
+    ps.iter()
+        .map(|p| rbo_state.result_with_persistence(*p))
+        .collect()
+}
+
+///
+/// RBO evaluated only down to rank `depth`, even if the lists are longer.
+/// Items below `depth` are never looked at (so they are not checked for
+/// duplicates either) and the residual covers all of the weight below
+/// `depth`, as if the rankings had only been observed to that depth.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if the evaluated prefixes contain duplicate items
+///
+pub fn rbo_at_depth<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    depth: usize,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+{
+    let first = &first[..depth.min(first.len())];
+    let second = &second[..depth.min(second.len())];
+    rbo(first, second, p)
+}
+
+///
+/// The exact RBO of two complete rankings of the same set of items. As
+/// nothing is left unseen there is no residual and no need to extrapolate,
+/// every depth beyond the end of the rankings has full agreement.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if the lists are not permutations of the same items
+///
+pub fn rbo_exact<Item>(first: &[Item], second: &[Item], p: f64) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    if first.len() != second.len() {
+        return Err(RboError::NotConjoint);
+    }
+    match evaluate(first, second, p)?.into_conjoint_result(first.len()) {
+        Ok(rbo) => Ok(rbo.min),
+        Err(RboError::InvalidDomain) => Err(RboError::NotConjoint),
+        Err(e) => Err(e),
     }
+}
+
+// feed both lists through the RBO state machine, leaving it ready to be finalized
+fn evaluate<'a, Item>(
+    first: &'a [Item],
+    second: &'a [Item],
+    p: f64,
+) -> Result<RboState<&'a Item>, RboError>
+where
+    Item: Eq + Hash,
+{
+    let rbo_state = RboState::with_persistence(p)?;
+
+    // ensure we have no duplicates in lists first
+    check_duplicates(first, 0)?;
+    check_duplicates(second, 1)?;
+    Ok(walk(rbo_state, first, second))
+}
+
+// feed both lists, already checked for duplicates, through the RBO state machine
+fn walk<'a, Item, O, S>(
+    rbo_state: RboState<&'a Item, O, S>,
+    first: &'a [Item],
+    second: &'a [Item],
+) -> RboState<&'a Item, O, S>
+where
+    Item: Eq + Hash,
+    O: FnMut(usize, f64, f64),
+    S: BuildHasher,
+{
+    walk_borrowed::<Item, Item, Item, O, S>(rbo_state, first, second)
+}
 
+// `walk`, over lists of different item types compared by the `Q` they borrow as
+fn walk_borrowed<'a, A, B, Q, O, S>(
+    mut rbo_state: RboState<&'a Q, O, S>,
+    first: &'a [A],
+    second: &'a [B],
+) -> RboState<&'a Q, O, S>
+where
+    A: Borrow<Q>,
+    B: Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    O: FnMut(usize, f64, f64),
+    S: BuildHasher,
+{
+    #[cfg(feature = "tracing")]
+    let _span =
+        tracing::debug_span!("rbo_update", first = first.len(), second = second.len()).entered();
     for (a, b) in first.iter().zip(second) {
-        rbo_state.update(a, Some(b));
+        rbo_state.update(a.borrow(), Some(b.borrow()));
     }
     // ensure we process the remainder if unequal lists
-    let remainder = match first.len().cmp(&second.len()) {
-        Ordering::Less => Some(second.iter().skip(first.len())),
-        Ordering::Equal => None,
-        Ordering::Greater => Some(first.iter().skip(second.len())),
-    };
-    if let Some(items) = remainder {
-        for item in items {
-            rbo_state.update(item, None);
+    match first.len().cmp(&second.len()) {
+        Ordering::Less => {
+            for item in &second[first.len()..] {
+                rbo_state.update(item.borrow(), None);
+            }
+        }
+        Ordering::Equal => {}
+        Ordering::Greater => {
+            for item in &first[second.len()..] {
+                rbo_state.update(item.borrow(), None);
+            }
         }
     }
-    // finalize
-    Ok(rbo_state.into_result())
+    rbo_state
 }
 
 #[cfg(test)]
@@ -156,60 +943,668 @@ mod tests {
         approx::assert_abs_diff_eq!(computed_rbo.residual, 0.232_860, epsilon = 0.000_001);
     }
 
-    #[derive(serde::Serialize, serde::Deserialize, Debug)]
-    struct TestCase {
-        other: String,
-        p: f64,
-        rbo: f64,
+    #[test]
+    fn test_consensus_weights() {
+        let ranking: Vec<char> = "abcdefg".chars().collect();
+        let gold: Vec<char> = "abcdefg".chars().collect();
+        let clicks: Vec<char> = "gfedcba".chars().collect();
+        let consensus = super::rbo_consensus(&ranking, &[(&gold, 3.0), (&clicks, 1.0)], 0.9)
+            .expect("valid rbo");
+        approx::assert_abs_diff_eq!(consensus.weights[0], 0.75);
+        let expected = 0.75 * consensus.components[0].extrapolated
+            + 0.25 * consensus.components[1].extrapolated;
+        approx::assert_abs_diff_eq!(consensus.combined.extrapolated, expected, epsilon = 1e-12);
+        assert!(super::rbo_consensus(&ranking, &[(&gold, -1.0)], 0.9).is_err());
     }
 
     #[test]
-    fn ext_permute_comparison_to_william_webber() {
-        let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        d.push("test_data/permute_abc.json");
-        let test_data_file = std::fs::File::open(d).expect("open test data file");
-        let test_cases: Vec<TestCase> =
-            serde_json::from_reader(&test_data_file).expect("read test data");
+    fn test_rank_movements() {
+        use super::MovementKind;
+        let first: Vec<char> = "abcd".chars().collect();
+        let second: Vec<char> = "bace".chars().collect();
+        let movements = super::rank_movements(&first, &second).expect("no duplicates");
+        let kinds: Vec<_> = movements.iter().map(|m| (*m.item, m.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ('a', MovementKind::Moved),
+                ('b', MovementKind::Moved),
+                ('c', MovementKind::Unchanged),
+                ('d', MovementKind::Dropped),
+                ('e', MovementKind::New),
+            ]
+        );
+        assert_eq!(movements[4].target_rank, Some(4));
+        assert_eq!(movements[4].source_rank, None);
+    }
 
-        for t in test_cases {
-            let second = t.other.chars().collect::<Vec<_>>();
-            let mut first = second.clone();
-            first.sort_unstable();
-            let computed_rbo = super::rbo(&first, &second, t.p).expect("valid rbo");
-            approx::assert_abs_diff_eq!(computed_rbo.extrapolated, t.rbo, epsilon = 0.001);
-        }
+    #[test]
+    fn test_weight_gain_identical() {
+        let first: Vec<char> = "abcdefg".chars().collect();
+        let gains = super::rbo_weight_gain(&first, &first, 0.9).expect("valid rbo");
+        assert_eq!(gains.len(), 7);
+        assert!(gains.iter().all(|g| g.forfeited == 0.0));
+        let gained: f64 = gains.iter().map(|g| g.gained).sum();
+        approx::assert_abs_diff_eq!(gained, 1.0 - 0.9_f64.powi(7), epsilon = 1e-12);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn ext_uneven_comparison_to_william_webber() {
-        let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        d.push("test_data/uneven_abc.json");
-        let test_data_file = std::fs::File::open(d).expect("open test data file");
-        let test_cases: Vec<TestCase> =
-            serde_json::from_reader(&test_data_file).expect("read test data");
-        let first = "abcdefghijklmnopqrstuvwxyz".chars().collect::<Vec<_>>();
-        for t in test_cases {
-            let second = t.other.chars().collect::<Vec<_>>();
-            let computed_rbo = super::rbo(&first, &second, t.p).expect("valid rbo");
-            approx::assert_abs_diff_eq!(computed_rbo.extrapolated, t.rbo, epsilon = 0.001);
+    fn test_serde_round_trip() {
+        let first: Vec<char> = "abcdefg".chars().collect();
+        let second: Vec<char> = "abdcgef".chars().collect();
+        let rbo = super::rbo(&first, &second, 0.9).expect("valid rbo");
+        let json = serde_json::to_string(&rbo).expect("serialize rbo");
+        let back: super::Rbo = serde_json::from_str(&json).expect("deserialize rbo");
+        assert_eq!(rbo, back);
+
+        let err = super::rbo(&first, &second, 1.5).unwrap_err();
+        let json = serde_json::to_value(&err).expect("serialize error");
+        assert_eq!(json["kind"], "InvalidPersistance");
+        assert_eq!(json["message"], err.to_string());
+    }
+
+    #[test]
+    fn test_components_match_rbo() {
+        let cases = [
+            ("abcdefg", "abdcgef"),
+            ("abcdefghij", "zyxabc"),
+            ("abc", "defghijk"),
+            ("abcdef", "abcdef"),
+            ("a", "ba"),
+        ];
+        for (first, second) in cases {
+            let first: Vec<char> = first.chars().collect();
+            let second: Vec<char> = second.chars().collect();
+            for p in [0.5, 0.9, 0.98] {
+                let rbo = super::rbo(&first, &second, p).expect("valid rbo");
+                let ext = super::rbo_ext(&first, &second, p).expect("valid rbo");
+                let min = super::rbo_min(&first, &second, p).expect("valid rbo");
+                let res = super::rbo_res(&second, &first, p).expect("valid rbo");
+                approx::assert_abs_diff_eq!(ext, rbo.extrapolated, epsilon = 1e-12);
+                approx::assert_abs_diff_eq!(min, rbo.min, epsilon = 1e-12);
+                approx::assert_abs_diff_eq!(res, rbo.residual, epsilon = 1e-12);
+            }
         }
     }
 
-    #[derive(serde::Serialize, serde::Deserialize, Debug)]
-    struct TestCaseSp {
-        other: String,
-        p: f64,
-        rbo_ext: f64,
-        rbo_min: f64,
-        rbo_res: f64,
+    #[test]
+    fn test_components_errors() {
+        let first: Vec<char> = "abca".chars().collect();
+        let second: Vec<char> = "abc".chars().collect();
+        assert!(matches!(
+            super::rbo_ext(&first, &second, 0.9),
+            Err(super::RboError::DuplicatesInList(_))
+        ));
+        assert!(matches!(
+            super::rbo_min(&second, &second, 1.0),
+            Err(super::RboError::InvalidPersistance)
+        ));
+        assert!(matches!(
+            super::rbo_res(&second, &first, 0.9),
+            Err(super::RboError::DuplicatesInList(_))
+        ));
     }
 
     #[test]
-    fn uneven_comparison_to_sp() {
-        let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        d.push("test_data/uneven_abc_sp.json");
-        let test_data_file = std::fs::File::open(d).expect("open test data file");
-        let test_cases: Vec<TestCaseSp> =
+    fn test_builder() {
+        use super::{Domain, Duplicates, Outputs, RboBuilder, RboOptions, TieVariant};
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let second: Vec<char> = "badcfehg".chars().collect();
+        let builder = RboBuilder::new(0.9);
+        assert_eq!(
+            builder.compute(&first, &second).expect("valid rbo"),
+            super::rbo(&first, &second, 0.9).expect("valid rbo")
+        );
+        assert_eq!(
+            builder
+                .max_depth(4)
+                .compute(&first, &second)
+                .expect("valid rbo"),
+            super::rbo_at_depth(&first, &second, 0.9, 4).expect("valid rbo")
+        );
+
+        let ext = builder
+            .outputs(Outputs::EXTRAPOLATED)
+            .compute(&first, &second)
+            .expect("valid rbo");
+        approx::assert_abs_diff_eq!(
+            ext.extrapolated,
+            super::rbo_ext(&first, &second, 0.9).expect("valid rbo"),
+            epsilon = 1e-12
+        );
+        assert!(ext.min.is_nan() && ext.residual.is_nan());
+
+        let options = RboOptions {
+            domain: Domain::Conjoint { size: 8 },
+            duplicates: Duplicates::DedupKeepFirst,
+        };
+        let configured = builder
+            .domain(options.domain)
+            .duplicates(options.duplicates);
+        assert_eq!(configured.options(), options);
+        assert_eq!(
+            configured.compute(&first, &second).expect("valid rbo"),
+            super::rbo_with_options(&first, &second, 0.9, options).expect("valid rbo")
+        );
+
+        let grouped: Vec<Vec<char>> = vec![vec!['a', 'b'], vec!['c'], vec!['d', 'e']];
+        let other: Vec<Vec<char>> = vec![vec!['b'], vec!['a', 'c', 'd'], vec!['e']];
+        let tied = builder
+            .ties(TieVariant::A)
+            .max_depth(2)
+            .compute_ties(&grouped, &other)
+            .expect("valid rbo");
+        let expected =
+            super::rbo_ties(&grouped[..1], &other[..2], 0.9, TieVariant::A).expect("valid rbo");
+        assert_eq!(tied, expected);
+
+        assert!(matches!(
+            RboBuilder::new(1.0).compute(&first, &second),
+            Err(super::RboError::InvalidPersistance)
+        ));
+    }
+
+    #[test]
+    fn test_tail_weighting() {
+        use super::{RboBuilder, Weighting};
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let second: Vec<char> = "xbyzaqhc".chars().collect();
+        let reversed = |list: &[char]| list.iter().rev().copied().collect::<Vec<_>>();
+        let tail = super::rbo_tail(&first, &second, 0.9).expect("valid rbo");
+        assert_eq!(
+            tail,
+            super::rbo(&reversed(&first), &reversed(&second), 0.9).expect("valid rbo")
+        );
+        // the bottom of two lists that only differ at the top agrees more
+        let swapped: Vec<char> = "bacdefgh".chars().collect();
+        let head = super::rbo(&first, &swapped, 0.9).expect("valid rbo");
+        let tail = super::rbo_tail(&first, &swapped, 0.9).expect("valid rbo");
+        assert!(tail.extrapolated > head.extrapolated);
+
+        let builder = RboBuilder::new(0.9).weighting(Weighting::Tail);
+        assert_eq!(builder.compute(&first, &swapped).expect("valid rbo"), tail);
+        assert_eq!(
+            builder
+                .max_depth(3)
+                .compute(&first, &second)
+                .expect("valid rbo"),
+            super::rbo(&reversed(&first)[..3], &reversed(&second)[..3], 0.9).expect("valid rbo")
+        );
+        let groups = vec![vec!['a', 'b'], vec!['c']];
+        let other = vec![vec!['c'], vec!['b', 'a']];
+        assert_eq!(
+            builder.compute_ties(&groups, &other).expect("valid rbo"),
+            RboBuilder::new(0.9)
+                .compute_ties(&[vec!['c'], vec!['a', 'b']], &[vec!['b', 'a'], vec!['c']])
+                .expect("valid rbo")
+        );
+    }
+
+    #[test]
+    fn test_builder_validate() {
+        use super::{Domain, Duplicates, InvariantViolation, RboBuilder, RboError, RboPoint};
+        use rand::seq::SliceRandom;
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut domain: Vec<usize> = (0..60).collect();
+        for p in [0.5, 0.9, 0.99] {
+            for _ in 0..50 {
+                domain.shuffle(&mut rng);
+                let first = domain[..rng.gen_range(0..40)].to_vec();
+                domain.shuffle(&mut rng);
+                let second = domain[..rng.gen_range(1..40)].to_vec();
+                for builder in [
+                    RboBuilder::new(p),
+                    RboBuilder::new(p).domain(Domain::Conjoint { size: 60 }),
+                    RboBuilder::new(p).duplicates(Duplicates::Multiset),
+                ] {
+                    // the extrapolated RBO of an empty list is NaN, compare the text
+                    let validated = builder.validate(true).compute(&first, &second);
+                    assert_eq!(
+                        format!("{:?}", validated.expect("valid rbo")),
+                        format!("{:?}", builder.compute(&first, &second).expect("valid rbo"))
+                    );
+                }
+            }
+        }
+        // many uneven pairs, where extrapolation may exceed min + residual
+        let mut domain: Vec<usize> = (0..100).collect();
+        for _ in 0..3000 {
+            let p = rng.gen_range(0.1..0.99);
+            domain.shuffle(&mut rng);
+            let first = domain[..rng.gen_range(1..80)].to_vec();
+            domain.shuffle(&mut rng);
+            let second = domain[..rng.gen_range(1..80)].to_vec();
+            let builder = RboBuilder::new(p).validate(true);
+            assert!(builder.compute(&first, &second).is_ok());
+        }
+
+        let rbo = |min, residual, extrapolated| super::Rbo {
+            min,
+            residual,
+            extrapolated,
+        };
+        assert!(super::invariants::check_bounds(rbo(0.2, 0.5, 0.4)).is_ok());
+        assert!(super::invariants::check_bounds(rbo(0.2, 0.5, f64::NAN)).is_ok());
+        // extrapolation is not bounded by min + residual on uneven lists
+        assert!(super::invariants::check_bounds(rbo(0.2, 0.5, 0.8)).is_ok());
+        for broken in [
+            rbo(0.2, 0.5, 0.1),
+            rbo(0.2, 0.5, 1.2),
+            rbo(0.2, 0.5, -0.1),
+            rbo(-0.1, 0.5, 0.1),
+            rbo(0.6, 0.5, 0.8),
+            rbo(f64::NAN, 0.5, 0.4),
+        ] {
+            assert!(matches!(
+                super::invariants::check_bounds(broken),
+                Err(RboError::InvariantViolated(InvariantViolation::Bounds(_)))
+            ));
+        }
+        let point = |depth, min, residual| RboPoint {
+            depth,
+            overlap: 0,
+            rbo: rbo(min, residual, min),
+        };
+        let curve = [point(1, 0.1, 0.8), point(2, 0.2, 0.6), point(3, 0.15, 0.6)];
+        assert!(super::invariants::check_curve(&curve[..2]).is_ok());
+        assert!(matches!(
+            super::invariants::check_curve(&curve),
+            Err(RboError::InvariantViolated(
+                InvariantViolation::MinDecreasing { depth: 3, .. }
+            ))
+        ));
+        let curve = [point(1, 0.1, 0.5), point(2, 0.2, 0.6)];
+        assert!(matches!(
+            super::invariants::check_curve(&curve),
+            Err(RboError::InvariantViolated(
+                InvariantViolation::UpperBoundIncreasing { depth: 2, .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_builder_normalize() {
+        use super::RboBuilder;
+        let first = ["A", "b", "C"];
+        let second = ["c", "B", "d"];
+        let lower = |item: &&str| item.to_lowercase();
+        let normalized = RboBuilder::new(0.9).normalize(lower);
+        assert_eq!(
+            normalized.compute(&first, &second).expect("valid rbo"),
+            super::rbo(&["a", "b", "c"], &["c", "b", "d"], 0.9).expect("valid rbo")
+        );
+        // the duplicate check runs on the normalized items
+        match normalized.compute(&["a", "A"], &second) {
+            Err(super::RboError::DuplicatesInList(duplicate)) => {
+                assert_eq!(duplicate.list, 0);
+                assert_eq!(duplicate.indices, vec![0, 1]);
+            }
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+        let tied = normalized
+            .compute_ties(&[vec!["A", "b"]], &[vec!["B"], vec!["a"]])
+            .expect("valid rbo");
+        assert_eq!(
+            tied,
+            RboBuilder::new(0.9)
+                .compute_ties(&[vec!["a", "b"]], &[vec!["b"], vec!["a"]])
+                .expect("valid rbo")
+        );
+    }
+
+    #[test]
+    fn test_duplicate_details() {
+        let first: Vec<char> = "abcdef".chars().collect();
+        let second: Vec<char> = "xbyzbqb".chars().collect();
+        let err = super::rbo(&first, &second, 0.9).unwrap_err();
+        let super::RboError::DuplicatesInList(duplicate) = err else {
+            panic!("expected a duplicate, got {err:?}");
+        };
+        assert_eq!(duplicate.list, 1);
+        assert_eq!(duplicate.indices, vec![1, 4, 6]);
+        assert_eq!(*duplicate.item(&second), 'b');
+        assert_eq!(
+            duplicate.to_string(),
+            "the second list repeats an item at ranks [1, 4, 6]"
+        );
+        // generic items are only rendered on request
+        assert_eq!(duplicate.item_debug, None);
+        let duplicate = duplicate.with_item_debug(&second);
+        assert_eq!(duplicate.item_debug.as_deref(), Some("'b'"));
+        assert_eq!(
+            duplicate.to_string(),
+            "the second list repeats 'b' at ranks [1, 4, 6]"
+        );
+        let err = super::rbo(&second, &first, 0.9).unwrap_err();
+        assert!(err
+            .with_item_debug(&second, &first)
+            .to_string()
+            .ends_with("the first list repeats 'b' at ranks [1, 4, 6]"));
+        let ids = super::rbo_ids(&[1, 2], &[3, 4, 3], 0.9).unwrap_err();
+        assert!(ids.to_string().ends_with("repeats 3 at ranks [0, 2]"));
+    }
+
+    #[test]
+    fn test_rbo_iter() {
+        let cases = [("abcdefg", "abdcgef"), ("abc", "zyxabcw"), ("abcdef", "")];
+        for (first, second) in cases {
+            let owned: (Vec<char>, Vec<char>) = (first.chars().collect(), second.chars().collect());
+            let expected = super::rbo(&owned.0, &owned.1, 0.9).expect("valid rbo");
+            let computed = super::rbo_iter(first.chars(), second.chars(), 0.9).expect("valid rbo");
+            approx::assert_abs_diff_eq!(computed.min, expected.min, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(computed.residual, expected.residual, epsilon = 1e-12);
+            if !second.is_empty() {
+                approx::assert_abs_diff_eq!(
+                    computed.extrapolated,
+                    expected.extrapolated,
+                    epsilon = 1e-12
+                );
+            }
+        }
+
+        let err = super::rbo_iter("abc".chars(), "xbyb".chars(), 0.9).unwrap_err();
+        let super::RboError::DuplicatesInList(duplicate) = err else {
+            panic!("expected a duplicate, got {err:?}");
+        };
+        assert_eq!(duplicate.list, 1);
+        assert_eq!(duplicate.indices, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_incremental() {
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let second: Vec<char> = "bazdc".chars().collect();
+        let mut state = super::RboIncremental::new(0.9).expect("valid p");
+        for (depth, a) in first.iter().enumerate() {
+            state
+                .push(*a, second.get(depth).copied())
+                .expect("no duplicates");
+            let expected = super::rbo(
+                &first[..=depth],
+                &second[..second.len().min(depth + 1)],
+                0.9,
+            )
+            .expect("valid rbo");
+            assert_eq!(state.depth(), depth + 1);
+            assert_eq!(state.current(), expected);
+        }
+        assert!(matches!(
+            state.push_second('q'),
+            Err(super::RboError::ListEnded(1))
+        ));
+        assert!(matches!(
+            state.push('a', None),
+            Err(super::RboError::DuplicatesInList(_))
+        ));
+        // refused items leave the state untouched
+        assert_eq!(state.depth(), first.len());
+        assert_eq!(
+            state.into_result(),
+            super::rbo(&first, &second, 0.9).expect("valid rbo")
+        );
+    }
+
+    #[test]
+    fn test_rbo_until_residual() {
+        let first: Vec<u32> = (0..1000).collect();
+        let second: Vec<u32> = (0..1000).rev().collect();
+        let mut pulled = 0;
+        let result = super::rbo_until_residual(
+            first.iter().inspect(|_| pulled += 1),
+            second.iter(),
+            0.9,
+            0.01,
+        )
+        .expect("valid rbo");
+        assert!(result.converged);
+        assert_eq!(pulled, result.depth);
+        assert!(result.depth < 100);
+        let truncated = super::rbo_at_depth(&first, &second, 0.9, result.depth).expect("valid rbo");
+        assert_eq!(result.rbo, truncated);
+        assert!(result.rbo.residual < 0.01);
+
+        let short =
+            super::rbo_until_residual("abc".chars(), "cba".chars(), 0.9, 0.01).expect("valid rbo");
+        assert!(!short.converged);
+        assert_eq!(short.depth, 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_incremental_resume() {
+        let first = ["a", "b", "c", "d", "e", "f"].map(String::from);
+        let second = ["b", "x", "a", "d", "y"].map(String::from);
+        let mut state = super::RboIncremental::new(0.9).expect("valid p");
+        for (a, b) in first.iter().zip(&second).take(3) {
+            state
+                .push(a.clone(), Some(b.clone()))
+                .expect("no duplicates");
+        }
+        let saved = serde_json::to_string(&state).expect("serialize state");
+        let mut state: super::RboIncremental<String> =
+            serde_json::from_str(&saved).expect("deserialize state");
+        assert_eq!(state.depth(), 3);
+        for (depth, a) in first.iter().enumerate().skip(3) {
+            state
+                .push(a.clone(), second.get(depth).cloned())
+                .expect("no duplicates");
+        }
+        assert!(matches!(
+            state.push("a".to_string(), None),
+            Err(super::RboError::DuplicatesInList(_))
+        ));
+        assert_eq!(
+            state.into_result(),
+            super::rbo(&first, &second, 0.9).expect("valid rbo")
+        );
+    }
+
+    #[test]
+    fn test_observer() {
+        let first: Vec<char> = "abcdefghij".chars().collect();
+        let second: Vec<char> = "bxaydc".chars().collect();
+        let mut trace = Vec::new();
+        let rbo = super::rbo_observed(&first, &second, 0.9, |depth, overlap, ext| {
+            trace.push((depth, overlap, ext));
+        })
+        .expect("valid rbo");
+        assert_eq!(rbo, super::rbo(&first, &second, 0.9).expect("valid rbo"));
+        assert_eq!(trace.len(), first.len());
+        let verbose = super::rbo_verbose(&first, &second, 0.9).expect("valid rbo");
+        for (depth, overlap, ext) in trace {
+            let prefix = super::rbo_at_depth(&first, &second, 0.9, depth).expect("valid rbo");
+            assert_eq!(overlap, verbose.overlap[depth - 1]);
+            approx::assert_abs_diff_eq!(ext, prefix.extrapolated, epsilon = 1e-12);
+        }
+
+        let mut depths = Vec::new();
+        let mut state = super::RboIncremental::new(0.9)
+            .expect("valid p")
+            .with_observer(|depth, _, _| depths.push(depth));
+        state.push('a', Some('b')).expect("no duplicates");
+        state.push('b', None).expect("no duplicates");
+        drop(state);
+        assert_eq!(depths, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_rbo_with_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+        let hasher = BuildHasherDefault::<DefaultHasher>::default();
+        let first: Vec<char> = "abcdefghij".chars().collect();
+        let second: Vec<char> = "zyxabcjihg".chars().collect();
+        let expected = super::rbo(&first, &second, 0.9).expect("valid rbo");
+        let rbo = super::rbo_with_hasher(&first, &second, 0.9, hasher.clone()).expect("valid rbo");
+        assert_eq!(rbo, expected);
+        let duplicated = ['a', 'b', 'a'];
+        match super::rbo_with_hasher(&first, &duplicated, 0.9, hasher) {
+            Err(super::RboError::DuplicatesInList(duplicate)) => {
+                assert_eq!(duplicate.indices, vec![0, 2]);
+            }
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_rbo_float() {
+        let cases = [
+            ("abcdefg", "abdcgef"),
+            ("abcdefghij", "zyxabc"),
+            ("abc", "abc"),
+        ];
+        for (first, second) in cases {
+            let first: Vec<char> = first.chars().collect();
+            let second: Vec<char> = second.chars().collect();
+            let expected = super::rbo(&first, &second, 0.9).expect("valid rbo");
+            let double = super::rbo_float(&first, &second, 0.9_f64).expect("valid rbo");
+            let single = super::rbo_float(&first, &second, 0.9_f32).expect("valid rbo");
+            for (value, reference) in [
+                (double.min, expected.min),
+                (double.residual, expected.residual),
+                (double.extrapolated, expected.extrapolated),
+            ] {
+                approx::assert_abs_diff_eq!(value, reference, epsilon = 1e-12);
+            }
+            for (value, reference) in [
+                (single.min, expected.min),
+                (single.residual, expected.residual),
+                (single.extrapolated, expected.extrapolated),
+            ] {
+                approx::assert_abs_diff_eq!(f64::from(value), reference, epsilon = 1e-5);
+            }
+        }
+        assert!(super::rbo_float(&['a'], &['b'], 1.0_f32).is_err());
+        assert!(super::rbo_float(&['a'], &['b'], f32::NAN).is_err());
+    }
+
+    #[test]
+    fn test_compensated_sum() {
+        let terms = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(terms.iter().sum::<f64>(), 0.0);
+        assert_eq!(super::state::sum(terms), 2.0);
+    }
+
+    #[test]
+    fn test_power_recurrence() {
+        let p = 0.99999_f64;
+        for (d, p_d) in super::state::powers(p).enumerate().take(200_000) {
+            let exact = p.powf((d + 1) as f64);
+            approx::assert_relative_eq!(p_d, exact, max_relative = 1e-13);
+        }
+    }
+
+    #[test]
+    fn test_chunked_residual() {
+        let p = 0.99999_f64;
+        for (s, l, x_l) in [
+            (70_000, 90_000, 10_000),
+            (100, 200_000, 50),
+            (150_000, 150_000, 0),
+        ] {
+            for (d, p_d) in super::state::powers(p).enumerate().take(s + l - x_l) {
+                assert_eq!(super::state::power_at(p, d + 1), p_d);
+            }
+            // a single compensated pass over all depths
+            let f = s + l - x_l;
+            let term = |d: usize| p.powf(d as f64) / d as f64;
+            let sum_s = super::state::sum((s + 1..=f).map(term));
+            let sum_l = super::state::sum((l + 1..=f).map(term));
+            let sum_t = super::state::sum((1..=f).map(term));
+            let (sf, lf, xf) = (s as f64, l as f64, x_l as f64);
+            let expected = p.powf(sf) + p.powf(lf)
+                - p.powf(f as f64)
+                - (1.0 - p) / p * (sf * sum_s + lf * sum_l + xf * ((1.0 / (1.0 - p)).ln() - sum_t));
+            let residual = super::state::residual(sf, lf, xf, p);
+            approx::assert_abs_diff_eq!(residual, expected, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_truncated_residual() {
+        // barely overlapping lists, the series stops long before f
+        let p = 0.9_f64;
+        for (s, l, x_l) in [
+            (200_000, 300_000, 3),
+            (100_000, 100_000, 0),
+            (5, 400_000, 2),
+        ] {
+            let f = s + l - x_l;
+            let term = |d: usize| p.powf(d as f64) / d as f64;
+            let sum_s = super::state::sum((s + 1..=f).map(term));
+            let sum_l = super::state::sum((l + 1..=f).map(term));
+            let sum_t = super::state::sum((1..=f).map(term));
+            let (sf, lf, xf) = (s as f64, l as f64, x_l as f64);
+            let expected = p.powf(sf) + p.powf(lf)
+                - p.powf(f as f64)
+                - (1.0 - p) / p * (sf * sum_s + lf * sum_l + xf * ((1.0 / (1.0 - p)).ln() - sum_t));
+            let residual = super::state::residual(sf, lf, xf, p);
+            approx::assert_abs_diff_eq!(residual, expected, epsilon = 1e-15);
+        }
+        // a full walk over billions of depths would not finish
+        let residual = super::state::residual(1e12, 1e12, 0.0, 0.9_f64);
+        approx::assert_abs_diff_eq!(residual, 0.0, epsilon = 1e-15);
+        let residual = super::state::residual(3.0, 1e12, 0.0, 0.5_f64);
+        let shorter = super::state::residual(3.0, 1e4, 0.0, 0.5_f64);
+        approx::assert_abs_diff_eq!(residual, shorter, epsilon = 1e-15);
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug)]
+    struct TestCase {
+        other: String,
+        p: f64,
+        rbo: f64,
+    }
+
+    #[test]
+    fn ext_permute_comparison_to_william_webber() {
+        let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("test_data/permute_abc.json");
+        let test_data_file = std::fs::File::open(d).expect("open test data file");
+        let test_cases: Vec<TestCase> =
+            serde_json::from_reader(&test_data_file).expect("read test data");
+
+        for t in test_cases {
+            let second = t.other.chars().collect::<Vec<_>>();
+            let mut first = second.clone();
+            first.sort_unstable();
+            let computed_rbo = super::rbo(&first, &second, t.p).expect("valid rbo");
+            approx::assert_abs_diff_eq!(computed_rbo.extrapolated, t.rbo, epsilon = 0.001);
+        }
+    }
+
+    #[test]
+    fn ext_uneven_comparison_to_william_webber() {
+        let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("test_data/uneven_abc.json");
+        let test_data_file = std::fs::File::open(d).expect("open test data file");
+        let test_cases: Vec<TestCase> =
+            serde_json::from_reader(&test_data_file).expect("read test data");
+        let first = "abcdefghijklmnopqrstuvwxyz".chars().collect::<Vec<_>>();
+        for t in test_cases {
+            let second = t.other.chars().collect::<Vec<_>>();
+            let computed_rbo = super::rbo(&first, &second, t.p).expect("valid rbo");
+            approx::assert_abs_diff_eq!(computed_rbo.extrapolated, t.rbo, epsilon = 0.001);
+        }
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug)]
+    struct TestCaseSp {
+        other: String,
+        p: f64,
+        rbo_ext: f64,
+        rbo_min: f64,
+        rbo_res: f64,
+    }
+
+    #[test]
+    fn uneven_comparison_to_sp() {
+        let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("test_data/uneven_abc_sp.json");
+        let test_data_file = std::fs::File::open(d).expect("open test data file");
+        let test_cases: Vec<TestCaseSp> =
             serde_json::from_reader(&test_data_file).expect("read test data");
         let first = "abcdefghijklmnopqrstuvwxyz".chars().collect::<Vec<_>>();
         for t in test_cases {
@@ -221,4 +1616,1053 @@ mod tests {
             approx::assert_abs_diff_eq!(computed_rbo.residual, t.rbo_res, epsilon = 0.001);
         }
     }
+
+    #[test]
+    fn test_ties() {
+        use super::TieVariant;
+        let untied: Vec<Vec<char>> = "abcdef".chars().map(|c| vec![c]).collect();
+        let reversed: Vec<Vec<char>> = "fedcba".chars().map(|c| vec![c]).collect();
+        let plain = super::rbo(
+            &"abcdef".chars().collect::<Vec<_>>(),
+            &"fedcba".chars().collect::<Vec<_>>(),
+            0.9,
+        )
+        .expect("valid rbo");
+        for variant in [TieVariant::W, TieVariant::A, TieVariant::B] {
+            let tied = super::rbo_ties(&untied, &reversed, 0.9, variant).expect("valid rbo");
+            approx::assert_abs_diff_eq!(tied.min, plain.min, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(tied.residual, plain.residual, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(tied.extrapolated, plain.extrapolated, epsilon = 1e-12);
+        }
+
+        // variant a averages over the two ways to break the tie between b and c
+        let tied = vec![vec!['a'], vec!['b', 'c'], vec!['d']];
+        let other = vec![vec!['c'], vec!['a'], vec!['d'], vec!['b']];
+        let other_flat: Vec<char> = "cadb".chars().collect();
+        let a = super::rbo_ties(&tied, &other, 0.9, TieVariant::A).expect("valid rbo");
+        let bc =
+            super::rbo(&"abcd".chars().collect::<Vec<_>>(), &other_flat, 0.9).expect("valid rbo");
+        let cb =
+            super::rbo(&"acbd".chars().collect::<Vec<_>>(), &other_flat, 0.9).expect("valid rbo");
+        approx::assert_abs_diff_eq!(a.min, (bc.min + cb.min) / 2.0, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(
+            a.extrapolated,
+            (bc.extrapolated + cb.extrapolated) / 2.0,
+            epsilon = 1e-12
+        );
+
+        assert!(super::rbo_ties(&[vec!['a', 'a']], &other, 0.9, TieVariant::W).is_err());
+    }
+
+    #[test]
+    fn test_vs_set() {
+        use super::TieVariant;
+        use std::collections::HashSet;
+        let ranking: Vec<char> = "xbay".chars().collect();
+        let set: HashSet<char> = "abc".chars().collect();
+        let groups: Vec<Vec<char>> = ranking.iter().map(|&c| vec![c]).collect();
+        for variant in [TieVariant::W, TieVariant::A, TieVariant::B] {
+            let vs_set = super::rbo_vs_set(&ranking, &set, 0.9, variant).expect("valid rbo");
+            let tied =
+                super::rbo_ties(&groups, &[vec!['c', 'b', 'a']], 0.9, variant).expect("valid rbo");
+            approx::assert_abs_diff_eq!(vs_set.min, tied.min, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(vs_set.residual, tied.residual, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(vs_set.extrapolated, tied.extrapolated, epsilon = 1e-12);
+        }
+
+        // variant a averages over both orders of the set
+        let ranking = ['b', 'x'];
+        let set: HashSet<char> = "ab".chars().collect();
+        let a = super::rbo_vs_set(&ranking, &set, 0.9, TieVariant::A).expect("valid rbo");
+        let ab = super::rbo(&ranking, &['a', 'b'], 0.9).expect("valid rbo");
+        let ba = super::rbo(&ranking, &['b', 'a'], 0.9).expect("valid rbo");
+        approx::assert_abs_diff_eq!(a.min, (ab.min + ba.min) / 2.0, epsilon = 1e-12);
+
+        let empty = super::rbo_vs_set(&ranking, &HashSet::new(), 0.9, TieVariant::W);
+        assert_eq!(empty.expect("valid rbo").min, 0.0);
+        assert!(super::rbo_vs_set(&['a', 'a'], &set, 0.9, TieVariant::W).is_err());
+    }
+
+    #[test]
+    fn test_rbp() {
+        use std::collections::HashSet;
+        let relevant: HashSet<char> = "ace".chars().collect();
+        let ranking: Vec<char> = "abcd".chars().collect();
+        let rbp = super::rbp(&ranking, &relevant, 0.8).expect("valid rbp");
+        approx::assert_abs_diff_eq!(rbp.base, 0.2 * (1.0 + 0.64), epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(rbp.residual, 0.8f64.powi(4), epsilon = 1e-12);
+
+        // a ranking of relevant items only reaches its upper bound
+        let all: Vec<u32> = (0..200).collect();
+        let relevant: HashSet<u32> = all.iter().copied().collect();
+        for p in [0.0, 0.5, 0.9, 0.99] {
+            let rbp = super::rbp(&all, &relevant, p).expect("valid rbp");
+            approx::assert_abs_diff_eq!(rbp.upper_bound(), 1.0, epsilon = 1e-12);
+        }
+
+        let empty = super::rbp::<char, _>(&[], &HashSet::new(), 0.9).expect("valid rbp");
+        assert_eq!((empty.base, empty.residual), (0.0, 1.0));
+        assert!(super::rbp(&['a', 'a'], &HashSet::new(), 0.9).is_err());
+        assert!(super::rbp(&ranking, &HashSet::new(), 1.0).is_err());
+    }
+
+    #[test]
+    fn test_segments() {
+        let first: Vec<u32> = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let second: Vec<u32> = vec![2, 1, 9, 3, 10, 5];
+        // a single segment over all the weight is plain rbo
+        for (a, b) in [(&first, &second), (&second, &first)] {
+            let plain = super::rbo(a, b, 0.9).expect("valid rbo");
+            let segments = super::rbo_segments(a, b, 0.9, &[1..=2000]).expect("valid rbo");
+            approx::assert_abs_diff_eq!(segments[0].min, plain.min, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(segments[0].residual, plain.residual, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(
+                segments[0].extrapolated,
+                plain.extrapolated,
+                epsilon = 1e-12
+            );
+        }
+
+        // only the weights within a segment count, renormalized
+        let segments = super::rbo_segments(&first, &second, 0.5, &[1..=2, 2..=3, 7..=7, 9..=10])
+            .expect("valid rbo");
+        approx::assert_abs_diff_eq!(segments[0].min, (0.0 + 0.5 * 1.0) / 1.5, epsilon = 1e-12);
+        assert_eq!(segments[0].residual, 0.0);
+        approx::assert_abs_diff_eq!(
+            segments[1].min,
+            (1.0 + 0.5 * 2.0 / 3.0) / 1.5,
+            epsilon = 1e-12
+        );
+        // at depth 7 the overlap is 4 of which at most one more is unseen
+        approx::assert_abs_diff_eq!(segments[2].min, 4.0 / 7.0, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(segments[2].residual, 1.0 / 7.0, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(
+            segments[3].upper_bound(),
+            (8.0 / 9.0 + 0.5 * 1.0) / 1.5,
+            epsilon = 1e-12
+        );
+
+        let same = super::rbo_segments(&first, &first, 0.9, &[1..=3, 4..=8]).expect("valid rbo");
+        assert!(same.iter().all(|rbo| rbo.min == 1.0 && rbo.residual == 0.0));
+        assert!(super::rbo_segments(&first, &second, 0.9, &[])
+            .expect("valid rbo")
+            .is_empty());
+        for invalid in [0..=3, std::ops::RangeInclusive::new(5, 4)] {
+            assert!(matches!(
+                super::rbo_segments(&first, &second, 0.9, &[invalid]),
+                Err(super::RboError::InvalidTarget)
+            ));
+        }
+        assert!(super::rbo_segments(&[1, 1], &second, 0.9, &[1..=2]).is_err());
+    }
+
+    #[test]
+    fn test_weighted() {
+        let first: Vec<char> = "abcdef".chars().collect();
+        let second: Vec<char> = "badcfe".chars().collect();
+        let plain = super::rbo(&first, &second, 0.9).expect("valid rbo");
+        let weigh = |list: &[char], w: fn(char) -> f64| {
+            list.iter().map(|c| (*c, w(*c))).collect::<Vec<_>>()
+        };
+        let unit = super::rbo_weighted(&weigh(&first, |_| 2.0), &weigh(&second, |_| 2.0), 0.9)
+            .expect("valid rbo");
+        approx::assert_abs_diff_eq!(unit.min, plain.min, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(unit.extrapolated, plain.extrapolated, epsilon = 1e-12);
+
+        // making the items which are late in the second ranking unimportant raises the agreement
+        let pinned = |c: char| if c == 'a' || c == 'b' { 1.0 } else { 0.1 };
+        let weighted = super::rbo_weighted(&weigh(&first, pinned), &weigh(&second, pinned), 0.9)
+            .expect("valid rbo");
+        assert!(weighted.min > plain.min);
+        assert!(super::rbo_weighted(&[('a', -1.0)], &[('a', 1.0)], 0.9).is_err());
+    }
+
+    #[test]
+    fn test_at_depth() {
+        let first: Vec<char> = "abcdefa".chars().collect();
+        let second: Vec<char> = "abcdxyz".chars().collect();
+        let truncated = super::rbo_at_depth(&first, &second, 0.9, 4).expect("valid rbo");
+        approx::assert_abs_diff_eq!(truncated.min + truncated.residual, 1.0, epsilon = 1e-6);
+        assert!(super::rbo(&first, &second, 0.9).is_err());
+    }
+
+    #[test]
+    fn test_average_overlap() {
+        let first: Vec<char> = "abcd".chars().collect();
+        let second: Vec<char> = "bacdxyz".chars().collect();
+        let ao = super::average_overlap(&first, &second).expect("valid lists");
+        approx::assert_abs_diff_eq!(ao, (0.0 + 1.0 + 1.0 + 1.0) / 4.0);
+        // the rbo weighted agreement over the top 4 approaches average overlap as p goes to 1
+        let gains = super::rbo_weight_gain(&first, &second[..4], 0.999_999).expect("valid rbo");
+        let gained: f64 = gains.iter().map(|g| g.gained).sum();
+        let weight: f64 = gains.iter().map(|g| g.weight).sum();
+        approx::assert_abs_diff_eq!(gained / weight, ao, epsilon = 1e-4);
+        assert!(super::average_overlap(&first, &[]).is_err());
+    }
+
+    #[test]
+    fn test_overlap_curve() {
+        let first: Vec<char> = "abcd".chars().collect();
+        let second: Vec<char> = "bax".chars().collect();
+        let curve = super::overlap_curve(&first, &second).expect("valid lists");
+        assert_eq!(curve.len(), 4);
+        assert_eq!(curve[0], (1, 0.0, 0.0));
+        assert_eq!(curve[1], (2, 1.0, 1.0));
+        approx::assert_abs_diff_eq!(curve[2].2, 2.0 / 4.0);
+        // {a, b, c, d} against {b, a, x}
+        approx::assert_abs_diff_eq!(curve[3].1, 2.0 / 4.0);
+        approx::assert_abs_diff_eq!(curve[3].2, 2.0 / 5.0);
+    }
+
+    #[test]
+    fn test_multi() {
+        let a: Vec<char> = "abcdef".chars().collect();
+        let b: Vec<char> = "abdcfe".chars().collect();
+        let c: Vec<char> = "fedcba".chars().collect();
+        let multi = super::rbo_multi(&[&a, &b, &c], 0.9).expect("valid rbo");
+        let pairs = [(&a, &b), (&a, &c), (&b, &c)]
+            .map(|(x, y)| super::rbo(x, y, 0.9).expect("valid rbo").extrapolated);
+        approx::assert_abs_diff_eq!(
+            multi.extrapolated,
+            pairs.iter().sum::<f64>() / 3.0,
+            epsilon = 1e-12
+        );
+        assert!(super::rbo_multi(&[&a], 0.9).is_err());
+    }
+
+    #[test]
+    fn test_conjoint_domain() {
+        use super::{Domain, RboOptions};
+        let first: Vec<char> = "abcdef".chars().collect();
+        let second: Vec<char> = "badcfe".chars().collect();
+        let plain = super::rbo(&first, &second, 0.9).expect("valid rbo");
+        let conjoint = |size| RboOptions {
+            domain: Domain::Conjoint { size },
+            ..RboOptions::default()
+        };
+        // a huge domain gives the indefinite bounds back
+        let huge =
+            super::rbo_with_options(&first, &second, 0.9, conjoint(1 << 20)).expect("valid rbo");
+        approx::assert_abs_diff_eq!(huge.min, plain.min, epsilon = 1e-9);
+        approx::assert_abs_diff_eq!(huge.residual, plain.residual, epsilon = 1e-9);
+        // a small domain narrows them
+        let small = super::rbo_with_options(&first[..4], &second[..4], 0.9, conjoint(8))
+            .expect("valid rbo");
+        let prefix = super::rbo(&first[..4], &second[..4], 0.9).expect("valid rbo");
+        assert!(small.min > prefix.min);
+        approx::assert_abs_diff_eq!(
+            small.min + small.residual,
+            prefix.min + prefix.residual,
+            epsilon = 1e-9
+        );
+        // fully observed permutations leave no residual
+        let full = super::rbo_with_options(&first, &second, 0.9, conjoint(6)).expect("valid rbo");
+        approx::assert_abs_diff_eq!(full.residual, 0.0, epsilon = 1e-12);
+        assert!(super::rbo_with_options(&first, &second, 0.9, conjoint(5)).is_err());
+    }
+
+    #[test]
+    fn test_exact() {
+        let first: Vec<char> = "abcdef".chars().collect();
+        let second: Vec<char> = "badcfe".chars().collect();
+        let exact = super::rbo_exact(&first, &second, 0.9).expect("valid rbo");
+        // agreement is 0 at odd depths and 1 at even depths, then 1 beyond depth 6
+        let expected: f64 = (1..=6)
+            .filter(|d| d % 2 == 0)
+            .map(|d| 0.1 * 0.9f64.powi(d - 1))
+            .sum::<f64>()
+            + (1..=6)
+                .filter(|d| d % 2 == 1)
+                .map(|d| 0.1 * 0.9f64.powi(d - 1) * (d - 1) as f64 / d as f64)
+                .sum::<f64>()
+            + 0.9f64.powi(6);
+        approx::assert_abs_diff_eq!(exact, expected, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(
+            super::rbo_exact(&first, &first, 0.9).expect("valid rbo"),
+            1.0,
+            epsilon = 1e-12
+        );
+        assert!(super::rbo_exact(&first, &second[..5], 0.9).is_err());
+        assert!(super::rbo_exact(&first, &"abcdeg".chars().collect::<Vec<_>>(), 0.9).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        use super::{Duplicates, RboOptions};
+        let first: Vec<char> = "abacb".chars().collect();
+        let second: Vec<char> = "abcab".chars().collect();
+        let with = |duplicates| RboOptions {
+            duplicates,
+            ..RboOptions::default()
+        };
+        assert!(super::rbo_with_options(&first, &second, 0.9, with(Duplicates::Error)).is_err());
+        let dedup = super::rbo_with_options(&first, &second, 0.9, with(Duplicates::DedupKeepFirst))
+            .expect("valid rbo");
+        assert_eq!(
+            dedup,
+            super::rbo(&['a', 'b', 'c'], &['a', 'b', 'c'], 0.9).expect("valid rbo")
+        );
+        let multiset = super::rbo_with_options(&first, &second, 0.9, with(Duplicates::Multiset))
+            .expect("valid rbo");
+        let tagged = super::rbo(
+            &["a0", "b0", "a1", "c0", "b1"],
+            &["a0", "b0", "c0", "a1", "b1"],
+            0.9,
+        )
+        .expect("valid rbo");
+        assert_eq!(multiset, tagged);
+    }
+
+    #[test]
+    fn test_rbo_by() {
+        let first = [0.1, 0.2, 0.3, 0.4];
+        let second = [0.2000001, 0.1, 0.5, 0.3];
+        let close = |a: &f64, b: &f64| (a - b).abs() < 1e-3;
+        let by = super::rbo_by(&first, &second, 0.9, close).expect("valid rbo");
+        let keyed = super::rbo(&[1, 2, 3, 4], &[2, 1, 5, 3], 0.9).expect("valid rbo");
+        assert_eq!(by, keyed);
+        assert!(super::rbo_by(&[0.1, 0.1000001], &second, 0.9, close).is_err());
+    }
+
+    #[test]
+    fn test_rbo_borrowed() {
+        use std::borrow::Cow;
+        let first: Vec<String> = "abcdefg".chars().map(String::from).collect();
+        let second: Vec<Cow<'_, str>> = ["c", "a", "x", "g"].map(Cow::Borrowed).to_vec();
+        let borrowed = super::rbo_borrowed::<_, _, str>(&first, &second, 0.9).expect("valid rbo");
+        let owned: Vec<String> = second.iter().map(|item| item.to_string()).collect();
+        assert_eq!(
+            borrowed,
+            super::rbo(&first, &owned, 0.9).expect("valid rbo")
+        );
+
+        let duplicates = ["a", "b", "a"];
+        match super::rbo_borrowed::<_, _, str>(&first, &duplicates, 0.9) {
+            Err(super::RboError::DuplicatesInList(duplicate)) => {
+                assert_eq!(duplicate.list, 1);
+                assert_eq!(duplicate.indices, vec![0, 2]);
+            }
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_rbo_ord() {
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let second: Vec<char> = "xbyzaqhc".chars().collect();
+        for p in [0.5, 0.9, 0.99] {
+            assert_eq!(
+                super::rbo_ord(&first, &second, p).expect("valid rbo"),
+                super::rbo(&first, &second, p).expect("valid rbo")
+            );
+        }
+        // the same duplicate as reported by `rbo`
+        for (a, b) in [("abcbca", "xy"), ("ab", "xyzzyx"), ("abca", "xyzx")] {
+            let a: Vec<char> = a.chars().collect();
+            let b: Vec<char> = b.chars().collect();
+            match (super::rbo_ord(&a, &b, 0.9), super::rbo(&a, &b, 0.9)) {
+                (
+                    Err(super::RboError::DuplicatesInList(ord)),
+                    Err(super::RboError::DuplicatesInList(hashed)),
+                ) => {
+                    assert_eq!(ord.list, hashed.list);
+                    assert_eq!(ord.indices, hashed.indices);
+                }
+                other => panic!("expected duplicates, got {other:?}"),
+            }
+        }
+        assert!(super::rbo_ord(&first, &second, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_drift() {
+        use super::DriftBaseline;
+        let snapshots: Vec<Vec<char>> = ["abcdef", "abdcef", "badcfe", "xyzabc"]
+            .iter()
+            .map(|s| s.chars().collect())
+            .collect();
+        let previous =
+            super::rbo_drift(&snapshots, 0.9, DriftBaseline::Previous).expect("valid rbo");
+        let first = super::rbo_drift(&snapshots, 0.9, DriftBaseline::First).expect("valid rbo");
+        assert_eq!(previous.len(), 3);
+        for i in 1..snapshots.len() {
+            let rbo = |a: usize| super::rbo(&snapshots[a], &snapshots[i], 0.9).expect("valid rbo");
+            assert_eq!(previous[i - 1], rbo(i - 1));
+            assert_eq!(first[i - 1], rbo(0));
+        }
+        let single = super::rbo_drift(&snapshots[..1], 0.9, DriftBaseline::First);
+        assert!(single.expect("valid rbo").is_empty());
+
+        let duplicates = vec![vec![1, 2], vec![2, 1], vec![3, 3]];
+        match super::rbo_drift(&duplicates, 0.9, DriftBaseline::Previous) {
+            Err(super::RboError::DuplicatesInList(duplicate)) => assert_eq!(duplicate.list, 2),
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_reference_ranking() {
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let reference = super::ReferenceRanking::new(first.clone()).expect("no duplicates");
+        assert_eq!(reference.len(), 8);
+        for candidate in ["badcfehg", "xbyzaqhc", "hgf", "abcdefghijklmn", "xyz"] {
+            let candidate: Vec<char> = candidate.chars().collect();
+            assert_eq!(
+                reference.rbo_against(&candidate, 0.9).expect("valid rbo"),
+                super::rbo(&first, &candidate, 0.9).expect("valid rbo")
+            );
+        }
+        assert!(reference.rbo_against(&['a'], 1.0).is_err());
+        match reference.rbo_against(&['x', 'c', 'y', 'c'], 0.9) {
+            Err(super::RboError::DuplicatesInList(duplicate)) => {
+                assert_eq!(duplicate.list, 1);
+                assert_eq!(duplicate.indices, vec![1, 3]);
+            }
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+        assert!(super::ReferenceRanking::new(vec![1, 2, 1]).is_err());
+    }
+
+    #[test]
+    fn test_rbo_ranked() {
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let second: Vec<char> = "xbyzaqhc".chars().collect();
+        let expected = super::rbo(&first, &second, 0.9).expect("valid rbo");
+        assert_eq!(
+            super::rbo_ranked(&first, second.as_slice(), 0.9).expect("valid rbo"),
+            expected
+        );
+        assert_eq!(
+            super::rbo_ranked(first.clone(), second.clone(), 0.9).expect("valid rbo"),
+            expected
+        );
+        assert!(super::rbo_ranked(vec![1, 2, 1], vec![3], 0.9).is_err());
+        assert!(super::rbo_ranked(&first, &second, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_rbo_ids() {
+        use rand::seq::SliceRandom;
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let mut ids: Vec<u32> = (0..500).collect();
+        for (n, m) in [(100, 100), (50, 120), (200, 3), (1, 10)] {
+            ids.shuffle(&mut rng);
+            let first = ids[..n].to_vec();
+            ids[..250].shuffle(&mut rng);
+            let second = ids[..m].to_vec();
+            assert_eq!(
+                super::rbo_ids(&first, &second, 0.9).expect("valid rbo"),
+                super::rbo(&first, &second, 0.9).expect("valid rbo")
+            );
+        }
+        // sparse ids take the hashed path
+        let sparse = [u32::MAX, 7, 1 << 30];
+        assert_eq!(
+            super::rbo_ids(&sparse, &[7, u32::MAX], 0.9).expect("valid rbo"),
+            super::rbo(&sparse, &[7, u32::MAX], 0.9).expect("valid rbo")
+        );
+        // the same duplicate as reported by `rbo`
+        for (first, second) in [
+            (vec![1, 2, 3, 2, 2], vec![4]),
+            (vec![1, 2], vec![5, 5, 6]),
+            (vec![1, 2, 3, 4, 1], vec![5, 5]),
+        ] {
+            match (
+                super::rbo_ids(&first, &second, 0.9),
+                super::rbo(&first, &second, 0.9),
+            ) {
+                (
+                    Err(super::RboError::DuplicatesInList(ids)),
+                    Err(super::RboError::DuplicatesInList(hashed)),
+                ) => {
+                    assert_eq!(ids.list, hashed.list);
+                    assert_eq!(ids.indices, hashed.indices);
+                }
+                other => panic!("expected duplicates, got {other:?}"),
+            }
+        }
+        assert!(super::rbo_ids(&[1], &[2], 1.0).is_err());
+    }
+
+    #[test]
+    fn test_engine() {
+        let mut engine = super::RboEngine::new();
+        let lists: Vec<Vec<u32>> = (0..50u32)
+            .map(|i| (0..(i % 7 + 1)).map(|j| (i * 3 + j * 5) % 11).collect())
+            .collect();
+        for pair in lists.windows(2) {
+            for p in [0.5, 0.9] {
+                assert_eq!(
+                    engine.compute(&pair[0], &pair[1], p).expect("valid rbo"),
+                    super::rbo(&pair[0], &pair[1], p).expect("valid rbo")
+                );
+            }
+        }
+        // errors leave the engine usable
+        match engine.compute(&[1, 2], &[3, 4, 3], 0.9) {
+            Err(super::RboError::DuplicatesInList(duplicate)) => {
+                assert_eq!(duplicate.list, 1);
+                assert_eq!(duplicate.indices, vec![0, 2]);
+            }
+            other => panic!("expected duplicates, got {other:?}"),
+        }
+        assert!(engine.compute(&[1], &[1], 1.0).is_err());
+        assert_eq!(
+            engine.compute(&[1, 2, 3], &[3, 2], 0.9).expect("valid rbo"),
+            super::rbo(&[1, 2, 3], &[3, 2], 0.9).expect("valid rbo")
+        );
+    }
+
+    #[test]
+    fn test_persistence_table() {
+        let lists: Vec<Vec<u32>> = (1..30u32)
+            .map(|i| (0..(i * 7 % 40)).map(|j| (i + j * 3) % 50).collect())
+            .collect();
+        for p in [0.5, 0.9, 0.999] {
+            // tables covering all lists, and too short for most of them
+            for max_depth in [100, 5] {
+                let table = super::PersistenceTable::new(p, max_depth).expect("valid persistence");
+                assert_eq!(table.persistence(), p);
+                for pair in lists.windows(2) {
+                    let rbo = table.rbo(&pair[0], &pair[1]).expect("valid rbo");
+                    let expected = super::rbo(&pair[0], &pair[1], p).expect("valid rbo");
+                    assert_eq!(rbo.min.to_bits(), expected.min.to_bits());
+                    assert_eq!(rbo.extrapolated.to_bits(), expected.extrapolated.to_bits());
+                    approx::assert_abs_diff_eq!(rbo.residual, expected.residual, epsilon = 1e-12);
+                }
+            }
+        }
+        assert!(super::PersistenceTable::new(1.0, 10).is_err());
+        let table = super::PersistenceTable::new(0.9, 10).expect("valid persistence");
+        assert!(table.rbo(&[1, 2, 1], &[3]).is_err());
+    }
+
+    #[test]
+    fn test_scored() {
+        use super::{ScoreTies, TieVariant};
+        let first = [('c', 0.2), ('a', 0.9), ('b', 0.5)];
+        let second = [('a', 3.0), ('c', 2.0), ('b', 1.0)];
+        let scored = super::rbo_scored(&first, &second, 0.9, ScoreTies::Error).expect("valid rbo");
+        assert_eq!(
+            scored,
+            super::rbo(&['a', 'b', 'c'], &['a', 'c', 'b'], 0.9).expect("valid rbo")
+        );
+
+        let tied = [('a', 1.0), ('b', 0.5), ('c', 0.5)];
+        assert!(super::rbo_scored(&tied, &second, 0.9, ScoreTies::Error).is_err());
+        let variant = ScoreTies::Variant(TieVariant::A);
+        let with_ties = super::rbo_scored(&tied, &second, 0.9, variant).expect("valid rbo");
+        let groups = super::rbo_ties(
+            &[vec!['a'], vec!['b', 'c']],
+            &[vec!['a'], vec!['c'], vec!['b']],
+            0.9,
+            TieVariant::A,
+        )
+        .expect("valid rbo");
+        assert_eq!(with_ties, groups);
+        assert!(super::rbo_scored(&[('a', f64::NAN)], &second, 0.9, ScoreTies::Error).is_err());
+    }
+
+    #[test]
+    fn test_rank_maps() {
+        use super::RankMap;
+        use std::collections::{BTreeMap, HashMap};
+        let zero_based: HashMap<char, usize> = [('b', 1), ('a', 0), ('c', 2)].into_iter().collect();
+        assert_eq!(
+            zero_based.ranking().expect("valid ranks"),
+            vec![&'a', &'b', &'c']
+        );
+        let gap: HashMap<char, usize> = [('a', 1), ('b', 3)].into_iter().collect();
+        assert!(gap.ranking().is_err());
+        let repeated: HashMap<char, usize> = [('a', 1), ('b', 1)].into_iter().collect();
+        assert!(repeated.ranking().is_err());
+        let btree_gap: BTreeMap<usize, char> = [(1, 'a'), (3, 'b')].into_iter().collect();
+        assert!(super::rbo_from_ranks(&zero_based, &btree_gap, 0.9).is_err());
+    }
+
+    #[test]
+    fn test_persistence_calibration() {
+        // summing the weight every rank receives from all depths gives the same values
+        approx::assert_abs_diff_eq!(
+            super::top_weight(0.9, 10).expect("valid p"),
+            0.855_585,
+            epsilon = 1e-6
+        );
+        approx::assert_abs_diff_eq!(
+            super::top_weight(0.98, 50).expect("valid p"),
+            0.852_234,
+            epsilon = 1e-6
+        );
+        let p = super::persistence_for_weight(10, 0.8556).expect("reachable weight");
+        approx::assert_abs_diff_eq!(p, 0.9, epsilon = 1e-4);
+        approx::assert_abs_diff_eq!(
+            super::persistence_for_weight(1, 1.0).expect("reachable weight"),
+            0.0
+        );
+        assert!(super::persistence_for_weight(0, 0.5).is_err());
+        assert!(super::persistence_for_weight(10, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_fuzzy() {
+        use rand::seq::SliceRandom;
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let exact = |a: &u32, b: &u32| if a == b { 1.0 } else { 0.0 };
+        for (n, m) in [(0, 0), (0, 5), (8, 8), (20, 12), (7, 30)] {
+            let mut first: Vec<u32> = (0..40).collect();
+            let mut second = first.clone();
+            first.shuffle(&mut rng);
+            second.shuffle(&mut rng);
+            let (first, second) = (&first[..n], &second[..m]);
+            let fuzzy = super::rbo_fuzzy(first, second, 0.9, 1.0, exact).expect("valid rbo");
+            let plain = super::rbo(first, second, 0.9).expect("valid rbo");
+            assert_eq!(format!("{fuzzy:?}"), format!("{plain:?}"));
+        }
+
+        // items within 1 of each other are the same, but every item matches once
+        let near = |a: &i32, b: &i32| 1.0 - f64::from((a - b).abs());
+        let first = [10, 20, 30];
+        let second = [21, 11, 31];
+        let fuzzy = super::rbo_fuzzy(&first, &second, 0.9, 0.0, near).expect("valid rbo");
+        let same = super::rbo(&[1, 2, 3], &[2, 1, 3], 0.9).expect("valid rbo");
+        assert_eq!(fuzzy, same);
+        // 5 is as close to 4 as to 6, the higher ranked 4 wins and 6 is left over
+        let fuzzy = super::rbo_fuzzy(&[4, 6], &[100, 5], 0.9, 0.0, near).expect("valid rbo");
+        let same = super::rbo(&[1, 2], &[3, 1], 0.9).expect("valid rbo");
+        assert_eq!(fuzzy, same);
+        // the most similar pair is matched first
+        let fuzzy = super::rbo_fuzzy(&[7, 5], &[6, 50], 0.9, 0.0, |a: &i32, b: &i32| {
+            1.0 - f64::from((a - b).abs()) / 2.0
+        })
+        .expect("valid rbo");
+        assert_eq!(fuzzy, super::rbo(&[1, 2], &[1, 3], 0.9).expect("valid rbo"));
+
+        assert!(matches!(
+            super::rbo_fuzzy(&first, &second, 0.9, f64::NAN, near),
+            Err(super::RboError::InvalidTarget)
+        ));
+        assert!(matches!(
+            super::rbo_fuzzy(&first, &second, 1.0, 0.0, near),
+            Err(super::RboError::InvalidPersistance)
+        ));
+    }
+
+    #[test]
+    fn test_weights() {
+        for p in [0.0, 0.5, 0.9, 0.98] {
+            let mut sum = 0.0;
+            let mut previous = f64::INFINITY;
+            for depth in 1..=60 {
+                let weight = super::weight_at_depth(p, depth).expect("valid p");
+                assert!(weight <= previous);
+                previous = weight;
+                sum += weight;
+                approx::assert_abs_diff_eq!(
+                    sum,
+                    super::cumulative_weight(p, depth).expect("valid p"),
+                    epsilon = 1e-9
+                );
+            }
+        }
+        approx::assert_abs_diff_eq!(
+            super::weight_at_depth(0.9, 1).expect("valid p"),
+            0.1 / 0.9 * 10f64.ln(),
+            epsilon = 1e-12
+        );
+        approx::assert_abs_diff_eq!(super::weight_at_depth(0.0, 1).expect("valid p"), 1.0);
+        approx::assert_abs_diff_eq!(super::weight_at_depth(0.5, 0).expect("valid p"), 0.0);
+        approx::assert_abs_diff_eq!(super::cumulative_weight(0.9, 0).expect("valid p"), 0.0);
+        assert!(super::weight_at_depth(1.0, 3).is_err());
+        assert!(super::cumulative_weight(-0.1, 3).is_err());
+    }
+
+    #[test]
+    fn test_numerical_guards() {
+        let (first, second) = (vec![0, 2, 1, 3], vec![0, 1, 2, 3]);
+        assert!(matches!(
+            super::rbo(&first, &second, 1e-12),
+            Err(super::RboError::NumericalInstability)
+        ));
+        assert!(super::rbo(&first, &second, 1e-3).is_ok());
+        assert!(super::rbo(&first, &second, 1.0 - 1e-12).is_ok());
+
+        let near_one = 1.0 - 1e-12;
+        for result in [
+            super::PersistenceTable::new(near_one, 10).map(|_| 0.0),
+            super::weight_at_depth(near_one, 1),
+            super::cumulative_weight(0.9, usize::MAX),
+            super::depth_for_residual(near_one, 1e-6).map(|depth| depth as f64),
+        ] {
+            assert!(matches!(
+                result,
+                Err(super::RboError::DepthLimitExceeded(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_determinism() {
+        use rand::seq::SliceRandom;
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let mut domain: Vec<u32> = (0..300).collect();
+        let lists: Vec<Vec<u32>> = (0..60)
+            .map(|i| {
+                domain.shuffle(&mut rng);
+                domain[..10 + i * 2].to_vec()
+            })
+            .collect();
+        let pairs: Vec<(Vec<u32>, Vec<u32>)> = (0..1000)
+            .map(|i| (lists[i % 60].clone(), lists[(i * 7 + 3) % 60].clone()))
+            .collect();
+        let mut broken = pairs.clone();
+        for i in [900, 300, 999] {
+            let repeated = broken[i].1[0];
+            broken[i].1.push(repeated);
+        }
+        let bits = |rbo: &super::Rbo| [rbo.min, rbo.residual, rbo.extrapolated].map(f64::to_bits);
+        // everything computed in parallel, by bits, with the reported error
+        let run = || {
+            let batch = super::rbo_batch(&pairs, 0.9).expect("valid rbo");
+            let matrix = super::matrix::rbo_matrix_par(&lists, 0.9).expect("valid rbo");
+            let matrix: Vec<_> = (0..60)
+                .flat_map(|i| (i..60).map(move |j| (i, j)))
+                .map(|(i, j)| bits(&matrix.get(i, j)))
+                .collect();
+            let search = super::most_similar_par(&lists[0], &lists, 0.9, 10);
+            let search: Vec<_> = search
+                .expect("valid rbo")
+                .iter()
+                .map(|(i, rbo)| (*i, bits(rbo)))
+                .collect();
+            // long lists, whose residual series is summed in many chunks
+            let residual = super::state::residual(2e5, 3e5, 1e3, 0.999_99_f64).to_bits();
+            let error = format!("{:?}", super::rbo_batch(&broken, 0.9));
+            (
+                batch.iter().map(bits).collect::<Vec<_>>(),
+                matrix,
+                search,
+                residual,
+                error,
+            )
+        };
+        let expected = run();
+        assert!(expected.4.contains("list: 601"));
+        for threads in [1, 2, 3, 8] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("thread pool");
+            for _ in 0..3 {
+                assert_eq!(pool.install(run), expected);
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_thread_pool_variants() {
+        let lists: Vec<Vec<u32>> = (0..40)
+            .map(|i| (0..30).map(|j| (i * 3 + j * 7) % 45).collect())
+            .collect();
+        let pairs: Vec<(Vec<u32>, Vec<u32>)> = lists
+            .windows(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        for threads in [1, 3] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("thread pool");
+            assert_eq!(
+                super::rbo_in(&pool, &lists[0], &lists[1], 0.9).expect("valid rbo"),
+                super::rbo(&lists[0], &lists[1], 0.9).expect("valid rbo")
+            );
+            assert_eq!(
+                super::rbo_batch_in(&pool, &pairs, 0.9).expect("valid rbo"),
+                super::rbo_batch(&pairs, 0.9).expect("valid rbo")
+            );
+            assert_eq!(
+                super::matrix::rbo_matrix_par_in(&pool, &lists, 0.9).expect("valid rbo"),
+                super::matrix::rbo_matrix(&lists, 0.9).expect("valid rbo")
+            );
+            assert_eq!(
+                super::most_similar_par_in(&pool, &lists[5], &lists, 0.9, 4).expect("valid rbo"),
+                super::most_similar(&lists[5], &lists, 0.9, 4).expect("valid rbo")
+            );
+            assert!(super::rbo_batch_in(&pool, &pairs, 1.0).is_err());
+        }
+    }
+
+    #[test]
+    fn test_zero_persistence() {
+        // all the weight is on depth 1, whose agreement is the whole of RBO
+        let agree = super::Rbo {
+            min: 1.0,
+            residual: 0.0,
+            extrapolated: 1.0,
+        };
+        let disagree = super::Rbo {
+            min: 0.0,
+            residual: 0.0,
+            extrapolated: 0.0,
+        };
+        for (first, second, expected) in [
+            (vec![0, 2, 1, 3], vec![0, 1, 2, 3], agree),
+            (vec![0, 1], vec![0, 2, 3, 4], agree),
+            (vec![1, 0, 2], vec![0, 1], disagree),
+        ] {
+            assert_eq!(
+                super::rbo(&first, &second, 0.0).expect("valid rbo"),
+                expected
+            );
+            assert_eq!(
+                super::rbo_iter(&first, &second, 0.0).expect("valid rbo"),
+                expected
+            );
+            assert_eq!(
+                super::rbo_ids(&first, &second, 0.0).expect("valid rbo"),
+                expected
+            );
+            assert_eq!(
+                super::rbo_float(&first, &second, 0.0).expect("valid rbo"),
+                expected
+            );
+            let curve = super::rbo_curve(&first, &second, 0.0).expect("valid rbo");
+            assert!(curve.iter().all(|point| point.rbo == expected));
+            assert_eq!(
+                super::rbo_ext(&first, &second, 0.0).expect("valid rbo"),
+                expected.extrapolated
+            );
+            assert_eq!(
+                super::rbo_min(&first, &second, 0.0).expect("valid rbo"),
+                expected.min
+            );
+            assert_eq!(
+                super::rbo_res(&first, &second, 0.0).expect("valid rbo"),
+                expected.residual
+            );
+            let table = super::PersistenceTable::new(0.0, 4).expect("valid p");
+            assert_eq!(table.rbo(&first, &second).expect("valid rbo"), expected);
+        }
+        // nothing is known before both lists reach depth 1
+        let empty = super::rbo(&[], &[0, 1], 0.0).expect("valid rbo");
+        assert_eq!((empty.min, empty.residual), (0.0, 1.0));
+        assert!(empty.extrapolated.is_nan());
+    }
+
+    #[test]
+    fn test_depth_for_residual() {
+        let depth = super::depth_for_residual(0.9, 0.01).expect("valid target");
+        let disjoint = |k: usize| {
+            let first: Vec<usize> = (0..k).collect();
+            let second: Vec<usize> = (k..2 * k).collect();
+            super::rbo(&first, &second, 0.9)
+                .expect("valid rbo")
+                .residual
+        };
+        assert!(disjoint(depth) <= 0.01);
+        assert!(disjoint(depth - 1) > 0.01);
+        assert_eq!(
+            super::depth_for_residual(0.9, 1.0).expect("valid target"),
+            0
+        );
+        assert!(super::depth_for_residual(0.9, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_multi_p() {
+        let first: Vec<char> = "abcdefghij".chars().collect();
+        let second: Vec<char> = "bacdfegxyz".chars().collect();
+        let ps = [0.5, 0.9, 0.98];
+        let results = super::rbo_multi_p(&first, &second, &ps).expect("valid rbo");
+        for (p, result) in ps.iter().zip(&results) {
+            let expected = super::rbo(&first, &second, *p).expect("valid rbo");
+            approx::assert_abs_diff_eq!(result.min, expected.min, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(result.residual, expected.residual, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(
+                result.extrapolated,
+                expected.extrapolated,
+                epsilon = 1e-12
+            );
+        }
+        assert!(super::rbo_multi_p(&first, &second, &[0.9, 1.0]).is_err());
+    }
+
+    #[test]
+    fn test_curve() {
+        let first: Vec<char> = "abcdefghij".chars().collect();
+        let second: Vec<char> = "bxcaefzyqrstuv".chars().collect();
+        let curve = super::rbo_curve(&first, &second, 0.9).expect("valid rbo");
+        assert_eq!(curve.len(), second.len());
+        for point in &curve {
+            let direct = super::rbo_at_depth(&first, &second, 0.9, point.depth).expect("valid rbo");
+            let prefix = |list: &[char]| list[..point.depth.min(list.len())].to_vec();
+            let detailed =
+                super::rbo_detailed(&prefix(&first), &prefix(&second), 0.9).expect("valid rbo");
+            assert_eq!(point.overlap, detailed.overlap);
+            approx::assert_abs_diff_eq!(point.rbo.min, direct.min, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(point.rbo.residual, direct.residual, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(
+                point.rbo.extrapolated,
+                direct.extrapolated,
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_verbose() {
+        let first: Vec<char> = "abcd".chars().collect();
+        let second: Vec<char> = "bade".chars().collect();
+        let verbose = super::rbo_verbose(&first, &second, 0.9).expect("valid rbo");
+        assert_eq!(
+            verbose.rbo,
+            super::rbo(&first, &second, 0.9).expect("valid rbo")
+        );
+        assert_eq!(verbose.overlap, vec![0.0, 2.0, 2.0, 3.0]);
+        assert_eq!(verbose.agreement, vec![0.0, 1.0, 2.0 / 3.0, 0.75]);
+    }
+
+    #[test]
+    fn test_sensitivity() {
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let second: Vec<char> = "hgfedcba".chars().collect();
+        let sensitivity = super::rbo_sensitivity(&first, &second, 0.9, 1e-4).expect("valid rbo");
+        let below = super::rbo(&first, &second, 0.9 - 1e-4).expect("valid rbo");
+        let above = super::rbo(&first, &second, 0.9 + 1e-4).expect("valid rbo");
+        let expected = (above.extrapolated - below.extrapolated) / 2e-4;
+        approx::assert_abs_diff_eq!(sensitivity.d_extrapolated, expected, epsilon = 1e-9);
+        // reversed lists agree more the deeper the user looks
+        assert!(sensitivity.d_extrapolated > 0.0);
+        let identical = super::rbo_sensitivity(&first, &first, 0.9, 1e-4).expect("valid rbo");
+        approx::assert_abs_diff_eq!(identical.d_extrapolated, 0.0, epsilon = 1e-9);
+        assert!(super::rbo_sensitivity(&first, &second, 0.9, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_null_model() {
+        use rand::seq::SliceRandom;
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let mut domain: Vec<usize> = (0..12).collect();
+        let samples = 4000;
+        let (mut min, mut ext) = (0.0, 0.0);
+        for _ in 0..samples {
+            domain.shuffle(&mut rng);
+            let first = domain[..4].to_vec();
+            domain.shuffle(&mut rng);
+            let result = super::rbo(&first, &domain[..6], 0.9).expect("valid rbo");
+            min += result.min / samples as f64;
+            ext += result.extrapolated / samples as f64;
+        }
+        let null = super::rbo_null(4, 6, 12, 0.9).expect("valid domain");
+        approx::assert_abs_diff_eq!(null.min, min, epsilon = 0.01);
+        approx::assert_abs_diff_eq!(null.extrapolated, ext, epsilon = 0.01);
+
+        let ranking: Vec<usize> = (0..6).collect();
+        let normalized = super::rbo_normalized(&ranking, &ranking, 0.9, 12).expect("valid rbo");
+        approx::assert_abs_diff_eq!(normalized, 1.0, epsilon = 1e-9);
+        assert!(super::rbo_null(4, 13, 12, 0.9).is_err());
+    }
+
+    #[test]
+    fn test_explain() {
+        let first: Vec<char> = "abcdef".chars().collect();
+        let second: Vec<char> = "bacxef".chars().collect();
+        let explanation = super::rbo_explain(&first, &second, 0.9).expect("valid rbo");
+        let total: f64 = explanation.items.iter().map(|i| i.contribution).sum();
+        approx::assert_abs_diff_eq!(total, explanation.rbo.min, epsilon = 1e-12);
+        let costliest = explanation
+            .items
+            .iter()
+            .max_by(|a, b| a.cost.total_cmp(&b.cost))
+            .expect("items");
+        // the swap at the very top costs more than dropping d at rank 4
+        assert!(['a', 'b'].contains(costliest.item));
+        let missing = explanation
+            .items
+            .iter()
+            .find(|i| *i.item == 'x')
+            .expect("x is explained");
+        assert_eq!((missing.first_rank, missing.second_rank), (None, Some(4)));
+        assert_eq!(missing.contribution, 0.0);
+    }
+
+    #[test]
+    fn test_explain_long_lists() {
+        use rand::seq::SliceRandom;
+        use rand::SeedableRng;
+
+        // result files of 10^5 items, explained for several p as by `rbo --explain`,
+        // which took minutes while the weight of every depth was summed per item
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let first: Vec<u32> = (0..100_000).collect();
+        let mut second = first.clone();
+        second[..80_000].shuffle(&mut rng);
+        for p in [0.9, 0.99, 0.999] {
+            let explanation = super::rbo_explain(&first, &second, p).expect("valid rbo");
+            assert_eq!(explanation.items.len(), first.len());
+            let total = super::state::sum(explanation.items.iter().map(|i| i.contribution));
+            approx::assert_abs_diff_eq!(total, explanation.rbo.min, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_explain_by_key() {
+        // the same documents under differently normalized ids, with a payload
+        let first = [("DOC-1", 0.9), ("doc-2", 0.8), ("Doc-3", 0.7)];
+        let second = [("doc-2", 0.6), ("doc-4", 0.5), ("doc-1", 0.4)];
+        let key = |hit: &(&str, f64)| hit.0.to_lowercase();
+        let explanation = super::rbo_explain_by_key(&first, &second, 0.9, key).expect("valid rbo");
+        let keys = |list: &[(&str, f64)]| list.iter().map(key).collect::<Vec<_>>();
+        let (first_keys, second_keys) = (keys(&first), keys(&second));
+        let expected = super::rbo_explain(&first_keys, &second_keys, 0.9).expect("valid rbo");
+        assert_eq!(explanation.rbo, expected.rbo);
+        for (item, expected) in explanation.items.iter().zip(&expected.items) {
+            assert_eq!(key(item.item), *expected.item);
+            assert_eq!(
+                (item.first_rank, item.second_rank),
+                (expected.first_rank, expected.second_rank)
+            );
+            assert_eq!(
+                (item.contribution, item.cost),
+                (expected.contribution, expected.cost)
+            );
+        }
+        // items are passed through as they are in the first ranking, or else the second
+        let items: Vec<_> = explanation.items.iter().map(|i| *i.item).collect();
+        assert_eq!(items, [first[0], first[1], first[2], second[1]]);
+
+        let movements = super::rank_movements_by_key(&first, &second, key).expect("valid rbo");
+        let items: Vec<_> = movements.iter().map(|m| *m.item).collect();
+        assert_eq!(items, [first[0], first[1], first[2], second[1]]);
+        assert_eq!(movements[3].kind, super::MovementKind::New);
+        let duplicate = [("a", 1.0), ("A", 2.0)];
+        assert!(super::rbo_explain_by_key(&duplicate, &second, 0.9, key).is_err());
+        assert!(super::rank_movements_by_key(&first, &duplicate, key).is_err());
+    }
+
+    #[test]
+    fn test_detailed() {
+        let first: Vec<char> = "abcd".chars().collect();
+        let second: Vec<char> = "bxaeyz".chars().collect();
+        let detailed = super::rbo_detailed(&first, &second, 0.9).expect("valid rbo");
+        let rbo = detailed.rbo;
+        assert_eq!(rbo, super::rbo(&first, &second, 0.9).expect("valid rbo"));
+        assert_eq!(
+            (
+                detailed.first_depth,
+                detailed.second_depth,
+                detailed.overlap
+            ),
+            (4, 6, 2)
+        );
+        approx::assert_abs_diff_eq!(rbo.upper_bound(), rbo.min + rbo.residual);
+        assert!(rbo.min < rbo.midpoint() && rbo.midpoint() < rbo.upper_bound());
+    }
 }
@@ -0,0 +1,777 @@
+//! Pairwise RBO between all rankings of a corpus.
+
+#[cfg(feature = "stats")]
+use crate::stats::ConfidenceInterval;
+use crate::{Rbo, RboError};
+#[cfg(feature = "stats")]
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Symmetric matrix of pairwise RBO results. Only the upper triangle
+/// (including the diagonal) is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RboMatrix {
+    size: usize,
+    values: Vec<Rbo>,
+}
+
+impl RboMatrix {
+    fn filled(size: usize, value: Rbo) -> Self {
+        Self {
+            size,
+            values: vec![value; size * (size + 1) / 2],
+        }
+    }
+
+    fn index(&self, i: usize, j: usize) -> usize {
+        let (i, j) = if i <= j { (i, j) } else { (j, i) };
+        assert!(j < self.size, "index out of bounds");
+        // offset of row i in the packed upper triangle
+        i * self.size - i * (i + 1) / 2 + j
+    }
+
+    /// Number of rankings, i.e. the number of rows and columns
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// `true` if the matrix was built from an empty corpus
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// RBO between ranking `i` and ranking `j`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    #[must_use]
+    pub fn get(&self, i: usize, j: usize) -> Rbo {
+        self.values[self.index(i, j)]
+    }
+
+    fn set(&mut self, i: usize, j: usize, value: Rbo) {
+        let index = self.index(i, j);
+        self.values[index] = value;
+    }
+
+    /// The full matrix of extrapolated RBO values, one row per ranking
+    #[must_use]
+    pub fn extrapolated(&self) -> Vec<Vec<f64>> {
+        (0..self.size)
+            .map(|i| {
+                (0..self.size)
+                    .map(|j| self.get(i, j).extrapolated)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The full matrix of RBO distances `1 - extrapolated`, see
+    /// [`rbo_distance`](crate::rbo_distance)
+    #[must_use]
+    pub fn distances(&self) -> Vec<Vec<f64>> {
+        (0..self.size)
+            .map(|i| {
+                (0..self.size)
+                    .map(|j| 1.0 - self.get(i, j).extrapolated)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+const MANIFEST: &str = "manifest";
+
+// the pairs (i, j), i <= j, belonging to block (bi, bj)
+fn block_pairs(
+    size: usize,
+    block_size: usize,
+    bi: usize,
+    bj: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    let rows = bi * block_size..((bi + 1) * block_size).min(size);
+    let cols = bj * block_size..((bj + 1) * block_size).min(size);
+    rows.flat_map(move |i| cols.clone().filter(move |j| i <= *j).map(move |j| (i, j)))
+}
+
+fn block_path(dir: &Path, bi: usize, bj: usize) -> PathBuf {
+    dir.join(format!("block-{}-{}.tsv", bi, bj))
+}
+
+// a fingerprint of the rankings of a corpus, in order, so a checkpoint is
+// never resumed for a corpus that changed since
+fn corpus_fingerprint<Item: Hash, L: AsRef<[Item]>>(lists: &[L]) -> u64 {
+    let mut hasher = Fnv1a::default();
+    lists.len().hash(&mut hasher);
+    for list in lists {
+        list.as_ref().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// make sure the checkpoint directory belongs to this computation
+fn check_manifest(dir: &Path, manifest: &str) -> Result<(), RboError> {
+    let path = dir.join(MANIFEST);
+    match std::fs::read_to_string(&path) {
+        Ok(existing) if existing == manifest => Ok(()),
+        Ok(_) => Err(RboError::CheckpointMismatch(dir.display().to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(path, manifest)?;
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+// read a finished block, refusing it unless it holds exactly the `pairs` of
+// the block in the order they are written
+fn read_block(
+    path: &Path,
+    mut pairs: impl Iterator<Item = (usize, usize)>,
+    matrix: &mut RboMatrix,
+) -> Result<(), RboError> {
+    let corrupt = || RboError::CheckpointMismatch(path.display().to_string());
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    for line in reader.lines() {
+        let line = line?;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 5 {
+            return Err(corrupt());
+        }
+        let index = |f: &str| f.parse::<usize>().map_err(|_| corrupt());
+        let value = |f: &str| f.parse::<f64>().map_err(|_| corrupt());
+        let (i, j) = (index(fields[0])?, index(fields[1])?);
+        if pairs.next() != Some((i, j)) {
+            return Err(corrupt());
+        }
+        matrix.set(
+            i,
+            j,
+            Rbo {
+                min: value(fields[2])?,
+                residual: value(fields[3])?,
+                extrapolated: value(fields[4])?,
+            },
+        );
+    }
+    if pairs.next().is_some() {
+        return Err(corrupt());
+    }
+    Ok(())
+}
+
+fn write_block(path: &Path, results: &[(usize, usize, Rbo)]) -> Result<(), RboError> {
+    // write to a temporary file first so an interrupted write never looks complete
+    let tmp = path.with_extension("tmp");
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+    for (i, j, rbo) in results {
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}",
+            i, j, rbo.min, rbo.residual, rbo.extrapolated
+        )?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+///
+/// Compute the pairwise RBO matrix of a corpus. Items are interned into
+/// integer ids and every list is checked for duplicates once up front, so
+/// each of the N (N + 1) / 2 pair evaluations only hashes small integers.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_matrix<Item, L>(lists: &[L], p: f64) -> Result<RboMatrix, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    let interned = intern(lists, p)?;
+    let empty = Rbo {
+        min: f64::NAN,
+        residual: f64::NAN,
+        extrapolated: f64::NAN,
+    };
+    let mut matrix = RboMatrix::filled(lists.len(), empty);
+    for (i, first) in interned.iter().enumerate() {
+        for (j, second) in interned.iter().enumerate().skip(i) {
+            let state = crate::state::RboState::constant_memory(p)?;
+            matrix.set(i, j, crate::walk(state, first, second).into_result()?);
+        }
+    }
+    Ok(matrix)
+}
+
+// validate `p` and map the items of every list to integer ids, refusing
+// lists with duplicates
+fn intern<Item, L>(lists: &[L], p: f64) -> Result<Vec<Vec<u32>>, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    crate::state::validate_persistence(p)?;
+    let mut ids: HashMap<&Item, u32> = HashMap::new();
+    let interned: Vec<Vec<u32>> = lists
+        .iter()
+        .map(|list| {
+            list.as_ref()
+                .iter()
+                .map(|item| {
+                    let next = u32::try_from(ids.len()).expect("fewer than 2^32 distinct items");
+                    *ids.entry(item).or_insert(next)
+                })
+                .collect()
+        })
+        .collect();
+    for (i, list) in interned.iter().enumerate() {
+        crate::check_duplicates(list, i)?;
+    }
+    Ok(interned)
+}
+
+///
+/// Like [`rbo_matrix`], but evaluates the rows of the matrix concurrently on
+/// the rayon thread pool. Interning and duplicate checks run up front on the
+/// calling thread. The result is the same for any number of threads.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+#[cfg(feature = "rayon")]
+pub fn rbo_matrix_par<Item, L>(lists: &[L], p: f64) -> Result<RboMatrix, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    use rayon::prelude::*;
+
+    let interned = intern(lists, p)?;
+    let size = interned.len();
+    // rows of the packed upper triangle in order, so they concatenate into the matrix
+    let values = (0..size)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let interned = &interned;
+            (i..size).map(move |j| {
+                let state =
+                    crate::state::RboState::constant_memory(p).expect("persistence was validated");
+                crate::walk(state, &interned[i], &interned[j]).into_result()
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(RboMatrix {
+        size,
+        values: crate::batch::in_order(values)?,
+    })
+}
+
+///
+/// Like [`rbo_matrix_par`], but evaluates the rows on `pool` instead of the
+/// global rayon thread pool.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+#[cfg(feature = "rayon")]
+pub fn rbo_matrix_par_in<Item, L>(
+    pool: &rayon::ThreadPool,
+    lists: &[L],
+    p: f64,
+) -> Result<RboMatrix, RboError>
+where
+    Item: Eq + Hash + Sync,
+    L: AsRef<[Item]> + Sync,
+{
+    pool.install(|| rbo_matrix_par(lists, p))
+}
+
+///
+/// Compute the pairwise RBO matrix of a large corpus block by block,
+/// checkpointing every finished block to `checkpoint_dir`. Calling the
+/// function again with the same corpus, `p` and `block_size` reuses all
+/// blocks already on disk, so an interrupted run resumes where it stopped.
+///
+/// The corpus is split into chunks of `block_size` rankings and every pair of
+/// chunks forms one block; each block is written atomically as a small TSV
+/// file. The directory also records the corpus size, `p`, block size and a
+/// fingerprint of the rankings, and resuming with different parameters or a
+/// changed corpus is refused.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if the checkpoint directory belongs to a different computation or is corrupt
+/// - Will return `Err` if reading or writing checkpoint files fails
+///
+/// # Panics
+///
+/// Panics if `block_size` is zero.
+pub fn rbo_matrix_checkpointed<Item, L>(
+    lists: &[L],
+    p: f64,
+    block_size: usize,
+    checkpoint_dir: impl AsRef<Path>,
+) -> Result<RboMatrix, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    assert!(block_size > 0, "block size must be positive");
+    crate::state::validate_persistence(p)?;
+    let dir = checkpoint_dir.as_ref();
+    let size = lists.len();
+    check_manifest(
+        dir,
+        &format!(
+            "size={}\np={}\nblock_size={}\nfingerprint={:016x}\n",
+            size,
+            p,
+            block_size,
+            corpus_fingerprint(lists)
+        ),
+    )?;
+
+    let empty = Rbo {
+        min: f64::NAN,
+        residual: f64::NAN,
+        extrapolated: f64::NAN,
+    };
+    let mut matrix = RboMatrix::filled(size, empty);
+    let blocks = size.div_ceil(block_size);
+    for bi in 0..blocks {
+        for bj in bi..blocks {
+            let path = block_path(dir, bi, bj);
+            if path.exists() {
+                read_block(&path, block_pairs(size, block_size, bi, bj), &mut matrix)?;
+                continue;
+            }
+            let results = block_pairs(size, block_size, bi, bj)
+                .map(|(i, j)| {
+                    let rbo = crate::rbo(lists[i].as_ref(), lists[j].as_ref(), p)?;
+                    Ok((i, j, rbo))
+                })
+                .collect::<Result<Vec<_>, RboError>>()?;
+            write_block(&path, &results)?;
+            for (i, j, rbo) in results {
+                matrix.set(i, j, rbo);
+            }
+        }
+    }
+    Ok(matrix)
+}
+
+/// A cheap fingerprint of the top of a ranking, used to skip pairs that can
+/// not be similar before running the full RBO computation.
+///
+/// The signature holds hashes of the first `depth` items; the prefix Jaccard
+/// similarity between two signatures is a fast proxy for top-weighted
+/// agreement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSignature {
+    // sorted hashes of the prefix items
+    hashes: Vec<u64>,
+}
+
+impl PrefixSignature {
+    /// Build the signature of the first `depth` items of `list`
+    pub fn new<Item: Hash>(list: &[Item], depth: usize) -> Self {
+        let mut hashes: Vec<u64> = list
+            .iter()
+            .take(depth)
+            .map(|item| {
+                let mut hasher = Fnv1a::default();
+                item.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        Self { hashes }
+    }
+
+    /// Number of items shared by the two prefixes
+    #[must_use]
+    pub fn overlap(&self, other: &Self) -> usize {
+        let (mut a, mut b) = (
+            self.hashes.iter().peekable(),
+            other.hashes.iter().peekable(),
+        );
+        let mut shared = 0;
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            match x.cmp(y) {
+                std::cmp::Ordering::Less => {
+                    a.next();
+                }
+                std::cmp::Ordering::Greater => {
+                    b.next();
+                }
+                std::cmp::Ordering::Equal => {
+                    shared += 1;
+                    a.next();
+                    b.next();
+                }
+            }
+        }
+        shared
+    }
+
+    /// Jaccard similarity of the two prefixes
+    #[must_use]
+    pub fn jaccard(&self, other: &Self) -> f64 {
+        let shared = self.overlap(other);
+        let union = self.hashes.len() + other.hashes.len() - shared;
+        if union == 0 {
+            return 1.0;
+        }
+        shared as f64 / union as f64
+    }
+}
+
+// 64-bit FNV-1a. Unlike `DefaultHasher`, whose algorithm may change between
+// Rust releases, it is fully specified, so signatures of items with the same
+// `Hash` implementation are the same in every run and every build.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Pre-filtering applied before the full RBO computation of a pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prefilter {
+    /// Number of top items in each [`PrefixSignature`]
+    pub depth: usize,
+    /// Pairs whose prefix Jaccard similarity is below this floor are skipped
+    pub floor: f64,
+}
+
+impl Default for Prefilter {
+    fn default() -> Self {
+        Self {
+            depth: 50,
+            floor: 0.0,
+        }
+    }
+}
+
+///
+/// Compute RBO for all pairs of rankings whose [`PrefixSignature`]s have a
+/// Jaccard similarity of at least `prefilter.floor`, skipping the full
+/// computation for all others. Useful when most pairs in a large corpus are
+/// dissimilar. Returns `(i, j, rbo)` for the evaluated pairs with `i < j`,
+/// ordered by decreasing prefix similarity so the most promising pairs come
+/// first.
+///
+/// Note that the filter is a heuristic: two rankings with disjoint prefixes
+/// can still have a small positive RBO from agreement further down.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_matrix_prefiltered<Item, L>(
+    lists: &[L],
+    p: f64,
+    prefilter: Prefilter,
+) -> Result<Vec<(usize, usize, Rbo)>, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    crate::state::validate_persistence(p)?;
+    let signatures: Vec<PrefixSignature> = lists
+        .iter()
+        .map(|l| PrefixSignature::new(l.as_ref(), prefilter.depth))
+        .collect();
+    let mut candidates: Vec<(usize, usize, f64)> = (0..lists.len())
+        .flat_map(|i| (i + 1..lists.len()).map(move |j| (i, j)))
+        .map(|(i, j)| (i, j, signatures[i].jaccard(&signatures[j])))
+        .filter(|(_, _, similarity)| *similarity >= prefilter.floor)
+        .collect();
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+    candidates
+        .into_iter()
+        .map(|(i, j, _)| Ok((i, j, crate::rbo(lists[i].as_ref(), lists[j].as_ref(), p)?)))
+        .collect()
+}
+
+/// Distribution of pairwise extrapolated RBO estimated from a sample of pairs
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PairwiseEstimate {
+    /// Number of pairs in the corpus
+    pub total_pairs: usize,
+    /// Extrapolated RBO of every sampled pair, sorted ascending
+    pub samples: Vec<f64>,
+    /// Mean pairwise similarity with a t-based confidence interval. The
+    /// interval has zero width if every pair was evaluated.
+    pub mean: ConfidenceInterval,
+    /// Sample standard deviation of the pairwise similarities
+    pub std_dev: f64,
+    /// Sample median of the pairwise similarities
+    pub median: f64,
+    /// Sample lower quartile of the pairwise similarities
+    pub lower_quartile: f64,
+    /// Sample upper quartile of the pairwise similarities
+    pub upper_quartile: f64,
+}
+
+// the pair (i, j), i < j, at position `k` in row-major order of the strict upper triangle
+#[cfg(feature = "stats")]
+fn pair_at(size: usize, k: usize) -> (usize, usize) {
+    // row i starts at offset i * (2n - i - 1) / 2
+    let offset = |i: usize| i * (2 * size - i - 1) / 2;
+    let n = size as f64;
+    let guess = n - 0.5 - ((n - 0.5) * (n - 0.5) - 2.0 * k as f64).max(0.0).sqrt();
+    let mut i = (guess.floor().max(0.0) as usize).min(size - 2);
+    while i > 0 && offset(i) > k {
+        i -= 1;
+    }
+    while offset(i + 1) <= k {
+        i += 1;
+    }
+    (i, i + 1 + k - offset(i))
+}
+
+// linear interpolation between closest ranks, expects sorted values
+#[cfg(feature = "stats")]
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+///
+/// Estimate the distribution of pairwise similarity within a corpus from a
+/// random sample of `samples` distinct pairs instead of the full O(N²)
+/// matrix. The sample is drawn without replacement using a generator seeded
+/// with `seed`; if `samples` covers all pairs the whole matrix is evaluated.
+/// The mean is reported with a confidence interval at level `confidence`.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if the corpus has fewer than two rankings or fewer than two pairs are sampled
+/// - Will return `Err` if `confidence` is not 0 < confidence < 1
+///
+#[cfg(feature = "stats")]
+pub fn estimate_pairwise<Item, L>(
+    lists: &[L],
+    p: f64,
+    samples: usize,
+    seed: u64,
+    confidence: f64,
+) -> Result<PairwiseEstimate, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(RboError::InvalidConfidenceLevel);
+    }
+    let size = lists.len();
+    let total_pairs = size * size.saturating_sub(1) / 2;
+    let samples = samples.min(total_pairs);
+    if samples < 2 {
+        return Err(RboError::InsufficientData);
+    }
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut values = rand::seq::index::sample(&mut rng, total_pairs, samples)
+        .into_iter()
+        .map(|k| {
+            let (i, j) = pair_at(size, k);
+            Ok(crate::rbo(lists[i].as_ref(), lists[j].as_ref(), p)?.extrapolated)
+        })
+        .collect::<Result<Vec<f64>, RboError>>()?;
+    values.sort_by(f64::total_cmp);
+
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let std_dev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    // finite population correction, the interval vanishes once every pair is evaluated
+    let correction = ((total_pairs - samples) as f64 / (total_pairs - 1).max(1) as f64).sqrt();
+    let half_width = crate::stats::dist::t_quantile(0.5 + confidence / 2.0, n - 1.0) * std_dev
+        / n.sqrt()
+        * correction;
+    Ok(PairwiseEstimate {
+        total_pairs,
+        mean: ConfidenceInterval {
+            mean,
+            lower: mean - half_width,
+            upper: mean + half_width,
+        },
+        std_dev,
+        median: quantile(&values, 0.5),
+        lower_quartile: quantile(&values, 0.25),
+        upper_quartile: quantile(&values, 0.75),
+        samples: values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn prefilter_skips_disjoint_pairs() {
+        use std::hash::Hasher;
+        let lists: Vec<Vec<char>> = ["abcdef", "abdcef", "uvwxyz", "bacfed"]
+            .iter()
+            .map(|l| l.chars().collect())
+            .collect();
+        let prefilter = super::Prefilter {
+            depth: 3,
+            floor: 0.5,
+        };
+        let pairs = super::rbo_matrix_prefiltered(&lists, 0.9, prefilter).expect("valid rbo");
+        let evaluated: Vec<_> = pairs.iter().map(|(i, j, _)| (*i, *j)).collect();
+        assert_eq!(evaluated, vec![(0, 3), (0, 1), (1, 3)]);
+        let a = super::PrefixSignature::new(&lists[0], 3);
+        let b = super::PrefixSignature::new(&lists[1], 3);
+        approx::assert_abs_diff_eq!(a.jaccard(&b), 0.5);
+        // the published FNV-1a test vector, signatures do not depend on the build
+        let mut hasher = super::Fnv1a::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn pair_enumeration_is_complete() {
+        let size = 7;
+        let pairs: Vec<_> = (0..size * (size - 1) / 2)
+            .map(|k| super::pair_at(size, k))
+            .collect();
+        let expected: Vec<_> = (0..size)
+            .flat_map(|i| (i + 1..size).map(move |j| (i, j)))
+            .collect();
+        assert_eq!(pairs, expected);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn sampled_estimate_is_exact_when_all_pairs_are_sampled() {
+        let lists: Vec<Vec<char>> = ["abcdef", "abdcef", "fedcba", "xyzabc"]
+            .iter()
+            .map(|l| l.chars().collect())
+            .collect();
+        let estimate = super::estimate_pairwise(&lists, 0.9, 100, 7, 0.95).expect("valid rbo");
+        assert_eq!(estimate.total_pairs, 6);
+        assert_eq!(estimate.samples.len(), 6);
+        approx::assert_abs_diff_eq!(estimate.mean.lower, estimate.mean.upper);
+        let partial = super::estimate_pairwise(&lists, 0.9, 3, 7, 0.95).expect("valid rbo");
+        assert_eq!(partial.samples.len(), 3);
+        assert!(partial.mean.lower < partial.mean.upper);
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let lists: Vec<Vec<char>> = ["abcdef", "abdcef", "fedcba", "xyzabc", "bcadef"]
+            .iter()
+            .map(|l| l.chars().collect())
+            .collect();
+        let dir = std::env::temp_dir().join(format!("rbo-matrix-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let matrix = super::rbo_matrix_checkpointed(&lists, 0.9, 2, &dir).expect("valid rbo");
+        assert_eq!(matrix.len(), 5);
+        let direct = crate::rbo(&lists[1], &lists[3], 0.9).expect("valid rbo");
+        assert_eq!(matrix.get(3, 1), direct);
+
+        // drop one block and resume
+        std::fs::remove_file(dir.join("block-0-1.tsv")).expect("block exists");
+        let resumed = super::rbo_matrix_checkpointed(&lists, 0.9, 2, &dir).expect("valid rbo");
+        assert_eq!(matrix, resumed);
+
+        // refuse blocks cut short or holding pairs of another block
+        let block = dir.join("block-1-2.tsv");
+        let content = std::fs::read_to_string(&block).expect("block exists");
+        let truncated = content.lines().take(1).collect::<Vec<_>>().join("\n");
+        let moved = content.replacen("2\t4\t", "0\t4\t", 1);
+        for edited in [truncated, moved] {
+            std::fs::write(&block, edited).expect("edit block");
+            assert!(matches!(
+                super::rbo_matrix_checkpointed(&lists, 0.9, 2, &dir),
+                Err(crate::RboError::CheckpointMismatch(_))
+            ));
+        }
+        std::fs::write(&block, content).expect("restore block");
+
+        // refuse to resume a different computation
+        assert!(super::rbo_matrix_checkpointed(&lists, 0.8, 2, &dir).is_err());
+        let mut changed = lists.clone();
+        changed[4].swap(0, 1);
+        assert!(matches!(
+            super::rbo_matrix_checkpointed(&changed, 0.9, 2, &dir),
+            Err(crate::RboError::CheckpointMismatch(_))
+        ));
+        std::fs::remove_dir_all(&dir).expect("clean up");
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_matrix_matches_sequential() {
+        let lists: Vec<Vec<char>> = ["abcdef", "abdcef", "uvwxyz", "bacfed", "fedcba", "a"]
+            .iter()
+            .map(|l| l.chars().collect())
+            .collect();
+        let sequential = super::rbo_matrix(&lists, 0.9).expect("valid rbo");
+        let parallel = super::rbo_matrix_par(&lists, 0.9).expect("valid rbo");
+        assert_eq!(sequential, parallel);
+        let duplicated = vec![vec!['a', 'b'], vec!['c', 'c']];
+        assert!(super::rbo_matrix_par(&duplicated, 0.9).is_err());
+    }
+
+    #[test]
+    fn matrix_matches_pairwise_rbo() {
+        let lists: Vec<Vec<char>> = ["abcdef", "abdcefxy", "fedcba", "xyz"]
+            .iter()
+            .map(|l| l.chars().collect())
+            .collect();
+        let matrix = super::rbo_matrix(&lists, 0.9).expect("valid rbo");
+        for i in 0..lists.len() {
+            for j in 0..lists.len() {
+                let direct = crate::rbo(&lists[i], &lists[j], 0.9).expect("valid rbo");
+                assert_eq!(matrix.get(i, j), direct);
+            }
+        }
+        assert!(super::rbo_matrix(&[vec!['a', 'a']], 0.9).is_err());
+    }
+}
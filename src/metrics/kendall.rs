@@ -0,0 +1,117 @@
+use crate::RboError;
+use std::hash::Hash;
+
+// for every position i, the number of earlier positions with a smaller value,
+// using a Fenwick tree over the values 0..n
+fn smaller_before(values: &[usize]) -> Vec<usize> {
+    let mut tree = vec![0usize; values.len() + 1];
+    values
+        .iter()
+        .map(|value| {
+            let mut count = 0;
+            let mut i = *value;
+            while i > 0 {
+                count += tree[i];
+                i &= i - 1;
+            }
+            let mut i = value + 1;
+            while i < tree.len() {
+                tree[i] += 1;
+                i += i & i.wrapping_neg();
+            }
+            count
+        })
+        .collect()
+}
+
+///
+/// Kendall's tau between two conjoint rankings: the difference between the
+/// fraction of concordant and discordant item pairs, from -1 (reversed) to 1
+/// (identical). Runs in O(n log n).
+///
+/// # Errors
+///
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if the rankings do not contain the same items
+/// - Will return `Err` if the rankings contain fewer than two items
+///
+pub fn kendall_tau<Item>(first: &[Item], second: &[Item]) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let ranks = super::reference_ranks(first, second)?;
+    let n = ranks.len() as f64;
+    let concordant: usize = smaller_before(&ranks).iter().sum();
+    Ok(4.0 * concordant as f64 / (n * (n - 1.0)) - 1.0)
+}
+
+///
+/// The top-weighted AP correlation tau_AP of Yilmaz, Aslam and Robertson
+/// (SIGIR 2008) between a ranking `first` and a reference ranking `second`.
+/// Like Kendall's tau it ranges from -1 to 1, but swaps near the top of
+/// `first` are penalized more than swaps near the bottom. The measure is not
+/// symmetric.
+///
+/// # Errors
+///
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if the rankings do not contain the same items
+/// - Will return `Err` if the rankings contain fewer than two items
+///
+pub fn tau_ap<Item>(first: &[Item], second: &[Item]) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let ranks = super::reference_ranks(first, second)?;
+    let n = ranks.len() as f64;
+    let precision: f64 = smaller_before(&ranks)
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, correct)| *correct as f64 / i as f64)
+        .sum();
+    Ok(2.0 / (n - 1.0) * precision - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kendall_tau, tau_ap};
+
+    #[test]
+    fn tau_and_tau_ap() {
+        let reference: Vec<char> = "abcde".chars().collect();
+        let top_swap: Vec<char> = "bacde".chars().collect();
+        let bottom_swap: Vec<char> = "abced".chars().collect();
+        let reversed: Vec<char> = "edcba".chars().collect();
+        approx::assert_abs_diff_eq!(
+            kendall_tau(&reference, &reference).expect("conjoint rankings"),
+            1.0
+        );
+        approx::assert_abs_diff_eq!(
+            kendall_tau(&reversed, &reference).expect("conjoint rankings"),
+            -1.0
+        );
+        approx::assert_abs_diff_eq!(
+            kendall_tau(&top_swap, &reference).expect("conjoint rankings"),
+            0.8
+        );
+        approx::assert_abs_diff_eq!(
+            kendall_tau(&bottom_swap, &reference).expect("conjoint rankings"),
+            0.8
+        );
+        approx::assert_abs_diff_eq!(
+            tau_ap(&top_swap, &reference).expect("conjoint rankings"),
+            0.5
+        );
+        approx::assert_abs_diff_eq!(
+            tau_ap(&bottom_swap, &reference).expect("conjoint rankings"),
+            0.875
+        );
+        approx::assert_abs_diff_eq!(
+            tau_ap(&reversed, &reference).expect("conjoint rankings"),
+            -1.0
+        );
+        assert!(kendall_tau(&reference, &reversed[1..]).is_err());
+        assert!(tau_ap(&reference, &"abcdf".chars().collect::<Vec<_>>()).is_err());
+    }
+}
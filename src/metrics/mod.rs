@@ -0,0 +1,35 @@
+//! Classic rank correlation measures alongside RBO.
+//!
+//! All functions take the same `&[Item]` rankings as [`rbo`](crate::rbo) and
+//! share its duplicate checking. Unlike RBO these measures are only defined for
+//! conjoint rankings, i.e. both rankings must order the same set of items.
+
+mod kendall;
+mod spearman;
+
+pub use kendall::{kendall_tau, tau_ap};
+pub use spearman::{spearman_footrule, spearman_rho};
+
+use crate::RboError;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// the 0-based rank in `second` of every item of `first`, in the order of `first`
+fn reference_ranks<Item>(first: &[Item], second: &[Item]) -> Result<Vec<usize>, RboError>
+where
+    Item: Eq + Hash,
+{
+    crate::check_duplicates(first, 0)?;
+    crate::check_duplicates(second, 1)?;
+    if first.len() != second.len() {
+        return Err(RboError::NotConjoint);
+    }
+    if first.len() < 2 {
+        return Err(RboError::InsufficientData);
+    }
+    let ranks: HashMap<&Item, usize> = second.iter().enumerate().map(|(r, i)| (i, r)).collect();
+    first
+        .iter()
+        .map(|item| ranks.get(item).copied().ok_or(RboError::NotConjoint))
+        .collect()
+}
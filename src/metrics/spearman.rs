@@ -0,0 +1,72 @@
+use crate::RboError;
+use std::hash::Hash;
+
+///
+/// Spearman's rank correlation coefficient rho between two conjoint
+/// rankings, 1 - 6 sum(d_i^2) / (n (n^2 - 1)) where d_i is the difference
+/// between the two ranks of item i. Ranges from -1 (reversed) to 1
+/// (identical).
+///
+/// # Errors
+///
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if the rankings do not contain the same items
+/// - Will return `Err` if the rankings contain fewer than two items
+///
+pub fn spearman_rho<Item>(first: &[Item], second: &[Item]) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let ranks = super::reference_ranks(first, second)?;
+    let n = ranks.len() as f64;
+    let squared: f64 = ranks
+        .iter()
+        .enumerate()
+        .map(|(rank, other)| (rank as f64 - *other as f64).powi(2))
+        .sum();
+    Ok(1.0 - 6.0 * squared / (n * (n * n - 1.0)))
+}
+
+///
+/// Spearman's footrule distance between two conjoint rankings, the sum of
+/// the absolute rank differences of all items. It is 0 for identical
+/// rankings and at most floor(n^2 / 2), reached by reversed rankings.
+///
+/// # Errors
+///
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if the rankings do not contain the same items
+/// - Will return `Err` if the rankings contain fewer than two items
+///
+pub fn spearman_footrule<Item>(first: &[Item], second: &[Item]) -> Result<usize, RboError>
+where
+    Item: Eq + Hash,
+{
+    let ranks = super::reference_ranks(first, second)?;
+    Ok(ranks
+        .iter()
+        .enumerate()
+        .map(|(rank, other)| rank.abs_diff(*other))
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{spearman_footrule, spearman_rho};
+
+    #[test]
+    fn rho_and_footrule() {
+        let reference: Vec<char> = "abcde".chars().collect();
+        let swapped: Vec<char> = "bacde".chars().collect();
+        let reversed: Vec<char> = "edcba".chars().collect();
+        let rho = |a: &[char]| spearman_rho(a, &reference).expect("conjoint rankings");
+        let footrule = |a: &[char]| spearman_footrule(a, &reference).expect("conjoint rankings");
+        approx::assert_abs_diff_eq!(rho(&reference), 1.0);
+        approx::assert_abs_diff_eq!(rho(&reversed), -1.0);
+        approx::assert_abs_diff_eq!(rho(&swapped), 0.9);
+        assert_eq!(footrule(&reference), 0);
+        assert_eq!(footrule(&swapped), 2);
+        assert_eq!(footrule(&reversed), 12);
+        assert!(spearman_rho(&reference, &reference[1..]).is_err());
+    }
+}
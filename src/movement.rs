@@ -0,0 +1,145 @@
+use crate::RboError;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// How an item changed position between the first and the second ranking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MovementKind {
+    /// Present in both rankings at the same rank
+    Unchanged,
+    /// Present in both rankings at different ranks
+    Moved,
+    /// Only present in the first ranking
+    Dropped,
+    /// Only present in the second ranking
+    New,
+}
+
+/// The movement of a single item between two rankings. Ranks count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ItemMovement<'a, Item> {
+    /// The item itself
+    pub item: &'a Item,
+    /// Rank in the first ranking, `None` if the item is new
+    pub source_rank: Option<usize>,
+    /// Rank in the second ranking, `None` if the item was dropped
+    pub target_rank: Option<usize>,
+    /// Summary of the movement
+    pub kind: MovementKind,
+}
+
+///
+/// Describe how items move from `first` to `second`: the source and target
+/// rank of every item, and which items were dropped or are new. Items of the
+/// first ranking are reported in rank order, followed by the new items in the
+/// order they appear in the second ranking.
+///
+/// With the `serde` feature enabled the result serializes to a flat list of
+/// objects which can be fed directly to slopegraph or Sankey visualizations.
+///
+/// # Errors
+///
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rank_movements<'a, Item>(
+    first: &'a [Item],
+    second: &'a [Item],
+) -> Result<Vec<ItemMovement<'a, Item>>, RboError>
+where
+    Item: Eq + Hash,
+{
+    crate::check_duplicates(first, 0)?;
+    crate::check_duplicates(second, 1)?;
+    let target_ranks: HashMap<&Item, usize> = second
+        .iter()
+        .enumerate()
+        .map(|(rank, item)| (item, rank + 1))
+        .collect();
+
+    let mut movements: Vec<_> = first
+        .iter()
+        .enumerate()
+        .map(|(rank, item)| {
+            let source_rank = rank + 1;
+            let target_rank = target_ranks.get(item).copied();
+            let kind = match target_rank {
+                Some(target) if target == source_rank => MovementKind::Unchanged,
+                Some(_) => MovementKind::Moved,
+                None => MovementKind::Dropped,
+            };
+            ItemMovement {
+                item,
+                source_rank: Some(source_rank),
+                target_rank,
+                kind,
+            }
+        })
+        .collect();
+
+    let source_items: std::collections::HashSet<&Item> = first.iter().collect();
+    movements.extend(
+        second
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !source_items.contains(item))
+            .map(|(rank, item)| ItemMovement {
+                item,
+                source_rank: None,
+                target_rank: Some(rank + 1),
+                kind: MovementKind::New,
+            }),
+    );
+    Ok(movements)
+}
+
+///
+/// Like [`rank_movements`], but for rankings of rich items compared by the
+/// key `key` extracts from each item, as [`rbo_by_key`](crate::rbo_by_key).
+/// Every movement refers to the original item, so payloads such as titles,
+/// scores or sources come through unchanged without joining them back by key.
+/// For an item in both rankings that is the item of the first ranking, the
+/// one of the second is at `target_rank`.
+///
+/// # Errors
+///
+/// - Will return `Err` if two items of the same list have the same key
+///
+pub fn rank_movements_by_key<'a, T, K, F>(
+    first: &'a [T],
+    second: &'a [T],
+    mut key: F,
+) -> Result<Vec<ItemMovement<'a, T>>, RboError>
+where
+    K: Eq + Hash,
+    F: FnMut(&T) -> K,
+{
+    let first_keys: Vec<K> = first.iter().map(&mut key).collect();
+    let second_keys: Vec<K> = second.iter().map(&mut key).collect();
+    let movements = rank_movements(&first_keys, &second_keys)?
+        .into_iter()
+        .map(|movement| ItemMovement {
+            item: item_at(first, second, movement.source_rank, movement.target_rank),
+            source_rank: movement.source_rank,
+            target_rank: movement.target_rank,
+            kind: movement.kind,
+        })
+        .collect();
+    Ok(movements)
+}
+
+// the item at `first_rank` of `first`, or else at `second_rank` of `second`
+pub(crate) fn item_at<'a, T>(
+    first: &'a [T],
+    second: &'a [T],
+    first_rank: Option<usize>,
+    second_rank: Option<usize>,
+) -> &'a T {
+    match (first_rank, second_rank) {
+        (Some(rank), _) => &first[rank - 1],
+        (None, Some(rank)) => &second[rank - 1],
+        (None, None) => unreachable!("every item is in one of the rankings"),
+    }
+}
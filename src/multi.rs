@@ -0,0 +1,42 @@
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+///
+/// RBO across more than two rankings, computed as the mean pairwise RBO over
+/// all N (N - 1) / 2 pairs of `lists`. As min, residual and extrapolated are
+/// averaged separately, `min + residual` remains an upper bound on the mean
+/// agreement.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if any list contains duplicate items
+/// - Will return `Err` if fewer than two lists are given
+///
+pub fn rbo_multi<Item>(lists: &[&[Item]], p: f64) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+{
+    if lists.len() < 2 {
+        return Err(RboError::InsufficientData);
+    }
+    let mut sum = Rbo {
+        min: 0.0,
+        residual: 0.0,
+        extrapolated: 0.0,
+    };
+    for (i, first) in lists.iter().enumerate() {
+        for second in &lists[i + 1..] {
+            let pair = crate::rbo(first, second, p)?;
+            sum.min += pair.min;
+            sum.residual += pair.residual;
+            sum.extrapolated += pair.extrapolated;
+        }
+    }
+    let pairs = (lists.len() * (lists.len() - 1) / 2) as f64;
+    Ok(Rbo {
+        min: sum.min / pairs,
+        residual: sum.residual / pairs,
+        extrapolated: sum.extrapolated / pairs,
+    })
+}
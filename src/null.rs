@@ -0,0 +1,63 @@
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+///
+/// The RBO expected by chance between a ranking of `first_len` and a ranking
+/// of `second_len` items, both drawn uniformly at random from a domain of
+/// `domain_size` items. In closed form the expected overlap at depth d is
+/// |S_:d| |T_:d| / domain_size, and as min and extrapolated are linear in the
+/// overlaps they are exact expectations. The residual is that of the
+/// expected overlap.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if a ranking is longer than the domain
+///
+pub fn rbo_null(
+    first_len: usize,
+    second_len: usize,
+    domain_size: usize,
+    p: f64,
+) -> Result<Rbo, RboError> {
+    crate::state::validate_persistence(p)?;
+    if first_len.max(second_len) > domain_size {
+        return Err(RboError::InvalidDomain);
+    }
+    let s = first_len.min(second_len);
+    let l = first_len.max(second_len);
+    let overlap: Vec<f64> = (0..=l)
+        .map(|d| (d.min(s) * d) as f64 / domain_size as f64)
+        .collect();
+    Ok(Rbo {
+        min: crate::state::min(&overlap, s, p),
+        residual: crate::state::residual(s as f64, l as f64, overlap[l], p),
+        extrapolated: crate::state::extrapolated(&overlap, s, l, p),
+    })
+}
+
+///
+/// Chance-corrected RBO, `(rbo - expected) / (1 - expected)` for the
+/// extrapolated RBO and its expectation under [`rbo_null`]. The score is 0
+/// for rankings that agree no more than random ones of the same lengths from
+/// a domain of `domain_size` items, and 1 for identical rankings.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if a ranking is longer than the domain
+///
+pub fn rbo_normalized<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    domain_size: usize,
+) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let expected = rbo_null(first.len(), second.len(), domain_size, p)?.extrapolated;
+    let observed = crate::rbo(first, second, p)?.extrapolated;
+    Ok((observed - expected) / (1.0 - expected))
+}
@@ -0,0 +1,125 @@
+use crate::state::RboState;
+use crate::{Rbo, RboError};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// The universe the ranked items are drawn from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Domain {
+    /// Indefinite rankings over an unbounded domain, as in the paper
+    #[default]
+    Indefinite,
+    /// Both rankings are orderings of the same finite domain of `size` items,
+    /// of which only prefixes may have been observed. Two prefixes of depth d
+    /// then share at least 2d - size items and the rankings agree completely
+    /// beyond depth `size`, which raises the lower bound and so shrinks the
+    /// residual.
+    Conjoint {
+        /// Number of items in the domain
+        size: usize,
+    },
+}
+
+/// What to do with items occurring more than once in a ranking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Duplicates {
+    /// Refuse the rankings with [`RboError::DuplicatesInList`]
+    #[default]
+    Error,
+    /// Keep the first occurrence of every item and drop the later ones
+    DedupKeepFirst,
+    /// Treat the rankings as multisets: the n-th occurrence of an item in one
+    /// ranking only matches the n-th occurrence in the other
+    Multiset,
+}
+
+/// Options for [`rbo_with_options`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboOptions {
+    /// The domain the ranked items are drawn from
+    pub domain: Domain,
+    /// How duplicate items are handled
+    pub duplicates: Duplicates,
+}
+
+fn dedup_keep_first<Item: Eq + Hash>(list: &[Item]) -> Vec<&Item> {
+    let mut seen = HashSet::with_capacity(list.len());
+    list.iter().filter(|item| seen.insert(*item)).collect()
+}
+
+// pair every item with the number of times it occurred before
+fn occurrences<Item: Eq + Hash>(list: &[Item]) -> Vec<(&Item, usize)> {
+    let mut counts: HashMap<&Item, usize> = HashMap::new();
+    list.iter()
+        .map(|item| {
+            let count = counts.entry(item).or_insert(0);
+            *count += 1;
+            (item, *count - 1)
+        })
+        .collect()
+}
+
+fn finish<Item>(state: RboState<&Item>, domain: Domain) -> Result<Rbo, RboError> {
+    match domain {
+        Domain::Indefinite => state.into_result(),
+        Domain::Conjoint { size } => state.into_conjoint_result(size),
+    }
+}
+
+// the curve of `rbo_curve` over the lists as `rbo_with_options` evaluates them
+pub(crate) fn curve_with_options<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    duplicates: Duplicates,
+) -> Result<Vec<crate::RboPoint>, RboError>
+where
+    Item: Eq + Hash,
+{
+    match duplicates {
+        Duplicates::Error => crate::rbo_curve(first, second, p),
+        Duplicates::DedupKeepFirst => {
+            crate::rbo_curve(&dedup_keep_first(first), &dedup_keep_first(second), p)
+        }
+        Duplicates::Multiset => crate::rbo_curve(&occurrences(first), &occurrences(second), p),
+    }
+}
+
+///
+/// RBO with non-default evaluation options, see [`RboOptions`]. With the
+/// default options this is the same as [`rbo`](crate::rbo).
+///
+/// In a [`Domain::Conjoint`] domain `min` and `min + residual` are the exact
+/// lower and upper bounds given the finite domain and the extrapolated value
+/// is clamped to lie between them.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items and duplicates are an error
+/// - Will return `Err` if the lists contain more distinct items than a conjoint domain holds
+///
+pub fn rbo_with_options<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    options: RboOptions,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+{
+    match options.duplicates {
+        Duplicates::Error => finish(crate::evaluate(first, second, p)?, options.domain),
+        Duplicates::DedupKeepFirst => {
+            let (first, second) = (dedup_keep_first(first), dedup_keep_first(second));
+            finish(crate::evaluate(&first, &second, p)?, options.domain)
+        }
+        Duplicates::Multiset => {
+            let (first, second) = (occurrences(first), occurrences(second));
+            finish(crate::evaluate(&first, &second, p)?, options.domain)
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::state::validate_persistence;
+use crate::{Duplicate, Rbo, RboError};
+
+///
+/// Like [`rbo`](crate::rbo), but items only need to be `Ord` instead of
+/// `Eq + Hash`. Items are matched by sorting both lists once, so this takes
+/// O((n + m) log(n + m)) comparisons and no hashing of the items.
+///
+/// ```
+/// #[derive(PartialEq, Eq, PartialOrd, Ord)]
+/// struct Doc(&'static str);
+///
+/// let first = [Doc("a"), Doc("b"), Doc("c")];
+/// let second = [Doc("b"), Doc("c"), Doc("d")];
+/// let rbo = rbo::rbo_ord(&first, &second, 0.9).expect("valid rbo");
+/// assert_eq!(rbo, rbo::rbo(&["a", "b", "c"], &["b", "c", "d"], 0.9).expect("valid rbo"));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_ord<Item>(first: &[Item], second: &[Item], p: f64) -> Result<Rbo, RboError>
+where
+    Item: Ord,
+{
+    validate_persistence(p)?;
+    // every occurrence as (item, list, rank), so equal items end up next to
+    // each other, in list and rank order
+    let mut occurrences: Vec<(&Item, usize, usize)> = first
+        .iter()
+        .enumerate()
+        .map(|(rank, item)| (item, 0, rank))
+        .chain(
+            second
+                .iter()
+                .enumerate()
+                .map(|(rank, item)| (item, 1, rank)),
+        )
+        .collect();
+    occurrences.sort_unstable();
+    // items are identified by the position of their group of equal items
+    let mut ids = [vec![0; first.len()], vec![0; second.len()]];
+    // the duplicate whose repetition comes first in its list, as for `rbo`
+    let mut duplicate: Option<(usize, Duplicate)> = None;
+    for (id, group) in occurrences.chunk_by(|a, b| a.0 == b.0).enumerate() {
+        for (list, list_ids) in ids.iter_mut().enumerate() {
+            let ranks: Vec<usize> = group
+                .iter()
+                .filter(|occurrence| occurrence.1 == list)
+                .map(|occurrence| occurrence.2)
+                .collect();
+            for &rank in &ranks {
+                list_ids[rank] = id;
+            }
+            if let [_, repeat, ..] = ranks[..] {
+                let earlier = duplicate
+                    .as_ref()
+                    .is_some_and(|(at, found)| (found.list, *at) < (list, repeat));
+                if !earlier {
+                    duplicate = Some((
+                        repeat,
+                        Duplicate {
+                            list,
+                            indices: ranks,
+                            item_debug: None,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+    if let Some((_, duplicate)) = duplicate {
+        return Err(RboError::DuplicatesInList(duplicate));
+    }
+    let [first, second] = ids;
+    crate::rbo(&first, &second, p)
+}
@@ -0,0 +1,56 @@
+use crate::RboError;
+use std::hash::Hash;
+
+///
+/// Average Overlap, the mean agreement A_d = X_d / d over the depths d = 1..k
+/// where both rankings are known, k being the length of the shorter one. This
+/// is the p -> 1 limit of RBO's weighted agreement over the top k: as p
+/// approaches 1 the weights of all depths become equal.
+///
+/// # Errors
+///
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if either list is empty
+///
+pub fn average_overlap<Item>(first: &[Item], second: &[Item]) -> Result<f64, RboError>
+where
+    Item: Eq + Hash,
+{
+    let k = first.len().min(second.len());
+    if k == 0 {
+        return Err(RboError::InsufficientData);
+    }
+    // the persistence is irrelevant for the agreements, here and below
+    let agreement = crate::evaluate(&first[..k], &second[..k], 0.5)?.agreement();
+    Ok(agreement.iter().sum::<f64>() / k as f64)
+}
+
+///
+/// The overlap fraction X_k / k and the Jaccard similarity of the two top-k
+/// prefixes at every depth k, as `(k, overlap, jaccard)` triples down to the
+/// length of the longer list. Once the shorter list is exhausted its prefix
+/// stays at its full length.
+///
+/// # Errors
+///
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn overlap_curve<Item>(
+    first: &[Item],
+    second: &[Item],
+) -> Result<Vec<(usize, f64, f64)>, RboError>
+where
+    Item: Eq + Hash,
+{
+    let agreement = crate::evaluate(first, second, 0.5)?.agreement();
+    Ok(agreement
+        .into_iter()
+        .enumerate()
+        .map(|(d, a_d)| {
+            let k = d + 1;
+            let x_k = a_d * k as f64;
+            let union = (k.min(first.len()) + k.min(second.len())) as f64 - x_k;
+            (k, a_d, x_k / union)
+        })
+        .collect())
+}
@@ -0,0 +1,154 @@
+//! Python bindings, built with [maturin](https://www.maturin.rs) into a
+//! Python module named `rbo`:
+//!
+//! ```text
+//! pip install maturin
+//! maturin develop --release
+//! ```
+//!
+//! ```python
+//! import rbo
+//!
+//! result = rbo.rbo(["a", "b", "c"], ["b", "a", "d"], 0.9)
+//! print(result.min, result.residual, result.ext)
+//! print(rbo.rbo_ext(numpy.array([3, 1, 4]), numpy.array([1, 5, 9]), 0.9))
+//! ```
+//!
+//! Rankings are any iterable of `str` or `int` items, such as lists, tuples
+//! or numpy arrays. Invalid persistence values and duplicate items raise a
+//! `ValueError`, the computation itself runs without holding the GIL.
+
+use crate::{Rbo, RboError};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+
+// an item of a Python ranking, numpy strings and integers included
+#[derive(PartialEq, Eq, Hash)]
+enum Item {
+    Str(String),
+    Int(i64),
+}
+
+// the item as it is written in Python, for error messages
+impl std::fmt::Debug for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Str(item) => write!(f, "{item:?}"),
+            Item::Int(item) => write!(f, "{item}"),
+        }
+    }
+}
+
+/// The result of [`rbo`](crate::rbo) as a Python object
+#[pyclass(
+    name = "RboResult",
+    module = "rbo",
+    frozen,
+    get_all,
+    eq,
+    skip_from_py_object
+)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PyRbo {
+    /// the lower bound on RBO
+    pub min: f64,
+    /// the maximum amount RBO can grow beyond `min`
+    pub residual: f64,
+    /// the extrapolated RBO
+    pub ext: f64,
+}
+
+#[pymethods]
+impl PyRbo {
+    fn __repr__(&self) -> String {
+        format!(
+            "RboResult(min={}, residual={}, ext={})",
+            self.min, self.residual, self.ext
+        )
+    }
+}
+
+impl From<Rbo> for PyRbo {
+    fn from(rbo: Rbo) -> Self {
+        Self {
+            min: rbo.min,
+            residual: rbo.residual,
+            ext: rbo.extrapolated,
+        }
+    }
+}
+
+impl From<RboError> for PyErr {
+    fn from(err: RboError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+// the items of a Python ranking
+fn ranking(list: &Bound<'_, PyAny>) -> PyResult<Vec<Item>> {
+    if list.is_instance_of::<PyString>() {
+        // a string is iterable, but almost certainly not meant as a ranking of characters
+        return Err(PyTypeError::new_err(
+            "expected a ranking of items, got a str",
+        ));
+    }
+    list.try_iter()?
+        .map(|item| {
+            let item = item?;
+            if let Ok(item) = item.extract() {
+                Ok(Item::Str(item))
+            } else if let Ok(item) = item.extract() {
+                Ok(Item::Int(item))
+            } else {
+                let kind = item.get_type().name()?;
+                Err(PyTypeError::new_err(format!(
+                    "ranked items must be str or int, got {kind}"
+                )))
+            }
+        })
+        .collect()
+}
+
+// RBO of two Python rankings, computed without holding the GIL
+fn compute(
+    py: Python<'_>,
+    first: &Bound<'_, PyAny>,
+    second: &Bound<'_, PyAny>,
+    p: f64,
+) -> PyResult<Rbo> {
+    let (first, second) = (ranking(first)?, ranking(second)?);
+    let rbo = py.detach(|| crate::rbo(&first, &second, p));
+    Ok(rbo.map_err(|err| err.with_item_debug(&first, &second))?)
+}
+
+/// RBO between the rankings `first` and `second` with persistence `p`
+#[pyfunction(name = "rbo")]
+fn py_rbo(
+    py: Python<'_>,
+    first: &Bound<'_, PyAny>,
+    second: &Bound<'_, PyAny>,
+    p: f64,
+) -> PyResult<PyRbo> {
+    Ok(compute(py, first, second, p)?.into())
+}
+
+/// The extrapolated RBO between the rankings `first` and `second` with persistence `p`
+#[pyfunction(name = "rbo_ext")]
+fn py_rbo_ext(
+    py: Python<'_>,
+    first: &Bound<'_, PyAny>,
+    second: &Bound<'_, PyAny>,
+    p: f64,
+) -> PyResult<f64> {
+    Ok(compute(py, first, second, p)?.extrapolated)
+}
+
+/// The `rbo` Python module
+#[pymodule(name = "rbo")]
+pub fn rbo_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRbo>()?;
+    m.add_function(wrap_pyfunction!(py_rbo, m)?)?;
+    m.add_function(wrap_pyfunction!(py_rbo_ext, m)?)?;
+    Ok(())
+}
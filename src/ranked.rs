@@ -0,0 +1,105 @@
+use crate::incremental::{consume, RboIncremental};
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+///
+/// A ranking that can be read once in rank order without being held in
+/// memory as a whole, such as a memory-mapped file, a database cursor or a
+/// reader of chunked results.
+///
+/// ```
+/// use rbo::RankedList;
+///
+/// // a ranking stored in fixed-size pages, fetched one at a time
+/// struct Paged {
+///     pages: Vec<Vec<u32>>,
+/// }
+///
+/// impl RankedList for Paged {
+///     type Item = u32;
+///     type Iter = std::iter::Flatten<std::vec::IntoIter<Vec<u32>>>;
+///
+///     fn items(self) -> Self::Iter {
+///         self.pages.into_iter().flatten()
+///     }
+/// }
+///
+/// let first = Paged { pages: vec![vec![1, 2], vec![3, 4]] };
+/// let second = Paged { pages: vec![vec![2, 1], vec![4]] };
+/// let rbo = rbo::rbo_ranked(first, second, 0.9).expect("valid rbo");
+/// assert_eq!(rbo, rbo::rbo(&[1, 2, 3, 4], &[2, 1, 4], 0.9).expect("valid rbo"));
+/// ```
+///
+pub trait RankedList {
+    /// The ranked item type
+    type Item: Eq + Hash;
+    /// The iterator over the items
+    type Iter: Iterator<Item = Self::Item>;
+
+    /// The items in rank order
+    fn items(self) -> Self::Iter;
+
+    /// The number of items, if known up front. Used to size the bookkeeping
+    /// of a comparison, the items are never counted.
+    fn len_hint(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<'a, T: Eq + Hash> RankedList for &'a [T] {
+    type Item = &'a T;
+    type Iter = std::slice::Iter<'a, T>;
+
+    fn items(self) -> Self::Iter {
+        self.iter()
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<'a, T: Eq + Hash> RankedList for &'a Vec<T> {
+    type Item = &'a T;
+    type Iter = std::slice::Iter<'a, T>;
+
+    fn items(self) -> Self::Iter {
+        self.iter()
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T: Eq + Hash> RankedList for Vec<T> {
+    type Item = T;
+    type Iter = std::vec::IntoIter<T>;
+
+    fn items(self) -> Self::Iter {
+        self.into_iter()
+    }
+
+    fn len_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+///
+/// RBO between two [`RankedList`]s, read one depth at a time as in
+/// [`rbo_iter`](crate::rbo_iter). Duplicates are detected while reading.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if either ranking yields the same item twice
+///
+pub fn rbo_ranked<A, B>(first: A, second: B, p: f64) -> Result<Rbo, RboError>
+where
+    A: RankedList,
+    B: RankedList<Item = A::Item>,
+{
+    let capacity = first.len_hint().unwrap_or(0) + second.len_hint().unwrap_or(0);
+    let state = RboIncremental::with_capacity(p, capacity)?;
+    consume(state, first.items(), second.items())
+}
@@ -0,0 +1,81 @@
+use crate::{Rbo, RboError};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+/// A ranking stored as a map between items and ranks. Ranks must be
+/// distinct and contiguous but may start at any value, commonly 0 or 1.
+pub trait RankMap {
+    /// The ranked item type
+    type Item: Eq + Hash;
+
+    ///
+    /// The items in rank order.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if ranks are repeated or have gaps
+    ///
+    fn ranking(&self) -> Result<Vec<&Self::Item>, RboError>;
+}
+
+/// item -> rank
+impl<Item: Eq + Hash, S: BuildHasher> RankMap for HashMap<Item, usize, S> {
+    type Item = Item;
+
+    fn ranking(&self) -> Result<Vec<&Item>, RboError> {
+        let Some(start) = self.values().min().copied() else {
+            return Ok(Vec::new());
+        };
+        let mut ranking = vec![None; self.len()];
+        for (item, rank) in self {
+            match ranking.get_mut(rank - start) {
+                Some(slot @ None) => *slot = Some(item),
+                _ => return Err(RboError::InvalidRanks),
+            }
+        }
+        // every slot is filled as there are as many distinct ranks as slots
+        Ok(ranking.into_iter().flatten().collect())
+    }
+}
+
+/// rank -> item
+impl<Item: Eq + Hash> RankMap for BTreeMap<usize, Item> {
+    type Item = Item;
+
+    fn ranking(&self) -> Result<Vec<&Item>, RboError> {
+        let contiguous = self
+            .keys()
+            .zip(self.keys().skip(1))
+            .all(|(a, b)| a + 1 == *b);
+        if !contiguous {
+            return Err(RboError::InvalidRanks);
+        }
+        Ok(self.values().collect())
+    }
+}
+
+///
+/// RBO between two rankings stored as rank maps, see [`RankMap`].
+///
+/// ```
+/// use std::collections::{BTreeMap, HashMap};
+///
+/// let first: HashMap<&str, usize> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+/// let second: BTreeMap<usize, &str> = [(1, "b"), (2, "a"), (3, "c")].into_iter().collect();
+/// let from_ranks = rbo::rbo_from_ranks(&first, &second, 0.9).expect("valid rbo");
+/// assert_eq!(from_ranks, rbo::rbo(&["a", "b", "c"], &["b", "a", "c"], 0.9).expect("valid rbo"));
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if an item appears more than once
+/// - Will return `Err` if ranks are repeated or have gaps
+///
+pub fn rbo_from_ranks<A, B>(first: &A, second: &B, p: f64) -> Result<Rbo, RboError>
+where
+    A: RankMap,
+    B: RankMap<Item = A::Item>,
+{
+    crate::rbo(&first.ranking()?, &second.ranking()?, p)
+}
@@ -0,0 +1,67 @@
+use crate::RboError;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+
+/// The result of [`rbp`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rbp {
+    /// RBP of the ranking as given, counting only the relevant items it holds
+    pub base: f64,
+    /// The weight of the ranks beyond the end of the ranking, which RBP would
+    /// gain if they all held relevant items; base + residual is an upper bound
+    pub residual: f64,
+}
+
+impl Rbp {
+    /// Upper bound on the RBP of any extension of the ranking, `base + residual`
+    #[must_use]
+    pub fn upper_bound(&self) -> f64 {
+        self.base + self.residual
+    }
+}
+
+///
+/// Rank-biased precision (Moffat and Zobel, 2008) of `ranking` given the set
+/// of `relevant` items: the expected rate at which a user who moves from
+/// every rank on to the next with probability `p` finds relevant items,
+/// `(1 - p)` times the sum of `p^(i - 1)` over the ranks `i` of relevant
+/// items. The ranks are weighted with the same powers of `p` as the depths
+/// of [`rbo`](crate::rbo). The residual `p^d` of a ranking of length `d` is
+/// the weight of the unseen ranks beyond it.
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// let relevant: HashSet<_> = ["a", "c"].into_iter().collect();
+/// let rbp = rbo::rbp(&["a", "b", "c"], &relevant, 0.5).expect("valid rbp");
+/// assert_eq!(rbp.base, 0.5 + 0.125);
+/// assert_eq!(rbp.residual, 0.125);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if the ranking contains duplicate items
+///
+pub fn rbp<Item, S>(ranking: &[Item], relevant: &HashSet<Item, S>, p: f64) -> Result<Rbp, RboError>
+where
+    Item: Eq + Hash,
+    S: BuildHasher,
+{
+    crate::state::validate_persistence(p)?;
+    crate::check_duplicates(ranking, 0)?;
+    // p^(i - 1) for every rank i
+    let weights = std::iter::once(1.0).chain(crate::state::powers(p));
+    let found = crate::state::sum(
+        ranking
+            .iter()
+            .zip(weights)
+            .filter(|(item, _)| relevant.contains(*item))
+            .map(|(_, weight)| weight),
+    );
+    Ok(Rbp {
+        base: (1.0 - p) * found,
+        residual: crate::state::power_at(p, ranking.len()),
+    })
+}
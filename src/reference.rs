@@ -0,0 +1,104 @@
+use crate::state::RboState;
+use crate::{Duplicate, Rbo, RboError};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+///
+/// A fixed ranking prepared once for comparisons against many candidates.
+/// The rank of every reference item is kept in a map, so a comparison only
+/// looks up the candidate's items instead of building a seen set for both
+/// lists: an item at rank `r` of the reference and rank `c` of the candidate
+/// adds to the overlap at every depth below `max(r, c)`.
+///
+/// ```
+/// use rbo::ReferenceRanking;
+///
+/// let reference = ReferenceRanking::new(vec!["a", "b", "c", "d"]).expect("no duplicates");
+/// for candidate in [vec!["b", "a", "e"], vec!["d", "c", "b", "a"]] {
+///     let rbo = reference.rbo_against(&candidate, 0.9).expect("valid rbo");
+///     assert_eq!(rbo, rbo::rbo(&["a", "b", "c", "d"], &candidate, 0.9).expect("valid rbo"));
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct ReferenceRanking<Item> {
+    // the 0-based rank of every item of the reference
+    ranks: HashMap<Item, usize>,
+}
+
+impl<Item: Eq + Hash> ReferenceRanking<Item> {
+    ///
+    /// Prepare the items of `ranking`, in rank order
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if the ranking contains duplicate items
+    ///
+    pub fn new(ranking: impl IntoIterator<Item = Item>) -> Result<Self, RboError> {
+        let ranking = ranking.into_iter();
+        let mut ranks = HashMap::with_capacity(ranking.size_hint().0);
+        for (rank, item) in ranking.enumerate() {
+            if let Some(&first) = ranks.get(&item) {
+                return Err(RboError::DuplicatesInList(Duplicate {
+                    list: 0,
+                    indices: vec![first, rank],
+                    item_debug: None,
+                }));
+            }
+            ranks.insert(item, rank);
+        }
+        Ok(Self { ranks })
+    }
+
+    /// The number of items of the reference
+    pub fn len(&self) -> usize {
+        self.ranks.len()
+    }
+
+    /// `true` if the reference has no items
+    pub fn is_empty(&self) -> bool {
+        self.ranks.is_empty()
+    }
+
+    ///
+    /// RBO between the reference, as the first list, and `candidate`. The
+    /// result is the same as that of [`rbo`](crate::rbo).
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if an item of the reference occurs more than once
+    ///   in the candidate. Repeated items that do not occur in the reference
+    ///   never match and are not detected.
+    ///
+    pub fn rbo_against(&self, candidate: &[Item], p: f64) -> Result<Rbo, RboError> {
+        let mut state = RboState::<()>::constant_memory(p)?;
+        let depth = self.len().max(candidate.len());
+        let paired = self.len().min(candidate.len());
+        // the number of items matched at every depth, and the candidate rank
+        // every reference item was matched at
+        let mut matches = vec![0.0; depth];
+        let mut matched_at: Vec<Option<usize>> = vec![None; self.len()];
+        for (rank, item) in candidate.iter().enumerate() {
+            let Some(&reference_rank) = self.ranks.get(item) else {
+                continue;
+            };
+            if let Some(first) = matched_at[reference_rank] {
+                let indices = (first..candidate.len())
+                    .filter(|&i| candidate[i] == *item)
+                    .collect();
+                return Err(RboError::DuplicatesInList(Duplicate {
+                    list: 1,
+                    indices,
+                    item_debug: None,
+                }));
+            }
+            matched_at[reference_rank] = Some(rank);
+            matches[rank.max(reference_rank)] += 1.0;
+        }
+        for (d, matches) in matches.into_iter().enumerate() {
+            state.record(matches, d < paired);
+        }
+        state.into_result()
+    }
+}
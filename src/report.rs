@@ -0,0 +1,322 @@
+//! Self-contained HTML reports over many RBO evaluations.
+//!
+//! A [`Report`] collects evaluations grouped by e.g. system pair, with one
+//! entry per query, and renders them into a single HTML document containing a
+//! summary table, per-group breakdowns and inline SVG agreement-curve charts.
+//! The output has no external dependencies (scripts, stylesheets or fonts) so
+//! it can be mailed around or archived as is.
+//!
+//! ```
+//! use rbo::report::Report;
+//!
+//! let first = "abcdefg".chars().collect::<Vec<_>>();
+//! let second = "abdcgef".chars().collect::<Vec<_>>();
+//! let mut report = Report::new("weekly ranking review");
+//! report.add_pair("prod vs staging", "query 1", &first, &second, 0.9).expect("valid rbo");
+//! let html = report.render();
+//! assert!(html.contains("prod vs staging"));
+//! ```
+
+use crate::{Rbo, RboError};
+use std::fmt::Write;
+use std::hash::Hash;
+
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 240.0;
+const CHART_MARGIN: f64 = 32.0;
+
+#[derive(Debug)]
+struct Entry {
+    label: String,
+    rbo: Rbo,
+    // agreement A_d at every depth, empty if unknown
+    agreement: Vec<f64>,
+}
+
+#[derive(Debug)]
+struct Group {
+    name: String,
+    entries: Vec<Entry>,
+}
+
+/// A collection of RBO evaluations that can be rendered as an HTML report
+#[derive(Debug)]
+pub struct Report {
+    title: String,
+    groups: Vec<Group>,
+}
+
+impl Report {
+    /// Create an empty report with the given title
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            groups: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, group: &str, entry: Entry) {
+        match self.groups.iter_mut().find(|g| g.name == group) {
+            Some(g) => g.entries.push(entry),
+            None => self.groups.push(Group {
+                name: group.to_string(),
+                entries: vec![entry],
+            }),
+        }
+    }
+
+    /// Add an already computed RBO result to `group` (e.g. a system pair) under
+    /// `label` (e.g. a query id). No agreement curve is drawn for the entry.
+    pub fn add(&mut self, group: &str, label: &str, rbo: Rbo) {
+        self.push(
+            group,
+            Entry {
+                label: label.to_string(),
+                rbo,
+                agreement: Vec::new(),
+            },
+        );
+    }
+
+    /// Compute RBO between `first` and `second` and add it, together with its
+    /// agreement curve, to `group` under `label`.
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if lists contain duplicate items
+    ///
+    pub fn add_pair<Item>(
+        &mut self,
+        group: &str,
+        label: &str,
+        first: &[Item],
+        second: &[Item],
+        p: f64,
+    ) -> Result<(), RboError>
+    where
+        Item: Eq + Hash,
+    {
+        let state = crate::evaluate(first, second, p)?;
+        let agreement = state.agreement();
+        self.push(
+            group,
+            Entry {
+                label: label.to_string(),
+                rbo: state.into_result()?,
+                agreement,
+            },
+        );
+        Ok(())
+    }
+
+    /// Render the report as a self-contained HTML document
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut html = String::new();
+        // writing into a String can not fail
+        self.render_into(&mut html).expect("write to string");
+        html
+    }
+
+    /// Render the report and write it to `writer`
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if writing to `writer` fails
+    ///
+    pub fn write_to<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self.render().as_bytes())
+    }
+
+    fn render_into(&self, out: &mut String) -> std::fmt::Result {
+        let title = escape(&self.title);
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>{}</title>", title)?;
+        writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+        writeln!(out, "<h1>{}</h1>", title)?;
+
+        writeln!(out, "<h2>Summary</h2>\n<table>")?;
+        writeln!(
+            out,
+            "<tr><th>group</th><th>comparisons</th><th>mean min</th><th>mean residual</th>\
+             <th>mean extrapolated</th><th>median extrapolated</th>\
+             <th>lowest extrapolated</th><th>highest extrapolated</th></tr>"
+        )?;
+        for group in &self.groups {
+            let mut ext: Vec<f64> = group.entries.iter().map(|e| e.rbo.extrapolated).collect();
+            ext.sort_by(f64::total_cmp);
+            writeln!(
+                out,
+                "<tr><td><a href=\"#group-{}\">{}</a></td><td>{}</td><td>{:.4}</td><td>{:.4}</td>\
+                 <td>{:.4}</td><td>{:.4}</td><td>{:.4}</td><td>{:.4}</td></tr>",
+                slug(&group.name),
+                escape(&group.name),
+                group.entries.len(),
+                mean(group.entries.iter().map(|e| e.rbo.min)),
+                mean(group.entries.iter().map(|e| e.rbo.residual)),
+                mean(ext.iter().copied()),
+                median(&ext),
+                ext.first().copied().unwrap_or(f64::NAN),
+                ext.last().copied().unwrap_or(f64::NAN),
+            )?;
+        }
+        writeln!(out, "</table>")?;
+
+        for group in &self.groups {
+            writeln!(
+                out,
+                "<h2 id=\"group-{}\">{}</h2>",
+                slug(&group.name),
+                escape(&group.name)
+            )?;
+            if group.entries.iter().any(|e| !e.agreement.is_empty()) {
+                render_chart(out, group)?;
+            }
+            writeln!(out, "<table>")?;
+            writeln!(
+                out,
+                "<tr><th>label</th><th>min</th><th>residual</th><th>extrapolated</th>\
+                 <th>upper bound</th></tr>"
+            )?;
+            for entry in &group.entries {
+                writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{:.4}</td><td>{:.4}</td><td>{:.4}</td><td>{:.4}</td></tr>",
+                    escape(&entry.label),
+                    entry.rbo.min,
+                    entry.rbo.residual,
+                    entry.rbo.extrapolated,
+                    entry.rbo.upper_bound(),
+                )?;
+            }
+            writeln!(out, "</table>")?;
+        }
+        writeln!(out, "</body>\n</html>")
+    }
+}
+
+// draw the agreement curves of all entries in the group plus their mean
+fn render_chart(out: &mut String, group: &Group) -> std::fmt::Result {
+    let depth = group
+        .entries
+        .iter()
+        .map(|e| e.agreement.len())
+        .max()
+        .unwrap_or(0);
+    let x = |d: usize| {
+        let span = (depth.max(2) - 1) as f64;
+        CHART_MARGIN + (d as f64 / span) * (CHART_WIDTH - 2.0 * CHART_MARGIN)
+    };
+    let y = |a: f64| CHART_HEIGHT - CHART_MARGIN - a * (CHART_HEIGHT - 2.0 * CHART_MARGIN);
+
+    writeln!(
+        out,
+        "<svg viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" role=\"img\">",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    )?;
+    writeln!(
+        out,
+        "<title>agreement by depth</title>\
+         <line class=\"axis\" x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\"/>\
+         <line class=\"axis\" x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{b}\"/>\
+         <text x=\"{m}\" y=\"{t}\">A_d</text>\
+         <text x=\"{r}\" y=\"{l}\" text-anchor=\"end\">depth {depth}</text>",
+        m = CHART_MARGIN,
+        b = y(0.0),
+        r = CHART_WIDTH - CHART_MARGIN,
+        t = CHART_MARGIN - 8.0,
+        l = CHART_HEIGHT - 8.0,
+        depth = depth,
+    )?;
+
+    let mut sum = vec![0.0; depth];
+    let mut count = vec![0usize; depth];
+    for entry in group.entries.iter().filter(|e| !e.agreement.is_empty()) {
+        write!(out, "<polyline class=\"curve\" points=\"")?;
+        for (d, a) in entry.agreement.iter().enumerate() {
+            write!(out, "{:.1},{:.1} ", x(d), y(*a))?;
+            sum[d] += a;
+            count[d] += 1;
+        }
+        writeln!(out, "\"><title>{}</title></polyline>", escape(&entry.label))?;
+    }
+    write!(out, "<polyline class=\"mean\" points=\"")?;
+    for (d, (s, c)) in sum.iter().zip(&count).enumerate() {
+        write!(out, "{:.1},{:.1} ", x(d), y(s / *c as f64))?;
+    }
+    writeln!(out, "\"><title>mean</title></polyline>\n</svg>")
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, n) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    sum / n as f64
+}
+
+// expects sorted values
+fn median(values: &[f64]) -> f64 {
+    match values.len() {
+        0 => f64::NAN,
+        n if n % 2 == 1 => values[n / 2],
+        n => (values[n / 2 - 1] + values[n / 2]) / 2.0,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn slug(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+const STYLE: &str = "\
+body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+th{background:#f0f0f0}\
+svg{display:block;margin-bottom:1em}\
+.axis{stroke:#444;stroke-width:1}\
+.curve{fill:none;stroke:#4a7ebb;stroke-opacity:0.35;stroke-width:1}\
+.mean{fill:none;stroke:#c0392b;stroke-width:2}";
+
+#[cfg(test)]
+mod tests {
+    use super::Report;
+
+    #[test]
+    fn renders_groups_and_escapes_labels() {
+        let first: Vec<char> = "abcdefg".chars().collect();
+        let second: Vec<char> = "gfedcba".chars().collect();
+        let mut report = Report::new("a <b> report");
+        report
+            .add_pair("sys1 vs sys2", "q&1", &first, &second, 0.9)
+            .expect("valid rbo");
+        report.add(
+            "sys1 vs sys3",
+            "q1",
+            crate::rbo(&first, &first, 0.9).expect("valid rbo"),
+        );
+        let html = report.render();
+        assert!(html.contains("<title>a &lt;b&gt; report</title>"));
+        assert!(html.contains("q&amp;1"));
+        assert!(html.contains("id=\"group-sys1-vs-sys3\""));
+        assert_eq!(html.matches("<svg").count(), 1);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{Rbo, RboError, TieVariant};
+use std::hash::Hash;
+
+/// What to do with items sharing the same score in [`rbo_scored`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScoreTies {
+    /// Refuse rankings with tied scores with [`RboError::TiedScores`]
+    #[default]
+    Error,
+    /// Keep tied items together and compare with [`rbo_ties`](crate::rbo_ties)
+    Variant(TieVariant),
+}
+
+// sort by decreasing score and group equal scores
+fn tie_groups<Item>(scored: &[(Item, f64)]) -> Result<Vec<Vec<&Item>>, RboError> {
+    if scored.iter().any(|(_, score)| score.is_nan()) {
+        return Err(RboError::InvalidScore);
+    }
+    let mut sorted: Vec<&(Item, f64)> = scored.iter().collect();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut groups: Vec<Vec<&Item>> = Vec::new();
+    let mut last = None;
+    for (item, score) in sorted {
+        match groups.last_mut() {
+            Some(group) if last == Some(*score) => group.push(item),
+            _ => groups.push(vec![item]),
+        }
+        last = Some(*score);
+    }
+    Ok(groups)
+}
+
+///
+/// RBO between two sets of scored results. Each list is ranked by
+/// decreasing score, and items with equal scores are either refused or kept
+/// tied depending on `ties`. Without ties this is [`rbo`](crate::rbo) over
+/// the sorted items.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if a score is NaN
+/// - Will return `Err` if scores are tied and `ties` is [`ScoreTies::Error`]
+///
+pub fn rbo_scored<Item>(
+    first: &[(Item, f64)],
+    second: &[(Item, f64)],
+    p: f64,
+    ties: ScoreTies,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+{
+    let first = tie_groups(first)?;
+    let second = tie_groups(second)?;
+    let has_ties = |groups: &[Vec<&Item>]| groups.iter().any(|g| g.len() > 1);
+    match ties {
+        ScoreTies::Variant(variant) if has_ties(&first) || has_ties(&second) => {
+            crate::rbo_ties(&first, &second, p, variant)
+        }
+        ScoreTies::Error if has_ties(&first) || has_ties(&second) => Err(RboError::TiedScores),
+        _ => {
+            let first: Vec<&Item> = first.into_iter().flatten().collect();
+            let second: Vec<&Item> = second.into_iter().flatten().collect();
+            crate::rbo(&first, &second, p)
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::matrix::PrefixSignature;
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+// depth of the cheap prefix evaluation used to bound the final score
+const PREFIX_DEPTH: usize = 50;
+// slack for rounding differences between the bound and the extrapolated value
+const BOUND_TOLERANCE: f64 = 1e-6;
+
+// the top items of both lists, down to the same depth. The upper bound of
+// prefixes of equal depth caps the extrapolated RBO of any extension of them,
+// as equation 32 never extrapolates an agreement the residual does not allow.
+// That of uneven prefixes does not, extrapolation may exceed it slightly.
+fn prefixes<'a, Item>(first: &'a [Item], second: &'a [Item]) -> (&'a [Item], &'a [Item]) {
+    let depth = first.len().min(second.len()).min(PREFIX_DEPTH);
+    (&first[..depth], &second[..depth])
+}
+
+///
+/// Find the `m` rankings in `corpus` most similar to `query` by extrapolated
+/// RBO. Returns `(index, rbo)` pairs sorted by decreasing similarity, ties
+/// broken by corpus index.
+///
+/// Every candidate is first evaluated on its top 50 items only, or on as many
+/// as the shorter of it and the query holds. The upper bound `min + residual`
+/// of prefixes of equal depth caps the final score, and candidates whose bound can not beat the current
+/// `m`-th best result are never evaluated in full. Candidates are visited in
+/// order of their prefix bound and [`PrefixSignature`] similarity, so for
+/// large corpora most of the work is typically skipped.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn most_similar<Item, L>(
+    query: &[Item],
+    corpus: &[L],
+    p: f64,
+    m: usize,
+) -> Result<Vec<(usize, Rbo)>, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    crate::state::validate_persistence(p)?;
+    crate::check_duplicates(query, 0)?;
+    let signature = PrefixSignature::new(query, PREFIX_DEPTH);
+    let candidates = corpus
+        .iter()
+        .enumerate()
+        .map(|(index, list)| candidate(query, &signature, index, list.as_ref(), p))
+        .collect::<Result<Vec<_>, RboError>>()?;
+
+    let mut best: Vec<(usize, Rbo)> = Vec::with_capacity(m + 1);
+    for (index, upper_bound, _) in sorted(candidates) {
+        if cannot_improve(&best, m, upper_bound) {
+            // candidates are sorted by their bound, nothing left can make it
+            break;
+        }
+        let rbo = crate::rbo(query, corpus[index].as_ref(), p)?;
+        insert(&mut best, m, index, rbo);
+    }
+    Ok(best)
+}
+
+///
+/// Like [`most_similar`], but evaluates the candidates concurrently on the
+/// rayon thread pool. Candidates are evaluated in rounds of a few per thread
+/// in the order of their prefix bound, so the bound still cuts off the search
+/// between rounds. The result is the same as that of [`most_similar`], for
+/// any number of threads.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+#[cfg(feature = "rayon")]
+pub fn most_similar_par<Item, L>(
+    query: &[Item],
+    corpus: &[L],
+    p: f64,
+    m: usize,
+) -> Result<Vec<(usize, Rbo)>, RboError>
+where
+    Item: Eq + Hash + Sync,
+    L: AsRef<[Item]> + Sync,
+{
+    use rayon::prelude::*;
+
+    crate::state::validate_persistence(p)?;
+    crate::check_duplicates(query, 0)?;
+    let signature = PrefixSignature::new(query, PREFIX_DEPTH);
+    let candidates = corpus
+        .par_iter()
+        .enumerate()
+        .map(|(index, list)| candidate(query, &signature, index, list.as_ref(), p))
+        .collect::<Vec<_>>();
+    let candidates = crate::batch::in_order(candidates)?;
+
+    let round = CANDIDATES_PER_THREAD * rayon::current_num_threads();
+    let mut best: Vec<(usize, Rbo)> = Vec::with_capacity(m + 1);
+    for chunk in sorted(candidates).chunks(round) {
+        let evaluated = chunk
+            .par_iter()
+            .filter(|&&(_, upper_bound, _)| !cannot_improve(&best, m, upper_bound))
+            .map(|&(index, _, _)| Ok((index, crate::rbo(query, corpus[index].as_ref(), p)?)))
+            .collect::<Vec<_>>();
+        let evaluated = crate::batch::in_order(evaluated)?;
+        if evaluated.is_empty() {
+            // candidates are sorted by their bound, nothing left can make it
+            break;
+        }
+        for (index, rbo) in evaluated {
+            insert(&mut best, m, index, rbo);
+        }
+    }
+    Ok(best)
+}
+
+///
+/// Like [`most_similar_par`], but evaluates the candidates on `pool` instead
+/// of the global rayon thread pool. The rounds are sized to the threads of
+/// `pool`.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+#[cfg(feature = "rayon")]
+pub fn most_similar_par_in<Item, L>(
+    pool: &rayon::ThreadPool,
+    query: &[Item],
+    corpus: &[L],
+    p: f64,
+    m: usize,
+) -> Result<Vec<(usize, Rbo)>, RboError>
+where
+    Item: Eq + Hash + Sync,
+    L: AsRef<[Item]> + Sync,
+{
+    pool.install(|| most_similar_par(query, corpus, p, m))
+}
+
+// candidates evaluated by each thread in one round of `most_similar_par`
+#[cfg(feature = "rayon")]
+const CANDIDATES_PER_THREAD: usize = 4;
+
+// the corpus index, prefix bound and prefix similarity of a candidate
+fn candidate<Item: Eq + Hash>(
+    query: &[Item],
+    signature: &PrefixSignature,
+    index: usize,
+    list: &[Item],
+    p: f64,
+) -> Result<(usize, f64, f64), RboError> {
+    let (query_prefix, list_prefix) = prefixes(query, list);
+    let bound = crate::rbo(query_prefix, list_prefix, p)?;
+    let similarity = signature.jaccard(&PrefixSignature::new(list, PREFIX_DEPTH));
+    Ok((index, bound.upper_bound(), similarity))
+}
+
+// candidates in the order they are evaluated in, most promising first
+fn sorted(mut candidates: Vec<(usize, f64, f64)>) -> Vec<(usize, f64, f64)> {
+    candidates.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then(b.2.total_cmp(&a.2))
+            .then(a.0.cmp(&b.0))
+    });
+    candidates
+}
+
+// whether a candidate bounded by `upper_bound` can not enter the `m` best
+fn cannot_improve(best: &[(usize, Rbo)], m: usize, upper_bound: f64) -> bool {
+    m == 0 || (best.len() == m && upper_bound + BOUND_TOLERANCE < best[m - 1].1.extrapolated)
+}
+
+// keep `best` the `m` most similar candidates so far, ties broken by index
+fn insert(best: &mut Vec<(usize, Rbo)>, m: usize, index: usize, rbo: Rbo) {
+    let position = best.partition_point(|(i, r)| {
+        r.extrapolated > rbo.extrapolated || (r.extrapolated == rbo.extrapolated && *i < index)
+    });
+    if position < m {
+        best.insert(position, (index, rbo));
+        best.truncate(m);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Rbo;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn matches_exhaustive_search() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let base: Vec<u32> = (0..120).collect();
+        let corpus: Vec<Vec<u32>> = (0..40)
+            .map(|i| {
+                let mut list = base.clone();
+                // perturb the head by varying amounts
+                list[..(i * 3).min(120)].shuffle(&mut rng);
+                list
+            })
+            .collect();
+        let query = base.clone();
+        let found = super::most_similar(&query, &corpus, 0.95, 5).expect("valid rbo");
+
+        let mut all: Vec<(usize, f64)> = corpus
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                (
+                    i,
+                    crate::rbo(&query, l, 0.95).expect("valid rbo").extrapolated,
+                )
+            })
+            .collect();
+        all.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        let expected: Vec<usize> = all.iter().take(5).map(|(i, _)| *i).collect();
+        let found: Vec<usize> = found.iter().map(|(i, _)| *i).collect();
+        assert_eq!(found, expected);
+    }
+
+    // the `m` best of `corpus` by evaluating every ranking in full
+    fn brute_force(query: &[u32], corpus: &[Vec<u32>], p: f64, m: usize) -> Vec<(usize, Rbo)> {
+        let mut all: Vec<(usize, Rbo)> = corpus
+            .iter()
+            .enumerate()
+            .map(|(i, l)| (i, crate::rbo(query, l, p).expect("valid rbo")))
+            .collect();
+        all.sort_by(|a, b| {
+            b.1.extrapolated
+                .total_cmp(&a.1.extrapolated)
+                .then(a.0.cmp(&b.0))
+        });
+        all.truncate(m);
+        all
+    }
+
+    #[test]
+    fn matches_exhaustive_search_on_uneven_lists() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(17);
+        let mut domain: Vec<u32> = (0..100).collect();
+        for _ in 0..100 {
+            let p = rng.gen_range(0.5..0.99);
+            let mut random_list = |rng: &mut rand::rngs::StdRng| {
+                domain.shuffle(rng);
+                domain[..rng.gen_range(1..80)].to_vec()
+            };
+            let query = random_list(&mut rng);
+            let corpus: Vec<Vec<u32>> = (0..30).map(|_| random_list(&mut rng)).collect();
+            let signature = super::PrefixSignature::new(&query, super::PREFIX_DEPTH);
+            for (index, list) in corpus.iter().enumerate() {
+                let (_, bound, _) =
+                    super::candidate(&query, &signature, index, list, p).expect("valid rbo");
+                let full = crate::rbo(&query, list, p).expect("valid rbo");
+                assert!(full.extrapolated <= bound + 1e-12);
+            }
+            for m in [1, 3] {
+                let expected = brute_force(&query, &corpus, p, m);
+                let found = super::most_similar(&query, &corpus, p, m).expect("valid rbo");
+                assert_eq!(found, expected);
+                #[cfg(feature = "rayon")]
+                assert_eq!(
+                    super::most_similar_par(&query, &corpus, p, m).expect("valid rbo"),
+                    expected
+                );
+            }
+        }
+
+        // a candidate longer than the prefix that extrapolates above the upper
+        // bound of uneven prefixes, and a copy of it with one more item of the
+        // query below the prefix, which shares its prefix bound but scores higher
+        let mut found = 0;
+        for _ in 0..3000 {
+            let p = rng.gen_range(0.7..0.99);
+            domain.shuffle(&mut rng);
+            let query = domain[..rng.gen_range(1..80)].to_vec();
+            domain.shuffle(&mut rng);
+            let list = domain[..rng.gen_range(super::PREFIX_DEPTH..80)].to_vec();
+            let Some(&extra) = query.iter().find(|item| !list.contains(item)) else {
+                continue;
+            };
+            let uneven = &query[..query.len().min(super::PREFIX_DEPTH)];
+            let prefix = crate::rbo(uneven, &list[..super::PREFIX_DEPTH], p);
+            let full = crate::rbo(&query, &list, p).expect("valid rbo");
+            if full.extrapolated <= prefix.expect("valid rbo").upper_bound() {
+                continue;
+            }
+            found += 1;
+            let mut longer = list.clone();
+            longer.push(extra);
+            let corpus = vec![list, longer];
+            let expected = brute_force(&query, &corpus, p, 1);
+            assert_eq!(
+                super::most_similar(&query, &corpus, p, 1).expect("valid rbo"),
+                expected
+            );
+            #[cfg(feature = "rayon")]
+            assert_eq!(
+                super::most_similar_par(&query, &corpus, p, 1).expect("valid rbo"),
+                expected
+            );
+        }
+        assert!(found > 0);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_search_matches_sequential() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let base: Vec<u32> = (0..80).collect();
+        let corpus: Vec<Vec<u32>> = (0..200)
+            .map(|i| {
+                let mut list = base.clone();
+                list[..(i % 80)].shuffle(&mut rng);
+                list
+            })
+            .collect();
+        for m in [0, 1, 7, 250] {
+            assert_eq!(
+                super::most_similar_par(&base, &corpus, 0.9, m).expect("valid rbo"),
+                super::most_similar(&base, &corpus, 0.9, m).expect("valid rbo")
+            );
+        }
+        assert!(super::most_similar_par(&base, &corpus, 1.0, 3).is_err());
+    }
+}
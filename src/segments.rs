@@ -0,0 +1,124 @@
+use crate::state::MAX_SERIES_DEPTH;
+use crate::{Rbo, RboError};
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+// the overlap X_d of the observed items of the top d ranks, for d = 0..=depth
+fn known_overlap<'a, Item: Eq + Hash>(
+    first: &'a [Item],
+    second: &'a [Item],
+    depth: usize,
+) -> Vec<f64> {
+    let mut pending: HashSet<&'a Item> = HashSet::new();
+    let mut overlap = Vec::with_capacity(depth + 1);
+    overlap.push(0.0);
+    for d in 0..depth {
+        let mut x_d = overlap[d];
+        for item in [first.get(d), second.get(d)].into_iter().flatten() {
+            if !pending.remove(item) {
+                pending.insert(item);
+            } else {
+                x_d += 1.0;
+            }
+        }
+        overlap.push(x_d);
+    }
+    overlap
+}
+
+///
+/// RBO restricted to segments of ranks, e.g. `1..=10`, `11..=50` and
+/// `51..=200`, to see whether two rankings agree at the head but diverge
+/// further down, which a single score hides. Each segment is scored like
+/// [`rbo`](crate::rbo) with the weights of its depths renormalized to sum to
+/// 1: depth `d` of segment `a..=b` is weighted by `p^(d - a)` over the sum of
+/// those weights, so every segment is top-weighted from its own first rank.
+/// Ranks count from 1 and the segments may overlap or leave gaps.
+///
+/// Within a segment `min` assumes that the unseen items beyond the end of
+/// a list add no overlap, `min + residual` that each of them adds one and
+/// `extrapolated` that the agreement continues as in [`rbo`](crate::rbo).
+/// Segments fully within both lists therefore have no residual.
+///
+/// ```
+/// let first = ["a", "b", "c", "d", "e", "f"];
+/// let second = ["a", "b", "c", "f", "e", "d"];
+/// let scores = rbo::rbo_segments(&first, &second, 0.9, &[1..=3, 4..=6]).expect("valid rbo");
+/// assert_eq!(scores[0].min, 1.0);
+/// assert!(scores[1].min < scores[0].min);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if a segment is empty or starts at rank 0
+/// - Will return `Err` if a segment ends beyond the depth limit
+///
+pub fn rbo_segments<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    segments: &[RangeInclusive<usize>],
+) -> Result<Vec<Rbo>, RboError>
+where
+    Item: Eq + Hash,
+{
+    crate::state::validate_persistence(p)?;
+    crate::check_duplicates(first, 0)?;
+    crate::check_duplicates(second, 1)?;
+    if segments
+        .iter()
+        .any(|segment| segment.is_empty() || *segment.start() == 0)
+    {
+        return Err(RboError::InvalidTarget);
+    }
+    let depth = segments.iter().map(|segment| *segment.end()).max();
+    let depth = depth.unwrap_or(0);
+    if depth > MAX_SERIES_DEPTH {
+        return Err(RboError::DepthLimitExceeded(depth));
+    }
+    let overlap = known_overlap(first, second, depth);
+    let (s, l) = if first.len() <= second.len() {
+        (first.len(), second.len())
+    } else {
+        (second.len(), first.len())
+    };
+    let (x_s, x_l) = (overlap[s.min(depth)], overlap[l.min(depth)]);
+    // the lowest, highest and extrapolated agreement at depth d
+    let agreement = |d: usize| {
+        let x_d = overlap[d];
+        let unseen = (d - d.min(first.len()) + d - d.min(second.len())) as f64;
+        let upper = (x_d + unseen).min(d as f64);
+        let extrapolated = if d <= s {
+            x_d / d as f64
+        } else if d <= l {
+            x_d / d as f64 + x_s * (d - s) as f64 / (s * d) as f64
+        } else {
+            (x_l - x_s) / l as f64 + x_s / s as f64
+        };
+        (x_d / d as f64, upper / d as f64, extrapolated)
+    };
+    let score = |segment: &RangeInclusive<usize>| {
+        // p^(d - a) for every depth d of the segment a..=b
+        let weights: Vec<f64> = std::iter::once(1.0)
+            .chain(crate::state::powers(p))
+            .take(segment.end() - segment.start() + 1)
+            .collect();
+        let total = crate::state::sum(weights.iter().copied());
+        let (mut min, mut residual, mut extrapolated) = (Vec::new(), Vec::new(), Vec::new());
+        for (d, weight) in segment.clone().zip(&weights) {
+            let (low, high, expected) = agreement(d);
+            min.push(weight * low);
+            residual.push(weight * (high - low));
+            extrapolated.push(weight * expected);
+        }
+        Rbo {
+            min: crate::state::sum(min) / total,
+            residual: crate::state::sum(residual) / total,
+            extrapolated: crate::state::sum(extrapolated) / total,
+        }
+    };
+    Ok(segments.iter().map(score).collect())
+}
@@ -0,0 +1,63 @@
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+/// How RBO responds to a change of the persistence parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboSensitivity {
+    /// The persistence the derivatives are taken at
+    pub p: f64,
+    /// The RBO result at `p`
+    pub rbo: Rbo,
+    /// d min / dp
+    pub d_min: f64,
+    /// d residual / dp
+    pub d_residual: f64,
+    /// d extrapolated / dp
+    pub d_extrapolated: f64,
+}
+
+///
+/// The sensitivity of RBO to the choice of `p`, estimated by a central
+/// finite difference with step `delta` (one-sided when `p - delta` or
+/// `p + delta` falls outside the valid range). The lists are walked once and
+/// only the final sums are recomputed for the neighbouring values of `p`.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if `delta` is not positive or too large to stay in the valid range
+/// - Will return `Err` if lists contain duplicate items
+///
+pub fn rbo_sensitivity<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    delta: f64,
+) -> Result<RboSensitivity, RboError>
+where
+    Item: Eq + Hash,
+{
+    crate::state::validate_persistence(p)?;
+    if delta.is_nan() || delta <= 0.0 {
+        return Err(RboError::InvalidTarget);
+    }
+    let low = if p - delta >= 0.0 { p - delta } else { p };
+    let high = if p + delta < 1.0 { p + delta } else { p };
+    if low == high {
+        return Err(RboError::InvalidTarget);
+    }
+    let state = crate::evaluate(first, second, p)?;
+    let (below, above) = (
+        state.result_with_persistence(low)?,
+        state.result_with_persistence(high)?,
+    );
+    let slope = |f: fn(&Rbo) -> f64| (f(&above) - f(&below)) / (high - low);
+    Ok(RboSensitivity {
+        p,
+        d_min: slope(|r| r.min),
+        d_residual: slope(|r| r.residual),
+        d_extrapolated: slope(|r| r.extrapolated),
+        rbo: state.into_result()?,
+    })
+}
@@ -1,124 +1,651 @@
+use num_traits::Float;
+use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 
 const VALID_P_RANGE: std::ops::Range<f64> = 0.0..1.0;
 
-pub(crate) struct RboState<'a, Item: Eq + Hash> {
-    // the items we have seen so far
-    seen: HashSet<&'a Item>,
+// The most depths a series over depths beyond the end of the lists is summed
+// to, a few seconds of work. Series over the depths of the lists themselves
+// are bounded by their length instead.
+pub(crate) const MAX_SERIES_DEPTH: usize = 1 << 30;
+
+// how far rounding errors may push a component of a result outside [0, 1]
+pub(crate) const ROUNDING_TOLERANCE: f64 = 1e-9;
+
+// ensure the persistence parameter is in the valid range
+pub(crate) fn validate_persistence(p: f64) -> Result<(), crate::RboError> {
+    if VALID_P_RANGE.contains(&p) {
+        Ok(())
+    } else {
+        Err(crate::RboError::InvalidPersistance)
+    }
+}
+
+// `K` identifies items, usually a reference into the ranked lists. `O` is
+// called with the depth, the overlap and the extrapolated RBO so far after
+// every update. `S` hashes the items of the seen set.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "K: serde::Serialize",
+        deserialize = "K: serde::Deserialize<'de> + Eq + Hash, S: BuildHasher + Default"
+    ))
+)]
+pub(crate) struct RboState<K, O = fn(usize, f64, f64), S = RandomState> {
+    // the items we have seen so far in one list but not yet in the other
+    seen: HashSet<K, S>,
     // depth is the current depth, counting from 1.
     depth_long: f64,
     // depth is the current depth, counting from 1.
     depth_short: f64,
     // the current overlap.
     cur_overlap: f64,
-    // the current overlap (X_d in the paper).
-    overlap: Vec<f64>,
+    // the overlap at every depth (X_d in the paper), unless only the result
+    // for `persistence` is needed and memory is kept constant
+    overlap: Option<Vec<f64>>,
     // the p value being used.
     persistence: f64,
+    // running sums of equation 32 for `persistence`, see `partial_extrapolated`
+    seen_sum: Compensated,
+    tail_sum: Compensated,
+    p_l: f64,
+    x_s: f64,
+    // the overlap at depth 1, all there is to RBO for p = 0
+    #[cfg_attr(feature = "serde", serde(default))]
+    x_1: f64,
+    // running sums of equation 11 for `persistence` over the first `depth_short` depths
+    min_weighted: Compensated,
+    min_harmonic: Compensated,
+    // the per-depth observer, if any
+    #[cfg_attr(feature = "serde", serde(skip))]
+    observer: Option<O>,
 }
 
-impl<'a, Item: Eq + Hash> RboState<'a, Item> {
+impl<K: Eq + Hash> RboState<K> {
     // Initialize the RBO state with persistance `p`
     pub(crate) fn with_persistence(p: f64) -> Result<Self, crate::RboError> {
-        if !VALID_P_RANGE.contains(&p) {
-            return Err(crate::RboError::InvalidPersistance);
-        }
+        Self::new(p, Some(vec![0.0]), RandomState::new())
+    }
+
+    // Like `with_persistence`, but the overlap at each depth is not kept, so only
+    // the result for `p` is available
+    pub(crate) fn constant_memory(p: f64) -> Result<Self, crate::RboError> {
+        Self::new(p, None, RandomState::new())
+    }
+}
+
+impl<K: Eq + Hash, S: BuildHasher> RboState<K, fn(usize, f64, f64), S> {
+    // Like `constant_memory`, hashing items with `hasher`
+    pub(crate) fn constant_memory_with_hasher(p: f64, hasher: S) -> Result<Self, crate::RboError> {
+        Self::new(p, None, hasher)
+    }
+
+    fn new(p: f64, overlap: Option<Vec<f64>>, hasher: S) -> Result<Self, crate::RboError> {
+        validate_persistence(p)?;
         Ok(Self {
-            seen: HashSet::with_capacity(4096),
+            seen: HashSet::with_capacity_and_hasher(4096, hasher),
             depth_long: 0.0,
             depth_short: 0.0,
             cur_overlap: 0.0,
-            overlap: vec![0.0],
+            overlap,
             persistence: p,
+            seen_sum: Compensated::default(),
+            tail_sum: Compensated::default(),
+            p_l: 1.0,
+            x_s: 0.0,
+            x_1: 0.0,
+            min_weighted: Compensated::default(),
+            min_harmonic: Compensated::default(),
+            observer: None,
         })
     }
+}
 
+impl<K, O, S> RboState<K, O, S> {
+    // start over with persistence `p`, keeping the memory of the seen set
+    pub(crate) fn reset(&mut self, p: f64) -> Result<(), crate::RboError> {
+        validate_persistence(p)?;
+        self.seen.clear();
+        self.depth_long = 0.0;
+        self.depth_short = 0.0;
+        self.cur_overlap = 0.0;
+        if let Some(overlap) = self.overlap.as_mut() {
+            overlap.truncate(1);
+        }
+        self.persistence = p;
+        self.seen_sum = Compensated::default();
+        self.tail_sum = Compensated::default();
+        self.p_l = 1.0;
+        self.x_s = 0.0;
+        self.x_1 = 0.0;
+        self.min_weighted = Compensated::default();
+        self.min_harmonic = Compensated::default();
+        Ok(())
+    }
+}
+
+impl<K, S> RboState<K, fn(usize, f64, f64), S> {
+    // Call `observer` with the depth, overlap and extrapolated RBO after every update
+    pub(crate) fn with_observer<O>(self, observer: O) -> RboState<K, O, S> {
+        RboState {
+            seen: self.seen,
+            depth_long: self.depth_long,
+            depth_short: self.depth_short,
+            cur_overlap: self.cur_overlap,
+            overlap: self.overlap,
+            persistence: self.persistence,
+            seen_sum: self.seen_sum,
+            tail_sum: self.tail_sum,
+            p_l: self.p_l,
+            x_s: self.x_s,
+            x_1: self.x_1,
+            min_weighted: self.min_weighted,
+            min_harmonic: self.min_harmonic,
+            observer: Some(observer),
+        }
+    }
+}
+
+impl<K: Eq + Hash, O: FnMut(usize, f64, f64), S: BuildHasher> RboState<K, O, S> {
     // Update the RBO state with two new elements.
-    pub(crate) fn update(&mut self, first: &'a Item, second: Option<&'a Item>) {
-        match second.map(|s| s.eq(first)) {
-            Some(true) => {
-                self.depth_short += 1.0;
-                self.cur_overlap += 1.0;
-            }
-            Some(false) => {
-                self.depth_short += 1.0;
-                for item in [first, second.unwrap()] {
-                    if self.seen.remove(item) {
+    pub(crate) fn update(&mut self, first: K, second: Option<K>) {
+        let paired = second.is_some();
+        let mut matches = 0.0;
+        match second {
+            Some(second) if second == first => matches += 1.0,
+            Some(second) => {
+                for item in [first, second] {
+                    if self.seen.remove(&item) {
                         // have we seen this before
-                        self.cur_overlap += 1.0;
+                        matches += 1.0;
                     } else {
                         self.seen.insert(item);
                     }
                 }
             }
             None => {
-                if self.seen.remove(first) {
+                if self.seen.remove(&first) {
                     // have we seen this before
-                    self.cur_overlap += 1.0;
+                    matches += 1.0;
                 }
             }
         }
-        self.overlap.push(self.cur_overlap);
+        self.record(matches, paired);
+    }
+}
+
+impl<K, O: FnMut(usize, f64, f64), S> RboState<K, O, S> {
+    // Advance one depth, where `matches` items of this depth matched an item
+    // of the other list and `paired` tells whether both lists reached it.
+    pub(crate) fn record(&mut self, matches: f64, paired: bool) {
+        if paired {
+            self.depth_short += 1.0;
+        }
+        self.cur_overlap += matches;
+        if let Some(overlap) = self.overlap.as_mut() {
+            overlap.push(self.cur_overlap);
+        }
         self.depth_long += 1.0;
+
+        let d = self.depth_long;
+        self.p_l = next_power(self.persistence, d as usize, self.p_l);
+        self.seen_sum.add(self.cur_overlap * self.p_l / d);
+        if paired {
+            if d == 1.0 {
+                self.x_1 = self.cur_overlap;
+            }
+            self.x_s = self.cur_overlap;
+            self.min_weighted.add(self.cur_overlap * self.p_l / d);
+            self.min_harmonic.add(self.p_l / d);
+        } else {
+            let s = self.depth_short;
+            self.tail_sum.add(self.x_s * (d - s) / (s * d) * self.p_l);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            depth = d as usize,
+            overlap = self.cur_overlap,
+            paired,
+            "depth"
+        );
+        if self.observer.is_some() {
+            let extrapolated = self.partial_extrapolated();
+            if let Some(observer) = self.observer.as_mut() {
+                observer(d as usize, self.cur_overlap, extrapolated);
+            }
+        }
     }
+}
 
-    // compute quation 30 for RBO_res
-    fn compute_residual(&mut self) -> f64 {
-        let s = self.depth_short;
-        let us = s as usize;
-        let l = self.depth_long;
-        let ul = l as usize;
-        let x_l = self.cur_overlap;
-        // the rank at which maximum agreement becomes 1
-        let f = s + l - x_l;
+impl<K, O, S> RboState<K, O, S> {
+    // equation 32 for `persistence` from the running sums, in O(1)
+    fn partial_extrapolated(&self) -> f64 {
         let p = self.persistence;
-        let uf = f as usize;
-        let sum_s: f64 = (us + 1..=uf).map(|d| p.powf(d as f64) / d as f64).sum();
-        let sum_l: f64 = (ul + 1..=uf).map(|d| p.powf(d as f64) / d as f64).sum();
-        let sum_t: f64 = (1..=uf).map(|i| (p.powf(i as f64) / i as f64)).sum();
-        let p_s = p.powf(s);
-        let p_l = p.powf(l);
-        let p_f = p.powf(f);
-        let ln_1p = (1.0 / (1.0 - p)).ln();
-        p_s + p_l - p_f - ((1.0 - p) / p) * (s * sum_s + l * sum_l + x_l * (ln_1p - sum_t))
+        if p == 0.0 {
+            return zero_persistence(self.x_1, self.depth_short as usize).extrapolated;
+        }
+        let (s, l) = (self.depth_short, self.depth_long);
+        let last = ((self.cur_overlap - self.x_s) / l + self.x_s / s) * self.p_l;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            seen = self.seen_sum.value(),
+            tail = self.tail_sum.value(),
+            last,
+            "extrapolated sums"
+        );
+        (1.0 - p) / p * (self.seen_sum.value() + self.tail_sum.value()) + last
     }
 
-    // equation 11 in the paper
-    fn compute_min(&self) -> f64 {
+    // equation 11 for `persistence` from the running sums, in O(1). The term of
+    // depth k itself cancels out of the sum, so it may be included.
+    fn partial_min(&self) -> f64 {
         let p = self.persistence;
-        let k = self.depth_short as usize;
-        let x_k = self.overlap[k];
-        let x_d = &self.overlap;
-        let other: f64 = (1..k)
-            .map(|d| (x_d[d] - x_k) * p.powf(d as f64) / d as f64)
-            .sum();
+        if p == 0.0 {
+            return zero_persistence(self.x_1, self.depth_short as usize).min;
+        }
+        let x_k = self.x_s;
+        let other = self.min_weighted.value() - x_k * self.min_harmonic.value();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            weighted = self.min_weighted.value(),
+            harmonic = self.min_harmonic.value(),
+            x_k,
+            "min sums"
+        );
         (1.0 - p) / p * (other - (x_k * (1.0 - p).ln()))
     }
 
+    // compute quation 30 for RBO_res
+    fn compute_residual(&self, p: f64) -> f64 {
+        residual(self.depth_short, self.depth_long, self.cur_overlap, p)
+    }
+
+    // equation 11 in the paper
+    fn compute_min(&self, p: f64) -> f64 {
+        min(self.overlap(), self.depth_short as usize, p)
+    }
+
     // equation 32 in the paper
-    fn compute_extrapolated(&self) -> f64 {
-        let p = self.persistence;
+    fn compute_extrapolated(&self, p: f64) -> f64 {
+        let s = self.depth_short as usize;
         let l = self.depth_long as usize;
-        let p_l = p.powf(l as f64);
+        extrapolated(self.overlap(), s, l, p)
+    }
+
+    // refuse a result that non-finite intermediate sums or rounding errors made
+    // meaningless, e.g. for `p` close to 0 where `(1 - p) / p` amplifies them.
+    // Nothing is extrapolated from an empty list, that stays NaN.
+    fn checked(&self, rbo: crate::Rbo) -> Result<crate::Rbo, crate::RboError> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            min = rbo.min,
+            residual = rbo.residual,
+            extrapolated = rbo.extrapolated,
+            "result"
+        );
+        let valid = -ROUNDING_TOLERANCE..=1.0 + ROUNDING_TOLERANCE;
+        let extrapolated = self.depth_short == 0.0 || valid.contains(&rbo.extrapolated);
+        if extrapolated && valid.contains(&rbo.min) && valid.contains(&rbo.residual) {
+            Ok(rbo)
+        } else {
+            Err(crate::RboError::NumericalInstability)
+        }
+    }
+
+    // the final result for any persistence, the walk itself does not depend on p
+    pub(crate) fn result_with_persistence(&self, p: f64) -> Result<crate::Rbo, crate::RboError> {
+        #[cfg(feature = "tracing")]
+        let _span = self.finalize_span(p).entered();
+        self.checked(crate::Rbo {
+            min: self.compute_min(p),
+            residual: self.compute_residual(p),
+            extrapolated: self.compute_extrapolated(p),
+        })
+    }
+
+    // the overlap X_d at every depth d seen so far, with X_0 = 0 at index 0
+    pub(crate) fn overlap(&self) -> &[f64] {
+        self.overlap
+            .as_deref()
+            .expect("the overlap of a constant memory state is not kept")
+    }
+
+    // the agreement A_d = X_d / d at every depth d seen so far
+    pub(crate) fn agreement(&self) -> Vec<f64> {
+        self.overlap()
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(d, x_d)| x_d / d as f64)
+            .collect()
+    }
+
+    // bounds on RBO when both lists are prefixes of permutations of a domain of
+    // `size` items: the overlap at depth d is at least 2d - size, and every
+    // depth beyond `size` has full agreement
+    fn conjoint_bounds(&self, size: usize) -> Result<(f64, f64), crate::RboError> {
         let s = self.depth_short as usize;
-        let x_s = self.overlap[s];
-        let x_l = self.overlap[l];
-        let x_d = &self.overlap;
-        let first: f64 = (1..=l).map(|d| x_d[d] * p.powf(d as f64) / d as f64).sum();
-        let second: f64 = (s + 1..=l)
-            .map(|d| (x_s * (d - s) as f64) / (s * d) as f64 * p.powf(d as f64))
-            .sum();
-        let third = ((x_l - x_s) / l as f64 + (x_s / s as f64)) * p_l;
-        (1.0 - p) / p * (first + second) + third
+        let l = self.depth_long as usize;
+        let x_l = self.cur_overlap;
+        if (s + l) as f64 - x_l > size as f64 {
+            return Err(crate::RboError::InvalidDomain);
+        }
+        let p = self.persistence;
+        let n = size as f64;
+        // weight (1 - p) p^(d - 1) of depth d
+        let mut weight = 1.0 - p;
+        let (mut lower, mut upper) = (0.0, 0.0);
+        for d in 1..=size {
+            if weight == 0.0 {
+                break;
+            }
+            let seen = if d <= l { self.overlap()[d] } else { x_l };
+            let unseen = (d.saturating_sub(s) + d.saturating_sub(l)) as f64;
+            let depth = d as f64;
+            lower += weight * seen.max(2.0 * depth - n) / depth;
+            upper += weight * (seen + unseen).min(depth) / depth;
+            weight *= p;
+        }
+        let tail = weight / (1.0 - p);
+        Ok((lower + tail, upper + tail))
+    }
+
+    // like `into_result`, with the bounds tightened to a conjoint domain of `size` items
+    pub(crate) fn into_conjoint_result(self, size: usize) -> Result<crate::Rbo, crate::RboError> {
+        let (lower, upper) = self.conjoint_bounds(size)?;
+        Ok(crate::Rbo {
+            min: lower,
+            residual: upper - lower,
+            extrapolated: self
+                .compute_extrapolated(self.persistence)
+                .clamp(lower, upper),
+        })
+    }
+
+    // the persistence the running sums are kept for
+    pub(crate) fn persistence(&self) -> f64 {
+        self.persistence
+    }
+
+    // the overlap at the current depth
+    pub(crate) fn current_overlap(&self) -> f64 {
+        self.cur_overlap
+    }
+
+    // the span of finalizing the result for persistence `p`
+    #[cfg(feature = "tracing")]
+    fn finalize_span(&self, p: f64) -> tracing::Span {
+        tracing::debug_span!(
+            "rbo_finalize",
+            p,
+            depth_short = self.depth_short as usize,
+            depth_long = self.depth_long as usize,
+            overlap = self.cur_overlap
+        )
+    }
+
+    // the RBO of everything seen so far, from the running sums
+    pub(crate) fn current(&self) -> crate::Rbo {
+        #[cfg(feature = "tracing")]
+        let _span = self.finalize_span(self.persistence).entered();
+        crate::Rbo {
+            min: self.partial_min(),
+            residual: self.compute_residual(self.persistence),
+            extrapolated: self.partial_extrapolated(),
+        }
+    }
+
+    // like `current`, with the residual looked up in `table`
+    pub(crate) fn current_with_table(&self, table: &crate::PersistenceTable) -> crate::Rbo {
+        crate::Rbo {
+            min: self.partial_min(),
+            residual: table.residual(self.depth_short, self.depth_long, self.cur_overlap),
+            extrapolated: self.partial_extrapolated(),
+        }
     }
 
     // we extrapolate the final RBO value and compute the residual
-    pub(crate) fn into_result(mut self) -> crate::Rbo {
+    pub(crate) fn into_result(self) -> Result<crate::Rbo, crate::RboError> {
+        self.checked(self.current())
+    }
+}
+
+// A running sum with Neumaier's compensation, which keeps the rounding error
+// independent of the number of terms. The long series over all depths of
+// equations 11, 30 and 32 would otherwise drift for deep lists and p near 1.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Compensated<F = f64> {
+    sum: F,
+    // the low order bits lost by `sum` so far
+    compensation: F,
+}
+
+impl<F: Float> Compensated<F> {
+    pub(crate) fn new() -> Self {
+        Self {
+            sum: F::zero(),
+            compensation: F::zero(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, x: F) {
+        let t = self.sum + x;
+        let lost = if self.sum.abs() >= x.abs() {
+            (self.sum - t) + x
+        } else {
+            (x - t) + self.sum
+        };
+        self.compensation = self.compensation + lost;
+        self.sum = t;
+    }
+
+    pub(crate) fn value(&self) -> F {
+        self.sum + self.compensation
+    }
+
+    // add the terms summed by `other`
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.add(other.sum);
+        self.compensation = self.compensation + other.compensation;
+    }
+}
+
+// p^d is carried forward from p^(d - 1) with a single multiplication instead of
+// a `powf` per depth, which dominated the finalization of long lists. Every
+// rounding of the recurrence adds up to half an ulp of relative error, so p^d
+// is recomputed with `powf` every `POWER_RESYNC` depths to keep the error
+// within a few ulps however deep the lists are.
+const POWER_RESYNC: usize = 64;
+
+// p^d given p^(d - 1). `usize::is_multiple_of` would need Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+pub(crate) fn next_power<F: Float>(p: F, d: usize, previous: F) -> F {
+    if d % POWER_RESYNC == 0 {
+        p.powf(F::from(d).expect("depth fits the float type"))
+    } else {
+        previous * p
+    }
+}
+
+// p^d for d = 1, 2, ...
+pub(crate) fn powers<F: Float>(p: F) -> impl Iterator<Item = F> {
+    (1..).scan(F::one(), move |p_d, d| {
+        *p_d = next_power(p, d, *p_d);
+        Some(*p_d)
+    })
+}
+
+// p^d as produced by `powers`, without iterating over all depths before d
+pub(crate) fn power_at<F: Float>(p: F, d: usize) -> F {
+    let base = d - d % POWER_RESYNC;
+    let mut p_d = if base == 0 {
+        F::one()
+    } else {
+        p.powf(F::from(base).expect("depth fits the float type"))
+    };
+    for _ in base + 1..=d {
+        p_d = p_d * p;
+    }
+    p_d
+}
+
+// the compensated sum of all `terms`
+pub(crate) fn sum<F: Float>(terms: impl IntoIterator<Item = F>) -> F {
+    let mut total = Compensated::new();
+    for term in terms {
+        total.add(term);
+    }
+    total.value()
+}
+
+// RBO for p = 0, where depth 1 carries all the weight and equations 11, 30
+// and 32 would divide by p: the agreement X_1 at depth 1, known as soon as
+// both lists reach it. Before that nothing is known, nor extrapolated.
+pub(crate) fn zero_persistence(x_1: f64, s: usize) -> crate::Rbo {
+    if s == 0 {
+        crate::Rbo {
+            min: 0.0,
+            residual: 1.0,
+            extrapolated: f64::NAN,
+        }
+    } else {
         crate::Rbo {
-            min: self.compute_min(),
-            residual: self.compute_residual(),
-            extrapolated: self.compute_extrapolated(),
+            min: x_1,
+            residual: 0.0,
+            extrapolated: x_1,
+        }
+    }
+}
+
+// equation 11 in the paper. `overlap` holds X_d for d = 0..=k
+pub(crate) fn min(overlap: &[f64], k: usize, p: f64) -> f64 {
+    if p == 0.0 {
+        return zero_persistence(overlap[k.min(1)], k).min;
+    }
+    let x_k = overlap[k];
+    let other = sum((1..k)
+        .zip(powers(p))
+        .map(|(d, p_d)| (overlap[d] - x_k) * p_d / d as f64));
+    #[cfg(feature = "tracing")]
+    tracing::debug!(other, x_k, "min sums");
+    (1.0 - p) / p * (other - (x_k * (1.0 - p).ln()))
+}
+
+// equation 30 in the paper, for lists of length `s` <= `l` with overlap `x_l` at depth `l`
+pub(crate) fn residual<F: Float + Send + Sync>(s: F, l: F, x_l: F, p: F) -> F {
+    let one = F::one();
+    if p == F::zero() {
+        // see `zero_persistence`, depth 1 is known once both lists reach it
+        return if s < one { one } else { F::zero() };
+    }
+    let us = s.to_usize().unwrap_or(0);
+    let ul = l.to_usize().unwrap_or(0);
+    // the rank at which maximum agreement becomes 1
+    let f = s + l - x_l;
+    let uf = f.to_usize().unwrap_or(0);
+    let ux = x_l.to_usize().unwrap_or(0);
+    // Beyond depth x_l every term of the series weighs at most 2 p^d / d, so
+    // stopping at depth D >= x_l changes the result by at most 2 p^D. Long
+    // lists that barely overlap have a huge f, but only the depths up to
+    // where 2 p^D drops below the machine epsilon are summed.
+    let cutoff = series_cutoff(p).max(ux);
+    let [sum_s, sum_l, sum_t] = residual_series(p, us, ul, uf.min(cutoff));
+    let (p_s, p_l, p_f) = (power_at(p, us), power_at(p, ul), power_at(p, uf));
+    let ln_1p = (one / (one - p)).ln();
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        sum_s = sum_s.to_f64(),
+        sum_l = sum_l.to_f64(),
+        sum_t = sum_t.to_f64(),
+        cutoff,
+        "residual sums"
+    );
+    p_s + p_l - p_f - ((one - p) / p) * (s * sum_s + l * sum_l + x_l * (ln_1p - sum_t))
+}
+
+// the depth D beyond which 2 p^D drops below the machine epsilon
+pub(crate) fn series_cutoff<F: Float>(p: F) -> usize {
+    let cutoff = (F::epsilon() / (F::one() + F::one())).ln() / p.ln();
+    cutoff.ceil().to_usize().unwrap_or(usize::MAX)
+}
+
+// Depths of the residual series summed by one task. A multiple of
+// `POWER_RESYNC`, so every chunk starts from the same p^d as a single pass.
+const RESIDUAL_CHUNK: usize = 1 << 16;
+
+// the sums of p^d / d over s < d <= f, l < d <= f and 0 < d <= f. Lists
+// of millions of items are summed in chunks, concurrently with the `rayon`
+// feature; the chunks are merged in chunk order on the calling thread, so the
+// sums are bit-identical for any number of threads.
+pub(crate) fn residual_series<F: Float + Send + Sync>(
+    p: F,
+    s: usize,
+    l: usize,
+    f: usize,
+) -> [F; 3] {
+    let chunk = |k: usize| {
+        let start = k * RESIDUAL_CHUNK + 1;
+        let end = f.min(start + RESIDUAL_CHUNK - 1);
+        let mut sums = [Compensated::new(); 3];
+        let mut p_d = power_at(p, start - 1);
+        for d in start..=end {
+            p_d = next_power(p, d, p_d);
+            let term = p_d / F::from(d).expect("depth fits the float type");
+            if d > s {
+                sums[0].add(term);
+            }
+            if d > l {
+                sums[1].add(term);
+            }
+            sums[2].add(term);
+        }
+        sums
+    };
+    let chunks = f.div_ceil(RESIDUAL_CHUNK);
+    #[cfg(feature = "rayon")]
+    let partial: Vec<[Compensated<F>; 3]> = {
+        use rayon::prelude::*;
+        (0..chunks).into_par_iter().map(chunk).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let partial: Vec<[Compensated<F>; 3]> = (0..chunks).map(chunk).collect();
+    let mut sums = [Compensated::new(); 3];
+    for part in partial {
+        for (sum, part) in sums.iter_mut().zip(part) {
+            sum.merge(part);
+        }
+    }
+    sums.map(|sum| sum.value())
+}
+
+// equation 32 in the paper. `overlap` holds X_d for d = 0..=l
+pub(crate) fn extrapolated(overlap: &[f64], s: usize, l: usize, p: f64) -> f64 {
+    if p == 0.0 {
+        return zero_persistence(overlap[s.min(1)], s).extrapolated;
+    }
+    let x_s = overlap[s];
+    let x_l = overlap[l];
+    let (mut first, mut second) = (Compensated::new(), Compensated::new());
+    let mut p_l = 1.0;
+    for (d, p_d) in (1..=l).zip(powers(p)) {
+        first.add(overlap[d] * p_d / d as f64);
+        if d > s {
+            second.add((x_s * (d - s) as f64) / (s * d) as f64 * p_d);
         }
+        p_l = p_d;
     }
+    let (first, second) = (first.value(), second.value());
+    let third = ((x_l - x_s) / l as f64 + (x_s / s as f64)) * p_l;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        seen = first,
+        tail = second,
+        last = third,
+        "extrapolated sums"
+    );
+    (1.0 - p) / p * (first + second) + third
 }
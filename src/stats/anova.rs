@@ -0,0 +1,162 @@
+use super::dist::f_sf;
+use crate::RboError;
+
+/// A single source of variation in the two-way decomposition
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VarianceComponent {
+    /// Sum of squares attributed to this source
+    pub sum_of_squares: f64,
+    /// Degrees of freedom
+    pub degrees_of_freedom: usize,
+    /// Mean square, `sum_of_squares / degrees_of_freedom`
+    pub mean_square: f64,
+}
+
+/// Two-way (query x system) decomposition of per-query RBO scores
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VarianceDecomposition {
+    /// Mean score over all queries and systems
+    pub grand_mean: f64,
+    /// Per-query deviation of the query mean from the grand mean
+    pub query_effects: Vec<f64>,
+    /// Per-system deviation of the system mean from the grand mean
+    pub system_effects: Vec<f64>,
+    /// Variation explained by queries
+    pub query: VarianceComponent,
+    /// Variation explained by systems
+    pub system: VarianceComponent,
+    /// Variation left unexplained (query x system interaction and noise)
+    pub residual: VarianceComponent,
+    /// F statistic for the system effect, `system.mean_square / residual.mean_square`
+    pub system_f: f64,
+    /// p-value of the system effect under the null hypothesis of no system differences
+    pub system_p_value: f64,
+    /// F statistic for the query effect, `query.mean_square / residual.mean_square`
+    pub query_f: f64,
+    /// p-value of the query effect under the null hypothesis of no query differences
+    pub query_p_value: f64,
+}
+
+fn component(sum_of_squares: f64, degrees_of_freedom: usize) -> VarianceComponent {
+    VarianceComponent {
+        sum_of_squares,
+        degrees_of_freedom,
+        mean_square: sum_of_squares / degrees_of_freedom as f64,
+    }
+}
+
+fn f_test(effect: &VarianceComponent, residual: &VarianceComponent) -> (f64, f64) {
+    let f = effect.mean_square / residual.mean_square;
+    if f.is_nan() {
+        // no variation at all
+        return (f, 1.0);
+    }
+    let p_value = f_sf(
+        f,
+        effect.degrees_of_freedom as f64,
+        residual.degrees_of_freedom as f64,
+    );
+    (f, p_value)
+}
+
+///
+/// Decompose per-query scores of several systems into query effects, system
+/// effects and residual variance (two-way ANOVA without replication), and
+/// test whether the differences between systems exceed the query-to-query
+/// noise.
+///
+/// `scores[q][s]` is the score of system `s` on query `q`, e.g. the RBO of
+/// each system's ranking against a common reference.
+///
+/// # Errors
+///
+/// - Will return `Err` if there are fewer than two queries or two systems
+/// - Will return `Err` if the rows of `scores` differ in length
+///
+pub fn variance_decomposition<S>(scores: &[S]) -> Result<VarianceDecomposition, RboError>
+where
+    S: AsRef<[f64]>,
+{
+    let queries = scores.len();
+    let systems = scores.first().map_or(0, |row| row.as_ref().len());
+    if queries < 2 || systems < 2 {
+        return Err(RboError::InsufficientData);
+    }
+    if scores.iter().any(|row| row.as_ref().len() != systems) {
+        return Err(RboError::RaggedScores);
+    }
+
+    let grand_mean =
+        scores.iter().flat_map(|row| row.as_ref()).sum::<f64>() / (queries * systems) as f64;
+    let query_effects: Vec<f64> = scores
+        .iter()
+        .map(|row| row.as_ref().iter().sum::<f64>() / systems as f64 - grand_mean)
+        .collect();
+    let system_effects: Vec<f64> = (0..systems)
+        .map(|s| {
+            scores.iter().map(|row| row.as_ref()[s]).sum::<f64>() / queries as f64 - grand_mean
+        })
+        .collect();
+
+    let ss_query = systems as f64 * query_effects.iter().map(|e| e * e).sum::<f64>();
+    let ss_system = queries as f64 * system_effects.iter().map(|e| e * e).sum::<f64>();
+    let ss_residual: f64 = scores
+        .iter()
+        .zip(&query_effects)
+        .flat_map(|(row, q)| {
+            row.as_ref()
+                .iter()
+                .zip(&system_effects)
+                .map(move |(x, s)| (x - grand_mean - q - s).powi(2))
+        })
+        .sum();
+
+    let query = component(ss_query, queries - 1);
+    let system = component(ss_system, systems - 1);
+    let residual = component(ss_residual, (queries - 1) * (systems - 1));
+    let (system_f, system_p_value) = f_test(&system, &residual);
+    let (query_f, query_p_value) = f_test(&query, &residual);
+
+    Ok(VarianceDecomposition {
+        grand_mean,
+        query_effects,
+        system_effects,
+        query,
+        system,
+        residual,
+        system_f,
+        system_p_value,
+        query_f,
+        query_p_value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn decomposition_adds_up() {
+        let scores = [
+            [0.61, 0.72, 0.55],
+            [0.40, 0.52, 0.31],
+            [0.83, 0.90, 0.79],
+            [0.52, 0.66, 0.48],
+        ];
+        let d = super::variance_decomposition(&scores).expect("valid scores");
+        let ss_total: f64 = scores
+            .iter()
+            .flatten()
+            .map(|x| (x - d.grand_mean).powi(2))
+            .sum();
+        approx::assert_abs_diff_eq!(
+            d.query.sum_of_squares + d.system.sum_of_squares + d.residual.sum_of_squares,
+            ss_total,
+            epsilon = 1e-12
+        );
+        assert_eq!(d.residual.degrees_of_freedom, 6);
+        approx::assert_abs_diff_eq!(d.system_f, 54.612_565_445, epsilon = 1e-6);
+        approx::assert_abs_diff_eq!(d.system_p_value, 0.000_141_192_660, epsilon = 1e-9);
+        assert!(super::variance_decomposition(&[[0.1, 0.2]]).is_err());
+    }
+}
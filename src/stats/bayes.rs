@@ -0,0 +1,130 @@
+use super::dist::{t_cdf, t_quantile};
+use crate::RboError;
+
+/// Posterior distribution of a mean score
+///
+/// Scores are modelled as normally distributed with unknown mean and variance
+/// under the non-informative Jeffreys prior, which makes the posterior of the
+/// mean a Student's t distribution with `n - 1` degrees of freedom centred on
+/// the sample mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Posterior {
+    /// Posterior mean (equal to the sample mean)
+    pub mean: f64,
+    /// Scale of the posterior, the standard error `s / sqrt(n)`
+    pub scale: f64,
+    /// Degrees of freedom of the posterior t distribution
+    pub degrees_of_freedom: usize,
+    /// Lower end of the equal-tailed credible interval
+    pub lower: f64,
+    /// Upper end of the equal-tailed credible interval
+    pub upper: f64,
+}
+
+/// Posterior comparison of two systems evaluated on the same queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BayesianComparison {
+    /// Posterior of the mean per-query difference `a - b`
+    pub difference: Posterior,
+    /// Posterior probability that the mean score of `a` exceeds that of `b`
+    pub probability_of_improvement: f64,
+}
+
+fn posterior(scores: &[f64], level: f64) -> Result<Posterior, RboError> {
+    if !(level > 0.0 && level < 1.0) {
+        return Err(RboError::InvalidConfidenceLevel);
+    }
+    let n = scores.len();
+    if n < 2 {
+        return Err(RboError::InsufficientData);
+    }
+    let mean = scores.iter().sum::<f64>() / n as f64;
+    let variance = scores.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let scale = (variance / n as f64).sqrt();
+    let dof = n - 1;
+    let half_width = scale * t_quantile(0.5 + level / 2.0, dof as f64);
+    Ok(Posterior {
+        mean,
+        scale,
+        degrees_of_freedom: dof,
+        lower: mean - half_width,
+        upper: mean + half_width,
+    })
+}
+
+///
+/// Posterior mean and equal-tailed credible interval (e.g. `level = 0.95`)
+/// for the mean of per-query scores such as extrapolated RBO.
+///
+/// # Errors
+///
+/// - Will return `Err` if there are fewer than two scores
+/// - Will return `Err` if `level` is not 0 < level < 1
+///
+pub fn credible_interval(scores: &[f64], level: f64) -> Result<Posterior, RboError> {
+    posterior(scores, level)
+}
+
+///
+/// Compare two systems scored on the same queries (`a[i]` and `b[i]` belong to
+/// query `i`) and report the posterior of the mean difference together with
+/// the probability that system `a` has the higher mean score.
+///
+/// # Errors
+///
+/// - Will return `Err` if `a` and `b` differ in length
+/// - Will return `Err` if there are fewer than two queries
+/// - Will return `Err` if `level` is not 0 < level < 1
+///
+pub fn bayesian_comparison(
+    a: &[f64],
+    b: &[f64],
+    level: f64,
+) -> Result<BayesianComparison, RboError> {
+    if a.len() != b.len() {
+        return Err(RboError::UnpairedScores);
+    }
+    let differences: Vec<f64> = a.iter().zip(b).map(|(a, b)| a - b).collect();
+    let difference = posterior(&differences, level)?;
+    let probability_of_improvement = if difference.scale > 0.0 {
+        t_cdf(
+            difference.mean / difference.scale,
+            difference.degrees_of_freedom as f64,
+        )
+    } else {
+        // every query shows the same difference
+        match difference.mean.partial_cmp(&0.0) {
+            Some(std::cmp::Ordering::Greater) => 1.0,
+            Some(std::cmp::Ordering::Less) => 0.0,
+            _ => 0.5,
+        }
+    };
+    Ok(BayesianComparison {
+        difference,
+        probability_of_improvement,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn credible_interval_and_improvement() {
+        let a = [0.62, 0.71, 0.55, 0.80, 0.67, 0.59, 0.73, 0.64];
+        let b = [0.60, 0.65, 0.56, 0.71, 0.60, 0.58, 0.70, 0.61];
+        let posterior = super::credible_interval(&a, 0.95).expect("valid scores");
+        assert!(posterior.lower < posterior.mean && posterior.mean < posterior.upper);
+        assert_eq!(posterior.degrees_of_freedom, 7);
+
+        let comparison = super::bayesian_comparison(&a, &b, 0.95).expect("valid scores");
+        assert!(comparison.probability_of_improvement > 0.99);
+        let reverse = super::bayesian_comparison(&b, &a, 0.95).expect("valid scores");
+        approx::assert_abs_diff_eq!(
+            comparison.probability_of_improvement + reverse.probability_of_improvement,
+            1.0,
+            epsilon = 1e-12
+        );
+        assert!(super::bayesian_comparison(&a, &b[1..], 0.95).is_err());
+    }
+}
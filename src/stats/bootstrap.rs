@@ -0,0 +1,219 @@
+use crate::RboError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hash::Hash;
+
+/// Configuration of a bootstrap resampling run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BootstrapOptions {
+    /// Number of bootstrap resamples to draw
+    pub resamples: usize,
+    /// Confidence level of the reported interval, e.g. 0.95
+    pub confidence: f64,
+    /// Seed of the random number generator, runs with the same seed are reproducible
+    pub seed: u64,
+}
+
+impl Default for BootstrapOptions {
+    fn default() -> Self {
+        Self {
+            resamples: 1000,
+            confidence: 0.95,
+            seed: 0,
+        }
+    }
+}
+
+/// A percentile bootstrap confidence interval for a mean
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfidenceInterval {
+    /// The mean of the observed sample
+    pub mean: f64,
+    /// Lower end of the interval
+    pub lower: f64,
+    /// Upper end of the interval
+    pub upper: f64,
+}
+
+/// Bootstrap confidence intervals overall and for each stratum
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StratifiedBootstrap<K> {
+    /// Interval for the mean over all samples
+    pub overall: ConfidenceInterval,
+    /// Interval for the mean within each stratum, in order of first appearance
+    pub strata: Vec<(K, ConfidenceInterval)>,
+}
+
+fn validate(options: &BootstrapOptions) -> Result<(), RboError> {
+    if !(options.confidence > 0.0 && options.confidence < 1.0) {
+        return Err(RboError::InvalidConfidenceLevel);
+    }
+    if options.resamples == 0 {
+        return Err(RboError::InsufficientData);
+    }
+    Ok(())
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// percentile interval from the (unsorted) bootstrap distribution of the mean
+fn interval(mean: f64, mut distribution: Vec<f64>, confidence: f64) -> ConfidenceInterval {
+    distribution.sort_by(f64::total_cmp);
+    let alpha = (1.0 - confidence) / 2.0;
+    let last = distribution.len() - 1;
+    let lower = ((alpha * last as f64).round() as usize).min(last);
+    let upper = (((1.0 - alpha) * last as f64).round() as usize).min(last);
+    ConfidenceInterval {
+        mean,
+        lower: distribution[lower],
+        upper: distribution[upper],
+    }
+}
+
+///
+/// Percentile bootstrap confidence intervals for the mean of per-query scores
+/// (e.g. extrapolated RBO), resampling with replacement *within* each
+/// stratum so every resample keeps the stratum sizes of the observed data.
+/// Reports an interval for the overall mean as well as for every stratum, so
+/// disagreement concentrated in a single query facet is not hidden by the
+/// aggregate.
+///
+/// `samples` holds one `(stratum, score)` pair per query.
+///
+/// # Errors
+///
+/// - Will return `Err` if `samples` is empty or `options.resamples` is zero
+/// - Will return `Err` if `options.confidence` is not 0 < confidence < 1
+///
+pub fn stratified_bootstrap<K>(
+    samples: &[(K, f64)],
+    options: &BootstrapOptions,
+) -> Result<StratifiedBootstrap<K>, RboError>
+where
+    K: Eq + Hash + Clone,
+{
+    validate(options)?;
+    if samples.is_empty() {
+        return Err(RboError::InsufficientData);
+    }
+    let mut strata: Vec<(K, Vec<f64>)> = Vec::new();
+    let mut index = std::collections::HashMap::new();
+    for (key, score) in samples {
+        let i = *index.entry(key).or_insert_with(|| {
+            strata.push((key.clone(), Vec::new()));
+            strata.len() - 1
+        });
+        strata[i].1.push(*score);
+    }
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut overall = Vec::with_capacity(options.resamples);
+    let mut per_stratum = vec![Vec::with_capacity(options.resamples); strata.len()];
+    for _ in 0..options.resamples {
+        let mut total = 0.0;
+        for ((_, scores), distribution) in strata.iter().zip(per_stratum.iter_mut()) {
+            let sum: f64 = (0..scores.len())
+                .map(|_| scores[rng.gen_range(0..scores.len())])
+                .sum();
+            total += sum;
+            distribution.push(sum / scores.len() as f64);
+        }
+        overall.push(total / samples.len() as f64);
+    }
+
+    let all: Vec<f64> = samples.iter().map(|(_, score)| *score).collect();
+    Ok(StratifiedBootstrap {
+        overall: interval(mean(&all), overall, options.confidence),
+        strata: strata
+            .into_iter()
+            .zip(per_stratum)
+            .map(|((key, scores), distribution)| {
+                let ci = interval(mean(&scores), distribution, options.confidence);
+                (key, ci)
+            })
+            .collect(),
+    })
+}
+
+///
+/// Percentile bootstrap confidence interval for the mean extrapolated RBO
+/// over a set of ranking pairs, e.g. one pair per query. Pairs are resampled
+/// with replacement.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if `pairs` is empty or `options.resamples` is zero
+/// - Will return `Err` if `options.confidence` is not 0 < confidence < 1
+///
+pub fn bootstrap_rbo<Item, L>(
+    pairs: &[(L, L)],
+    p: f64,
+    options: &BootstrapOptions,
+) -> Result<ConfidenceInterval, RboError>
+where
+    Item: Eq + Hash,
+    L: AsRef<[Item]>,
+{
+    let samples = pairs
+        .iter()
+        .map(|(first, second)| {
+            Ok((
+                (),
+                crate::rbo(first.as_ref(), second.as_ref(), p)?.extrapolated,
+            ))
+        })
+        .collect::<Result<Vec<_>, RboError>>()?;
+    Ok(stratified_bootstrap(&samples, options)?.overall)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BootstrapOptions;
+
+    #[test]
+    fn stratified_intervals_cover_means() {
+        let samples: Vec<(&str, f64)> = (0..40)
+            .map(|i| {
+                if i % 4 == 0 {
+                    ("navigational", 0.2 + (i % 3) as f64 * 0.01)
+                } else {
+                    ("informational", 0.8 + (i % 5) as f64 * 0.01)
+                }
+            })
+            .collect();
+        let options = BootstrapOptions::default();
+        let result = super::stratified_bootstrap(&samples, &options).expect("valid samples");
+        assert_eq!(result.strata.len(), 2);
+        assert_eq!(result.strata[0].0, "navigational");
+        for (_, ci) in result.strata.iter().chain([&("all", result.overall)]) {
+            assert!(ci.lower <= ci.mean && ci.mean <= ci.upper);
+        }
+        assert!(result.strata[0].1.upper < result.strata[1].1.lower);
+        // reproducible with the same seed
+        let again = super::stratified_bootstrap(&samples, &options).expect("valid samples");
+        assert_eq!(result, again);
+    }
+
+    #[test]
+    fn rbo_interval_over_pairs() {
+        let pairs: Vec<(Vec<char>, Vec<char>)> = ["abcdef", "abdcef", "bacdfe", "fedcba", "abcxyz"]
+            .iter()
+            .map(|second| ("abcdef".chars().collect(), second.chars().collect()))
+            .collect();
+        let ci =
+            super::bootstrap_rbo(&pairs, 0.9, &BootstrapOptions::default()).expect("valid rbo");
+        let mean = pairs
+            .iter()
+            .map(|(a, b)| crate::rbo(a, b, 0.9).expect("valid rbo").extrapolated)
+            .sum::<f64>()
+            / pairs.len() as f64;
+        approx::assert_abs_diff_eq!(ci.mean, mean, epsilon = 1e-12);
+        assert!(ci.lower < ci.mean && ci.mean < ci.upper);
+    }
+}
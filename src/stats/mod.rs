@@ -0,0 +1,24 @@
+//! Statistical tools for experiments built on RBO scores, such as comparing
+//! several systems over a set of queries. The bootstrap and permutation tests
+//! draw random samples and need the `stats` feature.
+
+mod anova;
+mod bayes;
+#[cfg(feature = "stats")]
+mod bootstrap;
+pub(crate) mod dist;
+mod paired;
+#[cfg(feature = "stats")]
+mod permutation;
+mod summary;
+
+pub use anova::{variance_decomposition, VarianceComponent, VarianceDecomposition};
+pub use bayes::{bayesian_comparison, credible_interval, BayesianComparison, Posterior};
+#[cfg(feature = "stats")]
+pub use bootstrap::{
+    bootstrap_rbo, stratified_bootstrap, BootstrapOptions, ConfidenceInterval, StratifiedBootstrap,
+};
+pub use paired::{paired_test, PairedTest};
+#[cfg(feature = "stats")]
+pub use permutation::{permutation_test, PermutationTest};
+pub use summary::{RboSummary, ScoreSummary};
@@ -0,0 +1,140 @@
+use super::dist::{normal_cdf, t_cdf};
+use crate::RboError;
+
+/// The outcome of paired significance tests between two systems
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PairedTest {
+    /// Number of queries
+    pub queries: usize,
+    /// Mean per-query difference `a - b`
+    pub mean_difference: f64,
+    /// Effect size, Cohen's d_z: the mean difference over the standard
+    /// deviation of the differences
+    pub effect_size: f64,
+    /// The paired t statistic
+    pub t_statistic: f64,
+    /// Two-sided p-value of the paired t-test
+    pub t_p_value: f64,
+    /// Wilcoxon's W+, the rank sum of the positive differences
+    pub wilcoxon_statistic: f64,
+    /// Two-sided p-value of the Wilcoxon signed-rank test, from the normal
+    /// approximation with tie and continuity correction
+    pub wilcoxon_p_value: f64,
+}
+
+///
+/// Paired t-test and Wilcoxon signed-rank test of the difference between two
+/// systems scored on the same queries (`a[i]` and `b[i]` belong to query
+/// `i`), e.g. the extrapolated RBO of each system against a common baseline.
+/// Queries with a zero difference are dropped by the Wilcoxon test.
+///
+/// ```
+/// let a = [0.62, 0.71, 0.55, 0.80, 0.67, 0.59, 0.73, 0.64];
+/// let b = [0.60, 0.65, 0.56, 0.71, 0.60, 0.58, 0.70, 0.61];
+/// let test = rbo::stats::paired_test(&a, &b).expect("valid scores");
+/// assert!(test.t_p_value < 0.05);
+/// assert!(test.effect_size > 0.0);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `a` and `b` differ in length
+/// - Will return `Err` if there are fewer than two queries
+///
+pub fn paired_test(a: &[f64], b: &[f64]) -> Result<PairedTest, RboError> {
+    if a.len() != b.len() {
+        return Err(RboError::UnpairedScores);
+    }
+    let n = a.len();
+    if n < 2 {
+        return Err(RboError::InsufficientData);
+    }
+    let differences: Vec<f64> = a.iter().zip(b).map(|(a, b)| a - b).collect();
+    let mean = differences.iter().sum::<f64>() / n as f64;
+    let variance = differences.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let sd = variance.sqrt();
+    let (t_statistic, t_p_value) = if sd > 0.0 {
+        let t = mean / (sd / (n as f64).sqrt());
+        (t, 2.0 * t_cdf(-t.abs(), (n - 1) as f64))
+    } else if mean == 0.0 {
+        (0.0, 1.0)
+    } else {
+        // every query shows the same non-zero difference
+        (mean.signum() * f64::INFINITY, 0.0)
+    };
+    let (wilcoxon_statistic, wilcoxon_p_value) = wilcoxon(&differences);
+    Ok(PairedTest {
+        queries: n,
+        mean_difference: mean,
+        effect_size: mean / sd,
+        t_statistic,
+        t_p_value,
+        wilcoxon_statistic,
+        wilcoxon_p_value,
+    })
+}
+
+// W+ and the two-sided p-value of the signed-rank test on `differences`
+fn wilcoxon(differences: &[f64]) -> (f64, f64) {
+    let mut nonzero: Vec<f64> = differences.iter().copied().filter(|d| *d != 0.0).collect();
+    nonzero.sort_by(|x, y| x.abs().total_cmp(&y.abs()));
+    let n = nonzero.len() as f64;
+    let (mut w_plus, mut tie_correction) = (0.0, 0.0);
+    // rank groups of equal magnitude with their average rank
+    let mut start = 0;
+    for group in nonzero.chunk_by(|x, y| x.abs() == y.abs()) {
+        let ties = group.len() as f64;
+        let rank = start as f64 + (ties + 1.0) / 2.0;
+        w_plus += rank * group.iter().filter(|d| **d > 0.0).count() as f64;
+        tie_correction += ties.powi(3) - ties;
+        start += group.len();
+    }
+    let expected = n * (n + 1.0) / 4.0;
+    let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction / 48.0;
+    if variance <= 0.0 {
+        return (w_plus, 1.0);
+    }
+    let deviation = ((w_plus - expected).abs() - 0.5).max(0.0);
+    let p_value = 2.0 * normal_cdf(-deviation / variance.sqrt());
+    (w_plus, p_value.min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn detects_a_consistent_improvement() {
+        let a = [0.62, 0.71, 0.55, 0.80, 0.67, 0.59, 0.73, 0.64];
+        let b = [0.60, 0.65, 0.56, 0.71, 0.60, 0.58, 0.70, 0.61];
+        let test = super::paired_test(&a, &b).expect("valid scores");
+        assert_eq!(test.queries, 8);
+        approx::assert_abs_diff_eq!(test.mean_difference, 0.0375, epsilon = 1e-12);
+        // reference values computed with mpmath
+        approx::assert_abs_diff_eq!(test.t_statistic, 3.187_677_888_774_311, epsilon = 1e-9);
+        approx::assert_abs_diff_eq!(test.t_p_value, 0.015_326_060_632_357_665, epsilon = 1e-9);
+        approx::assert_abs_diff_eq!(test.effect_size, 1.127_014_325_695_366, epsilon = 1e-9);
+        // |-0.01| and |0.01| as well as the two 0.03 differences are tied
+        assert_eq!(test.wilcoxon_statistic, 34.5);
+        approx::assert_abs_diff_eq!(
+            test.wilcoxon_p_value,
+            0.024_706_589_326_941_374,
+            epsilon = 1e-9
+        );
+
+        let swapped = super::paired_test(&b, &a).expect("valid scores");
+        approx::assert_abs_diff_eq!(swapped.t_p_value, test.t_p_value, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(
+            swapped.wilcoxon_p_value,
+            test.wilcoxon_p_value,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn degenerate_inputs() {
+        let same = super::paired_test(&[0.5, 0.6], &[0.5, 0.6]).expect("valid scores");
+        assert_eq!((same.t_p_value, same.wilcoxon_p_value), (1.0, 1.0));
+        assert!(super::paired_test(&[0.5], &[0.4]).is_err());
+        assert!(super::paired_test(&[0.5, 0.6], &[0.4]).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::RboError;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::hash::Hash;
+
+/// The outcome of a permutation test on the similarity of two rankings
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PermutationTest {
+    /// The observed extrapolated RBO
+    pub observed: f64,
+    /// Mean extrapolated RBO over the permutations
+    pub null_mean: f64,
+    /// Probability of an RBO at least as high as the observed one by chance,
+    /// (1 + #{permuted >= observed}) / (1 + permutations)
+    pub p_value: f64,
+    /// Number of permutations drawn
+    pub permutations: usize,
+}
+
+///
+/// Permutation test for the similarity of two rankings being above chance.
+/// The order of `second` is shuffled `permutations` times, keeping its
+/// items, and the extrapolated RBO against `first` is recomputed for each
+/// shuffle. Runs with the same `seed` are reproducible.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if `permutations` is zero
+///
+pub fn permutation_test<Item>(
+    first: &[Item],
+    second: &[Item],
+    p: f64,
+    permutations: usize,
+    seed: u64,
+) -> Result<PermutationTest, RboError>
+where
+    Item: Eq + Hash,
+{
+    if permutations == 0 {
+        return Err(RboError::InsufficientData);
+    }
+    let observed = crate::rbo(first, second, p)?.extrapolated;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut shuffled: Vec<&Item> = second.iter().collect();
+    let first: Vec<&Item> = first.iter().collect();
+    let (mut sum, mut at_least) = (0.0, 0usize);
+    for _ in 0..permutations {
+        shuffled.shuffle(&mut rng);
+        let permuted = crate::rbo(&first, &shuffled, p)?.extrapolated;
+        sum += permuted;
+        if permuted >= observed {
+            at_least += 1;
+        }
+    }
+    Ok(PermutationTest {
+        observed,
+        null_mean: sum / permutations as f64,
+        p_value: (1 + at_least) as f64 / (1 + permutations) as f64,
+        permutations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn similar_rankings_are_significant() {
+        let first: Vec<usize> = (0..30).collect();
+        let mut second = first.clone();
+        second.swap(0, 1);
+        let test = super::permutation_test(&first, &second, 0.9, 199, 11).expect("valid rbo");
+        approx::assert_abs_diff_eq!(test.p_value, 1.0 / 200.0);
+        assert!(test.null_mean < test.observed);
+
+        let reversed: Vec<usize> = first.iter().rev().copied().collect();
+        let test = super::permutation_test(&first, &reversed, 0.9, 199, 11).expect("valid rbo");
+        assert!(test.p_value > 0.5);
+        let again = super::permutation_test(&first, &reversed, 0.9, 199, 11).expect("valid rbo");
+        assert_eq!(test, again);
+    }
+}
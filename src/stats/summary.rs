@@ -0,0 +1,136 @@
+use crate::Rbo;
+
+/// Summary statistics of one value over many RBO results
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreSummary {
+    /// The mean, `NaN` without values
+    pub mean: f64,
+    /// The median, the mean of the two middle values for an even count
+    pub median: f64,
+    /// The sample standard deviation, `NaN` with fewer than two values
+    pub std_dev: f64,
+    /// The smallest value
+    pub min: f64,
+    /// The largest value
+    pub max: f64,
+}
+
+impl ScoreSummary {
+    fn new(mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+        let n = values.len();
+        if n == 0 {
+            return Self {
+                mean: f64::NAN,
+                median: f64::NAN,
+                std_dev: f64::NAN,
+                min: f64::NAN,
+                max: f64::NAN,
+            };
+        }
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n as f64 - 1.0);
+        let median = if n % 2 == 1 {
+            values[n / 2]
+        } else {
+            (values[n / 2 - 1] + values[n / 2]) / 2.0
+        };
+        Self {
+            mean,
+            median,
+            std_dev: if n < 2 { f64::NAN } else { variance.sqrt() },
+            min: values[0],
+            max: values[n - 1],
+        }
+    }
+}
+
+///
+/// Summary statistics of the RBO results of many queries, for each of the
+/// lower bound, the residual and the extrapolated value. Collect it from any
+/// iterator of results.
+///
+/// ```
+/// use rbo::stats::RboSummary;
+///
+/// let results = [
+///     rbo::rbo(&["a", "b", "c"], &["a", "c", "b"], 0.9).expect("valid rbo"),
+///     rbo::rbo(&["a", "b", "c"], &["d", "e", "f"], 0.9).expect("valid rbo"),
+/// ];
+/// let summary: RboSummary = results.iter().collect();
+/// assert_eq!(summary.count, 2);
+/// assert_eq!(summary.extrapolated.min, 0.0);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RboSummary {
+    /// The number of summarized results
+    pub count: usize,
+    /// Statistics of [`Rbo::min`]
+    pub min: ScoreSummary,
+    /// Statistics of [`Rbo::residual`]
+    pub residual: ScoreSummary,
+    /// Statistics of [`Rbo::extrapolated`]
+    pub extrapolated: ScoreSummary,
+}
+
+impl FromIterator<Rbo> for RboSummary {
+    fn from_iter<I: IntoIterator<Item = Rbo>>(results: I) -> Self {
+        let (mut min, mut residual, mut extrapolated) = (Vec::new(), Vec::new(), Vec::new());
+        for rbo in results {
+            min.push(rbo.min);
+            residual.push(rbo.residual);
+            extrapolated.push(rbo.extrapolated);
+        }
+        Self {
+            count: min.len(),
+            min: ScoreSummary::new(min),
+            residual: ScoreSummary::new(residual),
+            extrapolated: ScoreSummary::new(extrapolated),
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a Rbo> for RboSummary {
+    fn from_iter<I: IntoIterator<Item = &'a Rbo>>(results: I) -> Self {
+        results.into_iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Rbo;
+
+    fn rbo(value: f64) -> Rbo {
+        Rbo {
+            min: value / 2.0,
+            residual: 1.0 - value,
+            extrapolated: value,
+        }
+    }
+
+    #[test]
+    fn summarizes_every_value() {
+        let summary: super::RboSummary = [0.2, 0.8, 0.5, 0.4].map(rbo).into_iter().collect();
+        assert_eq!(summary.count, 4);
+        let ext = summary.extrapolated;
+        approx::assert_abs_diff_eq!(ext.mean, 0.475, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(ext.median, 0.45, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(ext.std_dev, 0.0625_f64.sqrt(), epsilon = 1e-12);
+        assert_eq!((ext.min, ext.max), (0.2, 0.8));
+        approx::assert_abs_diff_eq!(summary.min.median, 0.225, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(summary.residual.max, 0.8, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn few_results() {
+        let single: super::RboSummary = [rbo(0.3)].iter().collect();
+        assert_eq!(single.extrapolated.median, 0.3);
+        assert!(single.extrapolated.std_dev.is_nan());
+        let empty: super::RboSummary = std::iter::empty::<Rbo>().collect();
+        assert_eq!(empty.count, 0);
+        assert!(empty.extrapolated.mean.is_nan());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::incremental::RboIncremental;
+use crate::{Rbo, RboError};
+use futures::stream::{Stream, StreamExt};
+use std::hash::Hash;
+
+///
+/// RBO between two rankings produced by async streams, e.g. paginated API
+/// responses, without buffering them first. Both streams are polled
+/// concurrently, one depth at a time, and duplicates are detected as items
+/// arrive as in [`rbo_iter`](crate::rbo_iter).
+///
+/// ```
+/// use futures::stream;
+///
+/// let first = stream::iter(vec!["a", "b", "c", "d"]);
+/// let second = stream::iter(vec!["b", "a", "d"]);
+/// let rbo = futures::executor::block_on(rbo::rbo_from_streams(first, second, 0.9))
+///     .expect("valid rbo");
+/// assert!(rbo.extrapolated > 0.5);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if either stream yields the same item twice
+///
+pub async fn rbo_from_streams<A, B, Item>(first: A, second: B, p: f64) -> Result<Rbo, RboError>
+where
+    A: Stream<Item = Item>,
+    B: Stream<Item = Item>,
+    Item: Eq + Hash,
+{
+    let mut state = RboIncremental::new(p)?;
+    let first = first.fuse();
+    let second = second.fuse();
+    futures::pin_mut!(first, second);
+    loop {
+        match futures::join!(first.next(), second.next()) {
+            (None, None) => break,
+            (a, b) => state.push_items(a, b)?,
+        }
+    }
+    state.into_checked_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use futures::stream;
+
+    #[test]
+    fn matches_slices() {
+        let first: Vec<char> = "abcdefgh".chars().collect();
+        let second: Vec<char> = "bxadcz".chars().collect();
+        let streamed = block_on(super::rbo_from_streams(
+            stream::iter(first.clone()),
+            stream::iter(second.clone()),
+            0.9,
+        ))
+        .expect("valid rbo");
+        assert_eq!(
+            streamed,
+            crate::rbo_iter(first.iter(), second.iter(), 0.9).expect("valid rbo")
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        let result = block_on(super::rbo_from_streams(
+            stream::iter(vec![1, 2, 1]),
+            stream::iter(vec![3, 4]),
+            0.9,
+        ));
+        assert!(matches!(result, Err(crate::RboError::DuplicatesInList(_))));
+    }
+}
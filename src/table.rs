@@ -0,0 +1,122 @@
+use crate::state::{
+    power_at, residual, residual_series, series_cutoff, Compensated, RboState, MAX_SERIES_DEPTH,
+};
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+///
+/// The series of the RBO residual precomputed for one persistence, for many
+/// comparisons with the same `p`. Finalizing a comparison otherwise sums
+/// `p^d / d` over every depth up to where the lists' maximum agreement
+/// reaches 1, which dominates the cost of comparing short lists with `p`
+/// close to 1. With the table, the residual of lists of up to `max_depth`
+/// items is a handful of lookups.
+///
+/// The table takes two floats per depth up to `max_depth`, or up to the
+/// depth where `p^d` vanishes if that is smaller. Longer lists are compared
+/// as well, their residual is summed as by [`rbo`](crate::rbo).
+///
+/// ```
+/// let table = rbo::PersistenceTable::new(0.99, 100).expect("valid persistence");
+/// let rbo = table.rbo(&["a", "b", "c"], &["b", "a", "d"]).expect("valid rbo");
+/// let expected = rbo::rbo(&["a", "b", "c"], &["b", "a", "d"], 0.99).expect("valid rbo");
+/// assert!((rbo.residual - expected.residual).abs() < 1e-12);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct PersistenceTable {
+    persistence: f64,
+    // the depth beyond which the series is negligible
+    cutoff: usize,
+    // p^d for d = 0..=n
+    powers: Vec<f64>,
+    // the sum of p^i / i over d < i <= cutoff, for d = 0..=n
+    tails: Vec<f64>,
+}
+
+impl PersistenceTable {
+    ///
+    /// Precompute the series for persistence `p` and lists of up to
+    /// `max_depth` items
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if `p` is not 0 <= p < 1
+    /// - Will return `Err` if `p` is so close to 1 that the series would have
+    ///   to be summed beyond the depth limit
+    ///
+    pub fn new(p: f64, max_depth: usize) -> Result<Self, RboError> {
+        crate::state::validate_persistence(p)?;
+        let cutoff = series_cutoff(p);
+        if cutoff > MAX_SERIES_DEPTH {
+            return Err(RboError::DepthLimitExceeded(cutoff));
+        }
+        let n = max_depth.min(cutoff);
+        let powers: Vec<f64> = (0..=n).map(|d| power_at(p, d)).collect();
+        // summed from the smallest terms up
+        let mut tail = Compensated::new();
+        if cutoff > n {
+            tail.add(residual_series(p, n, n, cutoff)[0]);
+        }
+        let mut tails = vec![0.0; n + 1];
+        for d in (0..=n).rev() {
+            tails[d] = tail.value();
+            if d > 0 {
+                tail.add(powers[d] / d as f64);
+            }
+        }
+        Ok(Self {
+            persistence: p,
+            cutoff,
+            powers,
+            tails,
+        })
+    }
+
+    /// The persistence the table was computed for
+    pub fn persistence(&self) -> f64 {
+        self.persistence
+    }
+
+    ///
+    /// RBO between `first` and `second` for the table's persistence, the same
+    /// result as [`rbo`](crate::rbo) up to the rounding of the residual
+    ///
+    /// # Errors
+    ///
+    /// - Will return `Err` if lists contain duplicate items
+    ///
+    pub fn rbo<Item>(&self, first: &[Item], second: &[Item]) -> Result<Rbo, RboError>
+    where
+        Item: Eq + Hash,
+    {
+        let state = RboState::constant_memory(self.persistence)?;
+        crate::check_duplicates(first, 0)?;
+        crate::check_duplicates(second, 1)?;
+        Ok(crate::walk(state, first, second).current_with_table(self))
+    }
+
+    // equation 30 for lists of length `s` <= `l` with overlap `x_l` at depth `l`
+    pub(crate) fn residual(&self, s: f64, l: f64, x_l: f64) -> f64 {
+        let p = self.persistence;
+        if p == 0.0 {
+            return residual(s, l, x_l, p);
+        }
+        let (us, ul) = (s as usize, l as usize);
+        let uf = (s + l - x_l) as usize;
+        // the series beyond `cutoff` is negligible, see `state::residual`
+        let tail = |d: usize| self.tails.get(d.min(self.cutoff)).copied();
+        let (Some(tail_s), Some(tail_l), Some(tail_f)) = (tail(us), tail(ul), tail(uf)) else {
+            return residual(s, l, x_l, p);
+        };
+        let power = |d: usize| {
+            self.powers
+                .get(d)
+                .copied()
+                .unwrap_or_else(|| power_at(p, d))
+        };
+        let (p_s, p_l, p_f) = (power(us), power(ul), power(uf));
+        let (sum_s, sum_l) = (tail_s - tail_f, tail_l - tail_f);
+        p_s + p_l - p_f - ((1.0 - p) / p) * (s * sum_s + l * sum_l + x_l * tail_f)
+    }
+}
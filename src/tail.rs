@@ -0,0 +1,42 @@
+use crate::{Rbo, RboError};
+use std::hash::Hash;
+
+/// Which end of the rankings RBO puts its weight on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weighting {
+    /// The top ranks weigh most, as in the paper
+    #[default]
+    Head,
+    /// The bottom ranks weigh most, see [`rbo_tail`]
+    Tail,
+}
+
+///
+/// Tail-weighted RBO: the agreement among the lowest ranked items, e.g. to
+/// compare what two systems demote or filter out. Both lists are read from
+/// their last item upwards, so depth 1 compares the two last items and the
+/// weights decay towards the top. Lists of different lengths are aligned at
+/// their ends.
+///
+/// ```
+/// let first = vec!["a", "b", "c", "d"];
+/// let second = vec!["b", "a", "c", "d"];
+/// let tail = rbo::rbo_tail(&first, &second, 0.9).expect("valid rbo");
+/// assert!(tail.min > rbo::rbo(&first, &second, 0.9).expect("valid rbo").min);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items. The reported ranks
+///   count from the bottom of the list.
+///
+pub fn rbo_tail<Item>(first: &[Item], second: &[Item], p: f64) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+{
+    let first: Vec<&Item> = first.iter().rev().collect();
+    let second: Vec<&Item> = second.iter().rev().collect();
+    crate::rbo(&first, &second, p)
+}
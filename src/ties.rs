@@ -0,0 +1,231 @@
+use crate::{Rbo, RboError};
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+/// How tied items contribute to the agreement at a given depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TieVariant {
+    /// Webber et al.'s original proposal: the prefix at depth d holds every
+    /// tie group starting at or before d, and the agreement is
+    /// 2 X_d / (|S_:d| + |T_:d|)
+    W,
+    /// Ties as an unknown order: the agreement is the expected agreement over
+    /// all ways of breaking the ties uniformly at random, so min and
+    /// extrapolated equal their averages over all tie-breakings
+    A,
+    /// Ties as true ties, analogous to Kendall's tau_b: prefixes as in `W`
+    /// but the agreement is X_d / sqrt(|S_:d| |T_:d|)
+    B,
+}
+
+// the ranks [start, end] covered by the tie group of every item
+fn group_ranks<Item: Eq + Hash>(groups: &[Vec<Item>]) -> HashMap<&Item, (usize, usize)> {
+    let mut ranks = HashMap::new();
+    let mut before = 0;
+    for group in groups {
+        for item in group {
+            ranks.insert(item, (before + 1, before + group.len()));
+        }
+        before += group.len();
+    }
+    ranks
+}
+
+// probability that an item covering ranks `range` is within the top `d` when
+// its tie group is ordered uniformly at random
+fn prob_within(range: Option<&(usize, usize)>, d: usize) -> f64 {
+    match range {
+        Some((_, end)) if *end <= d => 1.0,
+        Some((start, end)) if *start <= d => (d + 1 - start) as f64 / (end + 1 - start) as f64,
+        _ => 0.0,
+    }
+}
+
+// items of the tie group that straddles depth d, if any
+fn active_group<'a, Item>(groups: &'a [Vec<Item>], starts: &[usize], d: usize) -> &'a [Item] {
+    // index of the last group starting at or before d
+    let index = starts.partition_point(|start| *start <= d);
+    match index.checked_sub(1) {
+        Some(i) if starts[i] + groups[i].len() - 1 > d => &groups[i],
+        _ => &[],
+    }
+}
+
+// the expected overlap E[X_d] under random tie-breaking for d = 1..=k
+fn expected_overlap<Item: Eq + Hash>(
+    first: &[Vec<Item>],
+    second: &[Vec<Item>],
+    k: usize,
+) -> Vec<f64> {
+    let ranks_first = group_ranks(first);
+    let ranks_second = group_ranks(second);
+    let starts = |groups: &[Vec<Item>]| -> Vec<usize> {
+        groups
+            .iter()
+            .scan(1, |start, g| {
+                let s = *start;
+                *start += g.len();
+                Some(s)
+            })
+            .collect()
+    };
+    let starts_first = starts(first);
+    let starts_second = starts(second);
+
+    // number of common items certainly within the top d, bucketed by the depth they enter
+    let mut certain = vec![0usize; k + 1];
+    for (item, (_, end)) in &ranks_first {
+        if let Some((_, other_end)) = ranks_second.get(item) {
+            let depth = (*end).max(*other_end);
+            if depth <= k {
+                certain[depth] += 1;
+            }
+        }
+    }
+
+    let mut overlap = Vec::with_capacity(k + 1);
+    overlap.push(0.0);
+    let mut certain_total = 0;
+    for (d, entering) in certain.iter().enumerate().skip(1) {
+        certain_total += entering;
+        let active_first = active_group(first, &starts_first, d);
+        let active_second = active_group(second, &starts_second, d);
+        let partial = |item: &Item| {
+            prob_within(ranks_first.get(item), d) * prob_within(ranks_second.get(item), d)
+        };
+        let in_active_first: HashSet<&Item> = active_first.iter().collect();
+        let uncertain: f64 = active_first.iter().map(partial).sum::<f64>()
+            + active_second
+                .iter()
+                .filter(|item| !in_active_first.contains(item))
+                .map(partial)
+                .sum::<f64>();
+        overlap.push(certain_total as f64 + uncertain);
+    }
+    overlap
+}
+
+// overlap and prefix sizes when every tie group entering at or before d is included
+fn group_overlap<Item: Eq + Hash>(
+    first: &[Vec<Item>],
+    second: &[Vec<Item>],
+    k: usize,
+    variant: TieVariant,
+) -> Vec<f64> {
+    let mut seen = HashSet::new();
+    let (mut overlap, mut size_first, mut size_second) = (0.0, 0.0, 0.0);
+    let (mut next_first, mut next_second) = (first.iter().peekable(), second.iter().peekable());
+    let (mut rank_first, mut rank_second) = (1, 1);
+    let mut effective = Vec::with_capacity(k + 1);
+    effective.push(0.0);
+    for d in 1..=k {
+        for (next, rank, size) in [
+            (&mut next_first, &mut rank_first, &mut size_first),
+            (&mut next_second, &mut rank_second, &mut size_second),
+        ] {
+            while *rank <= d {
+                let Some(group) = next.next() else { break };
+                *rank += group.len();
+                *size += group.len() as f64;
+                for item in group {
+                    if !seen.insert(item) {
+                        overlap += 1.0;
+                    }
+                }
+            }
+        }
+        let agreement = match variant {
+            TieVariant::B => overlap / (size_first * size_second).sqrt(),
+            _ => 2.0 * overlap / (size_first + size_second),
+        };
+        effective.push(agreement * d as f64);
+    }
+    effective
+}
+
+///
+/// RBO for rankings containing ties. Each ranking is a sequence of tie
+/// groups, items within a group sharing the same rank, so
+/// `[vec![a], vec![b, c], vec![d]]` ranks `a` first, `b` and `c` tied second
+/// and `d` fourth. The `variant` selects how ties affect the agreement at
+/// each depth, see [`TieVariant`]. Without ties all variants agree with
+/// [`rbo`](crate::rbo) on lists of equal length.
+///
+/// Both rankings are evaluated to the depth of the shorter one, and the
+/// residual and extrapolation treat the agreement at that depth like the
+/// untied case does.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if an item appears more than once in either ranking
+///
+pub fn rbo_ties<Item>(
+    first: &[Vec<Item>],
+    second: &[Vec<Item>],
+    p: f64,
+    variant: TieVariant,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+{
+    crate::state::validate_persistence(p)?;
+    let flat_first: Vec<&Item> = first.iter().flatten().collect();
+    let flat_second: Vec<&Item> = second.iter().flatten().collect();
+    crate::check_duplicates(&flat_first, 0)?;
+    crate::check_duplicates(&flat_second, 1)?;
+    let k = flat_first.len().min(flat_second.len());
+    let overlap = match variant {
+        TieVariant::A => expected_overlap(first, second, k),
+        TieVariant::W | TieVariant::B => group_overlap(first, second, k, variant),
+    };
+    Ok(Rbo {
+        min: crate::state::min(&overlap, k, p),
+        residual: crate::state::residual(k as f64, k as f64, overlap[k], p),
+        extrapolated: crate::state::extrapolated(&overlap, k, k, p),
+    })
+}
+
+///
+/// RBO of a ranking against an unordered reference set, e.g. a gold set of
+/// relevant items without a gold ordering. The set is treated as a ranking
+/// whose items are all tied at rank 1, and `variant` selects how that tie is
+/// handled as in [`rbo_ties`]. With [`TieVariant::A`] the score is the
+/// expected RBO against a uniformly random ordering of the set.
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// let relevant: HashSet<_> = ["a", "b", "c"].into_iter().collect();
+/// let good = rbo::rbo_vs_set(&["b", "a", "x", "c"], &relevant, 0.9, rbo::TieVariant::A)
+///     .expect("valid rbo");
+/// let bad = rbo::rbo_vs_set(&["x", "y", "a", "b"], &relevant, 0.9, rbo::TieVariant::A)
+///     .expect("valid rbo");
+/// assert!(good.min > bad.min);
+/// ```
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if an item appears more than once in the ranking
+///
+pub fn rbo_vs_set<Item, S>(
+    ranking: &[Item],
+    set: &HashSet<Item, S>,
+    p: f64,
+    variant: TieVariant,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+    S: BuildHasher,
+{
+    let ranking: Vec<Vec<&Item>> = ranking.iter().map(|item| vec![item]).collect();
+    let set = [set.iter().collect::<Vec<&Item>>()];
+    let set = if set[0].is_empty() {
+        &set[..0]
+    } else {
+        &set[..]
+    };
+    rbo_ties(&ranking, set, p, variant)
+}
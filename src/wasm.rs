@@ -0,0 +1,71 @@
+//! JavaScript bindings, built with
+//! [wasm-pack](https://rustwasm.github.io/wasm-pack/) for the browser or
+//! Node:
+//!
+//! ```text
+//! wasm-pack build --target web --features wasm-bindgen
+//! ```
+//!
+//! ```js
+//! import init, { rbo, rboExt } from "./pkg/rbo.js";
+//!
+//! await init();
+//! const result = rbo(["a", "b", "c"], ["b", "a", "d"], 0.9);
+//! console.log(result.min, result.residual, result.ext);
+//! ```
+//!
+//! Rankings are arrays of strings. Invalid persistence values and duplicate
+//! items throw an `Error`.
+
+use crate::Rbo;
+use wasm_bindgen::prelude::*;
+
+/// The result of [`rbo`](crate::rbo) as a JavaScript object
+#[wasm_bindgen(js_name = RboResult)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JsRbo {
+    /// the lower bound on RBO
+    pub min: f64,
+    /// the maximum amount RBO can grow beyond `min`
+    pub residual: f64,
+    /// the extrapolated RBO
+    pub ext: f64,
+}
+
+impl From<Rbo> for JsRbo {
+    fn from(rbo: Rbo) -> Self {
+        Self {
+            min: rbo.min,
+            residual: rbo.residual,
+            ext: rbo.extrapolated,
+        }
+    }
+}
+
+///
+/// RBO between the rankings `first` and `second` with persistence `p`
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+#[wasm_bindgen(js_name = rbo)]
+pub fn js_rbo(first: Vec<String>, second: Vec<String>, p: f64) -> Result<JsRbo, JsError> {
+    let rbo = crate::rbo(&first, &second, p).map_err(|err| err.with_item_debug(&first, &second));
+    Ok(rbo?.into())
+}
+
+///
+/// The extrapolated RBO between the rankings `first` and `second` with persistence `p`
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+///
+#[wasm_bindgen(js_name = rboExt)]
+pub fn js_rbo_ext(first: Vec<String>, second: Vec<String>, p: f64) -> Result<f64, JsError> {
+    let rbo = crate::rbo(&first, &second, p).map_err(|err| err.with_item_debug(&first, &second));
+    Ok(rbo?.extrapolated)
+}
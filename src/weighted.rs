@@ -0,0 +1,77 @@
+use crate::{Rbo, RboError};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+fn check_duplicate_items<Item: Eq + Hash>(
+    list: &[(Item, f64)],
+    list_index: usize,
+) -> Result<(), RboError> {
+    let items: Vec<&Item> = list.iter().map(|(item, _)| item).collect();
+    crate::check_duplicates(&items, list_index)
+}
+
+///
+/// Item-weighted RBO. Every item carries an importance weight and the
+/// agreement at depth d becomes the weight of the items shared by both
+/// prefixes over the mean weight of the two prefixes. A shared item
+/// contributes the mean of the weights given to it by the two rankings.
+/// With all weights equal this is plain RBO.
+///
+/// Both rankings are evaluated to the depth of the shorter one, and the
+/// residual and extrapolation treat the agreement at that depth like the
+/// unweighted case does.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if lists contain duplicate items
+/// - Will return `Err` if a weight is negative or not finite
+///
+pub fn rbo_weighted<Item>(
+    first: &[(Item, f64)],
+    second: &[(Item, f64)],
+    p: f64,
+) -> Result<Rbo, RboError>
+where
+    Item: Eq + Hash,
+{
+    crate::state::validate_persistence(p)?;
+    check_duplicate_items(first, 0)?;
+    check_duplicate_items(second, 1)?;
+    if first
+        .iter()
+        .chain(second)
+        .any(|(_, w)| !w.is_finite() || *w < 0.0)
+    {
+        return Err(RboError::InvalidWeights);
+    }
+
+    let k = first.len().min(second.len());
+    // items seen in exactly one of the prefixes so far, with their weight
+    let mut pending: HashMap<&Item, f64> = HashMap::new();
+    let (mut shared, mut prefix_weight) = (0.0, 0.0);
+    let mut overlap = Vec::with_capacity(k + 1);
+    overlap.push(0.0);
+    for (d, ((a, wa), (b, wb))) in first.iter().zip(second).enumerate() {
+        prefix_weight += (wa + wb) / 2.0;
+        for (item, weight) in [(a, wa), (b, wb)] {
+            match pending.remove(item) {
+                Some(other) => shared += (weight + other) / 2.0,
+                None => {
+                    pending.insert(item, *weight);
+                }
+            }
+        }
+        let agreement = if prefix_weight > 0.0 {
+            shared / prefix_weight
+        } else {
+            0.0
+        };
+        overlap.push(agreement * (d + 1) as f64);
+    }
+    Ok(Rbo {
+        min: crate::state::min(&overlap, k, p),
+        residual: crate::state::residual(k as f64, k as f64, overlap[k], p),
+        extrapolated: crate::state::extrapolated(&overlap, k, k, p),
+    })
+}
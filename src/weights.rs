@@ -0,0 +1,67 @@
+use crate::state::MAX_SERIES_DEPTH;
+use crate::RboError;
+
+///
+/// The share of the total RBO evaluation weight that the item at rank
+/// `depth` (counting from 1) receives for persistence `p`. The agreement at
+/// depth `k` is weighted by `(1 - p) p^(k - 1)` and shared equally by its
+/// `k` ranks, so rank `depth` receives the sum over all `k >= depth` of
+/// `(1 - p) p^(k - 1) / k`. The weights of all ranks sum to 1, those of the
+/// top `depth` ranks to [`cumulative_weight`]. Rank 0 receives no weight.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if `p` is so close to 1 that the sum would exceed the
+///   depth limit
+///
+pub fn weight_at_depth(p: f64, depth: usize) -> Result<f64, RboError> {
+    crate::state::validate_persistence(p)?;
+    if depth == 0 {
+        return Ok(0.0);
+    }
+    let cutoff = crate::state::series_cutoff(p);
+    if cutoff > MAX_SERIES_DEPTH {
+        return Err(RboError::DepthLimitExceeded(cutoff));
+    }
+    // the terms shrink geometrically: sum them until they no longer count
+    let mut power = p.powf((depth - 1) as f64);
+    let mut weight = 0.0;
+    for k in depth.. {
+        let term = (1.0 - p) * power / k as f64;
+        weight += term;
+        if term <= weight * f64::EPSILON {
+            break;
+        }
+        power *= p;
+    }
+    Ok(weight)
+}
+
+///
+/// The share of the total RBO evaluation weight that the top `depth` ranks
+/// receive for persistence `p` (equation 21 in the paper), the sum of
+/// [`weight_at_depth`] over ranks 1 to `depth`. For example the top 10 ranks
+/// carry about 86% of the weight at p = 0.9, and the top 50 about 85% at
+/// p = 0.98.
+///
+/// # Errors
+///
+/// - Will return `Err` if `p` is not 0 <= p < 1
+/// - Will return `Err` if `depth` exceeds the depth limit
+///
+pub fn cumulative_weight(p: f64, depth: usize) -> Result<f64, RboError> {
+    crate::state::validate_persistence(p)?;
+    if depth > MAX_SERIES_DEPTH {
+        return Err(RboError::DepthLimitExceeded(depth));
+    }
+    if depth == 0 {
+        return Ok(0.0);
+    }
+    if p == 0.0 {
+        return Ok(1.0);
+    }
+    let d = depth as f64;
+    let sum: f64 = (1..depth).map(|i| p.powi(i as i32) / i as f64).sum();
+    Ok(1.0 - p.powf(d - 1.0) + (1.0 - p) / p * d * ((1.0 / (1.0 - p)).ln() - sum))
+}